@@ -10,19 +10,72 @@ use ratatui::{
 };
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use unicode_segmentation::UnicodeSegmentation;
 
 #[tokio::main]
 async fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
-    let terminal = ratatui::init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some((command, rest)) = args.split_first() {
+        return match command.as_str() {
+            "helper" => run_helper(rest),
+            "lint" => {
+                if run_lint(rest)? {
+                    std::process::exit(1);
+                }
+                Ok(())
+            }
+            "changes" => {
+                if run_changes(rest)? {
+                    std::process::exit(1);
+                }
+                Ok(())
+            }
+            "audit" => {
+                if run_audit(rest)? {
+                    std::process::exit(1);
+                }
+                Ok(())
+            }
+            "diff" => {
+                if run_diff(rest)? {
+                    std::process::exit(1);
+                }
+                Ok(())
+            }
+            "fmt" => {
+                if run_fmt(rest)? {
+                    std::process::exit(1);
+                }
+                Ok(())
+            }
+            _ => Err(color_eyre::eyre::eyre!("Unknown command: {}", command)),
+        };
+    }
+
+    let terminal = init_terminal();
 
     // Create app and run with async loading
     let result = App::run_with_loading(terminal).await;
-    ratatui::restore();
+    restore_terminal();
     result
 }
 
+/// Sets up the terminal like `ratatui::init`, and turns on bracketed paste so
+/// pasted text arrives as one event instead of a burst of key presses.
+fn init_terminal() -> DefaultTerminal {
+    let terminal = ratatui::init();
+    let _ = crossterm::execute!(std::io::stdout(), crossterm::event::EnableBracketedPaste);
+    terminal
+}
+
+fn restore_terminal() {
+    let _ = crossterm::execute!(std::io::stdout(), crossterm::event::DisableBracketedPaste);
+    ratatui::restore();
+}
+
 // Modern color theme inspired by OneHalfDark
 pub struct Theme;
 
@@ -51,6 +104,17 @@ pub struct App {
     current_tab: TabLocation,
     list_state: ListState,
     selected_plist: Option<PlistData>,
+    selected_access: Access,
+    selected_path: Option<PathBuf>,
+    pristine_plist: Option<PlistData>,
+    unsaved_prompt: Option<PendingNavigation>,
+    /// Navigation from the unsaved-changes prompt, waiting for its save to
+    /// go through.
+    navigation_after_save: Option<PendingNavigation>,
+    save_preview: Option<SavePreview>,
+    loaded_fingerprint: Option<FileFingerprint>,
+    conflict: Option<Conflict>,
+    edit_history: std::collections::HashMap<PathBuf, EditHistory>,
     user_agents_dir: PathBuf,
     global_agents_dir: PathBuf,
     apple_agents_dir: PathBuf,
@@ -58,15 +122,43 @@ pub struct App {
     current_field: FormField,
     editing: bool,
     editing_field: Option<FormField>,
-    edit_buffer: String,
+    edit_input: TextInput,
+    edit_error: Option<String>,
+    list_editor: Option<ListEditor>,
+    picker: Option<Picker>,
+    fix_menu: Option<FixMenu>,
+    /// The agent marked with x, to compare with another one.
+    compare_mark: Option<PathBuf>,
+    comparison: Option<Comparison>,
     status_message: String,
     status_timer: u32,
     filter_text: String,
     showing_exit_confirmation: bool,
+    pending_transfer: Option<AgentTransfer>,
+    pending_privileged_action: Option<PrivilegedAction>,
+    elevation_prompt: Option<PrivilegedAction>,
     form_scroll_offset: u16,
     log_view: LogView,
     log_scroll_offset: u16,
     log_lines: Vec<String>,
+    backup_entries: Vec<PathBuf>,
+    backup_list_state: ListState,
+    backup_diff: Vec<(DiffKind, String)>,
+    backup_diff_scroll: u16,
+    audit_agents: Vec<AuditedAgent>,
+    audit_list_state: ListState,
+    watcher: Option<AgentWatcher>,
+    /// The fingerprint of the open agent's file when the watcher found it
+    /// changed, so the flag clears once the form is reloaded or saved.
+    disk_changed: Option<FileFingerprint>,
+    acknowledged_snapshot: Snapshot,
+    /// Whether snapshot.json couldn't be read. It's left alone until the
+    /// changes are acknowledged.
+    snapshot_error: bool,
+    /// The agent directories as of the last watcher event.
+    current_snapshot: Snapshot,
+    /// Agents added, changed or removed since the acknowledged snapshot.
+    agent_changes: Vec<(PathBuf, AgentChange)>,
     loading: bool,
     loading_message: String,
     loading_progress: f32,
@@ -79,6 +171,7 @@ pub struct LaunchAgent {
     label: Option<String>,
     status: AgentStatus,
     enabled: bool,
+    diagnostics: Vec<Diagnostic>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -95,6 +188,16 @@ enum Focus {
     Sidebar,
     Form,
     Logs,
+    Backups,
+    Audit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DiffKind {
+    Hunk,
+    Context,
+    Added,
+    Removed,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -103,7 +206,7 @@ enum LogView {
     Stderr,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum TabLocation {
     User,
     Global,
@@ -123,6 +226,15 @@ impl TabLocation {
         }
     }
 
+    /// The name used in reports, such as `lam audit --json`.
+    fn name(&self) -> &'static str {
+        match self {
+            TabLocation::User => "user",
+            TabLocation::Global => "global",
+            TabLocation::Apple => "apple",
+        }
+    }
+
     fn get_display_name(&self) -> &str {
         match self {
             TabLocation::User => "👤 User",
@@ -130,9 +242,122 @@ impl TabLocation {
             TabLocation::Apple => "🍎 Apple",
         }
     }
+
+    /// The location an agent can be moved or copied to from this one. Apple
+    /// agents can only be copied, into User as an override.
+    fn transfer_target(&self, mode: TransferMode) -> Option<TabLocation> {
+        match (self, mode) {
+            (TabLocation::User, _) => Some(TabLocation::Global),
+            (TabLocation::Global, _) => Some(TabLocation::User),
+            (TabLocation::Apple, TransferMode::Copy) => Some(TabLocation::User),
+            (TabLocation::Apple, TransferMode::Move) => None,
+        }
+    }
+
+    /// What lam may do with the plist at `path` in this location.
+    fn access_for(&self, path: &Path) -> Access {
+        // SIP protects /System even from root
+        if *self == TabLocation::Apple || path.starts_with("/System") {
+            return Access::ReadOnly;
+        }
+
+        let directory_writable = path.parent().is_some_and(is_writable);
+        let file_writable = !path.exists() || is_writable(path);
+        if directory_writable && file_writable {
+            Access::Writable
+        } else if path.parent().is_some_and(|parent| {
+            PRIVILEGED_DIRECTORIES
+                .iter()
+                .any(|privileged| parent == Path::new(privileged))
+        }) {
+            Access::NeedsElevation
+        } else {
+            Access::ReadOnly
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Access {
+    ReadOnly,
+    Writable,
+    NeedsElevation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TransferMode {
+    Move,
+    Copy,
+}
+
+#[derive(Debug, Clone)]
+struct AgentTransfer {
+    mode: TransferMode,
+    filename: String,
+    from: TabLocation,
+    to: TabLocation,
+}
+
+impl AgentTransfer {
+    /// Writing into or removing from `/Library/LaunchAgents` needs root.
+    fn needs_root(&self) -> bool {
+        self.to == TabLocation::Global
+            || (self.mode == TransferMode::Move && self.from == TabLocation::Global)
+    }
+}
+
+/// Work that has to wait until the TUI is suspended and `sudo` has cached
+/// credentials.
+#[derive(Debug, Clone)]
+enum PrivilegedAction {
+    Transfer(AgentTransfer),
+    Save {
+        filename: String,
+        path: PathBuf,
+        contents: String,
+    },
+}
+
+/// Root-owned directories `lam helper` is allowed to write into.
+const PRIVILEGED_DIRECTORIES: [&str; 2] = ["/Library/LaunchAgents", "/Library/LaunchDaemons"];
+
+/// A pending Ctrl-S, showing what the write would change before it happens.
+#[derive(Debug, Clone)]
+struct SavePreview {
+    title: &'static str,
+    path: PathBuf,
+    contents: String,
+    diff: Vec<(DiffKind, String)>,
+    scroll: u16,
+}
+
+/// A semantic diff of two plists, from comparing two agents or an agent with
+/// one of its backups.
+#[derive(Debug, Clone)]
+struct Comparison {
+    old_name: String,
+    new_name: String,
+    changes: Vec<PlistChange>,
+    scroll: u16,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Navigation that would throw away unsaved form edits, held while the user
+/// decides what to do with them.
+#[derive(Debug, Clone, PartialEq)]
+enum PendingNavigation {
+    LoadAgent,
+    BrowseBackups,
+    SwitchTab(TabLocation),
+    /// Open the agent with this filename in a tab, from the audit.
+    OpenAgent(TabLocation, String),
+    /// Move or copy the agent selected in the sidebar.
+    Transfer(TransferMode),
+    /// Restore the open agent from the backup selected in the browser.
+    RestoreBackup,
+    Quit,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum LimitLoadToSessionType {
     Single(String),
@@ -145,7 +370,36 @@ impl Default for LimitLoadToSessionType {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+/// A plist value as written in the file. Dictionaries keep their keys in file
+/// order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlistValue {
+    String(String),
+    Integer(i64),
+    Real(f64),
+    Boolean(bool),
+    Date(String),
+    Data(String),
+    Array(Vec<PlistValue>),
+    Dict(Vec<(String, PlistValue)>),
+}
+
+impl PlistValue {
+    fn kind(&self) -> &'static str {
+        match self {
+            PlistValue::String(_) => "string",
+            PlistValue::Integer(_) => "integer",
+            PlistValue::Real(_) => "real",
+            PlistValue::Boolean(_) => "boolean",
+            PlistValue::Date(_) => "date",
+            PlistValue::Data(_) => "data",
+            PlistValue::Array(_) => "array",
+            PlistValue::Dict(_) => "dict",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct PlistData {
     #[serde(rename = "Label")]
     pub label: Option<String>,
@@ -166,7 +420,8 @@ pub struct PlistData {
     #[serde(rename = "WorkingDirectory")]
     pub working_directory: Option<String>,
     #[serde(rename = "EnvironmentVariables")]
-    pub environment_variables: Option<std::collections::HashMap<String, String>>,
+    /// In file order; duplicate names are kept so they can be flagged.
+    pub environment_variables: Option<Vec<(String, String)>>,
     #[serde(rename = "LimitLoadToSessionType")]
     pub limit_load_to_session_type: Option<LimitLoadToSessionType>,
     #[serde(rename = "AbandonProcessGroup")]
@@ -177,12 +432,21 @@ pub struct PlistData {
     pub throttle_interval: Option<i32>,
     #[serde(rename = "POSIXSpawnType")]
     pub posix_spawn_type: Option<String>,
+    #[serde(rename = "ProcessType")]
+    pub process_type: Option<String>,
     #[serde(rename = "EnablePressuredExit")]
     pub enable_pressured_exit: Option<bool>,
     #[serde(rename = "EnableTransactions")]
     pub enable_transactions: Option<bool>,
     #[serde(rename = "EventMonitor")]
     pub event_monitor: Option<bool>,
+    /// Keys lam doesn't edit, and known keys holding a value of the wrong
+    /// type. Kept as-is so saving doesn't drop them.
+    #[serde(skip)]
+    pub extra: Vec<(String, PlistValue)>,
+    /// Top-level keys in the order the file had them.
+    #[serde(skip)]
+    pub key_order: Vec<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -202,2625 +466,11022 @@ enum FormField {
     AssociatedBundleIdentifiers,
     ThrottleInterval,
     POSIXSpawnType,
+    ProcessType,
     EnablePressuredExit,
     EnableTransactions,
     EventMonitor,
 }
 
-impl App {
-    pub async fn new() -> Result<Self> {
-        let user_agents_dir = TabLocation::User.get_directory()?;
-        let global_agents_dir = TabLocation::Global.get_directory()?;
-        let apple_agents_dir = TabLocation::Apple.get_directory()?;
-
-        // Create the app in loading state first
-        let mut app = Self {
-            running: false,
-            event_stream: EventStream::new(),
-            user_agents: Vec::new(),
-            global_agents: Vec::new(),
-            apple_agents: Vec::new(),
-            current_tab: TabLocation::User,
-            list_state: ListState::default(),
-            selected_plist: None,
-            user_agents_dir,
-            global_agents_dir,
-            apple_agents_dir,
-            focus: Focus::Sidebar,
-            current_field: FormField::Label,
-            editing: false,
-            editing_field: None,
-            edit_buffer: String::new(),
-            status_message: String::new(),
-            status_timer: 0,
-            filter_text: String::new(),
-            showing_exit_confirmation: false,
-            form_scroll_offset: 0,
-            log_view: LogView::Stdout,
-            log_scroll_offset: 0,
-            log_lines: Vec::new(),
-            loading: true,
-            loading_message: "Initializing Launch Agent Manager...".to_string(),
-            loading_progress: 0.0,
-            loading_step: 1,
-        };
-
-        // Load agents with progress updates
-        app.loading_message = "📂 Loading User LaunchAgents...".to_string();
-        app.loading_progress = 0.1;
-        let user_agents = Self::load_launch_agents(&app.user_agents_dir)?;
+impl FormField {
+    /// Every field, in the order the form shows them.
+    const ALL: [FormField; 19] = [
+        FormField::Label,
+        FormField::Program,
+        FormField::ProgramArguments,
+        FormField::StartInterval,
+        FormField::ThrottleInterval,
+        FormField::RunAtLoad,
+        FormField::KeepAlive,
+        FormField::AbandonProcessGroup,
+        FormField::StandardOutPath,
+        FormField::StandardErrorPath,
+        FormField::WorkingDirectory,
+        FormField::POSIXSpawnType,
+        FormField::ProcessType,
+        FormField::EnablePressuredExit,
+        FormField::EnableTransactions,
+        FormField::EventMonitor,
+        FormField::LimitLoadToSessionType,
+        FormField::AssociatedBundleIdentifiers,
+        FormField::EnvironmentVariables,
+    ];
+
+    /// Array and dictionary fields are edited in the list editor.
+    fn is_list(&self) -> bool {
+        matches!(
+            self,
+            FormField::ProgramArguments
+                | FormField::AssociatedBundleIdentifiers
+                | FormField::EnvironmentVariables
+        )
+    }
 
-        app.loading_message = "🌐 Loading Global LaunchAgents...".to_string();
-        app.loading_progress = 0.4;
-        let global_agents = Self::load_launch_agents(&app.global_agents_dir)?;
+    /// Fields with a known set of values are chosen from a picker.
+    fn has_choices(&self) -> bool {
+        matches!(
+            self,
+            FormField::POSIXSpawnType | FormField::ProcessType | FormField::LimitLoadToSessionType
+        )
+    }
 
-        app.loading_message = "🍎 Loading Apple LaunchAgents...".to_string();
-        app.loading_progress = 0.7;
-        let apple_agents = Self::load_launch_agents(&app.apple_agents_dir)?;
+    fn is_boolean(&self) -> bool {
+        matches!(
+            self,
+            FormField::RunAtLoad
+                | FormField::KeepAlive
+                | FormField::AbandonProcessGroup
+                | FormField::EnablePressuredExit
+                | FormField::EnableTransactions
+                | FormField::EventMonitor
+        )
+    }
 
-        app.loading_message = "✨ Finalizing interface...".to_string();
-        app.loading_progress = 0.9;
+    fn next(&self) -> FormField {
+        let i = Self::ALL
+            .iter()
+            .position(|field| field == self)
+            .unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()].clone()
+    }
 
-        // Update the app with loaded data
-        app.user_agents = user_agents;
-        app.global_agents = global_agents;
-        app.apple_agents = apple_agents;
+    fn previous(&self) -> FormField {
+        let i = Self::ALL
+            .iter()
+            .position(|field| field == self)
+            .unwrap_or(0);
+        Self::ALL[(i + Self::ALL.len() - 1) % Self::ALL.len()].clone()
+    }
 
-        let mut list_state = ListState::default();
-        if !app.user_agents.is_empty() {
-            list_state.select(Some(0));
+    /// The plist key the field is stored under.
+    fn key(&self) -> &'static str {
+        match self {
+            FormField::Label => "Label",
+            FormField::ProgramArguments => "ProgramArguments",
+            FormField::Program => "Program",
+            FormField::StartInterval => "StartInterval",
+            FormField::RunAtLoad => "RunAtLoad",
+            FormField::KeepAlive => "KeepAlive",
+            FormField::StandardOutPath => "StandardOutPath",
+            FormField::StandardErrorPath => "StandardErrorPath",
+            FormField::WorkingDirectory => "WorkingDirectory",
+            FormField::EnvironmentVariables => "EnvironmentVariables",
+            FormField::LimitLoadToSessionType => "LimitLoadToSessionType",
+            FormField::AbandonProcessGroup => "AbandonProcessGroup",
+            FormField::AssociatedBundleIdentifiers => "AssociatedBundleIdentifiers",
+            FormField::ThrottleInterval => "ThrottleInterval",
+            FormField::POSIXSpawnType => "POSIXSpawnType",
+            FormField::ProcessType => "ProcessType",
+            FormField::EnablePressuredExit => "EnablePressuredExit",
+            FormField::EnableTransactions => "EnableTransactions",
+            FormField::EventMonitor => "EventMonitor",
         }
-        app.list_state = list_state;
-
-        // Complete loading
-        app.loading = false;
-        app.loading_progress = 1.0;
-
-        Ok(app)
     }
 
-    pub fn new_with_loading() -> Self {
-        Self {
-            running: false,
-            event_stream: EventStream::new(),
-            user_agents: Vec::new(),
-            global_agents: Vec::new(),
-            apple_agents: Vec::new(),
-            current_tab: TabLocation::User,
-            list_state: ListState::default(),
-            selected_plist: None,
-            user_agents_dir: PathBuf::new(),
-            global_agents_dir: PathBuf::new(),
-            apple_agents_dir: PathBuf::new(),
-            focus: Focus::Sidebar,
-            current_field: FormField::Label,
-            editing: false,
-            editing_field: None,
-            edit_buffer: String::new(),
-            status_message: String::new(),
-            status_timer: 0,
-            filter_text: String::new(),
-            showing_exit_confirmation: false,
-            form_scroll_offset: 0,
-            log_view: LogView::Stdout,
-            log_scroll_offset: 0,
-            log_lines: Vec::new(),
-            loading: true,
-            loading_message: "🚀 Starting Launch Agent Manager...".to_string(),
-            loading_progress: 0.0,
-            loading_step: 0,
+    fn name(&self) -> &'static str {
+        match self {
+            FormField::Label => "Label",
+            FormField::ProgramArguments => "Program Arguments",
+            FormField::Program => "Program",
+            FormField::StartInterval => "Start Interval",
+            FormField::RunAtLoad => "Run At Load",
+            FormField::KeepAlive => "Keep Alive",
+            FormField::StandardOutPath => "Standard Out Path",
+            FormField::StandardErrorPath => "Standard Error Path",
+            FormField::WorkingDirectory => "Working Directory",
+            FormField::EnvironmentVariables => "Environment Variables",
+            FormField::LimitLoadToSessionType => "Limit Load To Session Type",
+            FormField::AbandonProcessGroup => "Abandon Process Group",
+            FormField::AssociatedBundleIdentifiers => "Associated Bundle Identifiers",
+            FormField::ThrottleInterval => "Throttle Interval",
+            FormField::POSIXSpawnType => "POSIX Spawn Type",
+            FormField::ProcessType => "Process Type",
+            FormField::EnablePressuredExit => "Enable Pressured Exit",
+            FormField::EnableTransactions => "Enable Transactions",
+            FormField::EventMonitor => "Event Monitor",
         }
     }
+}
 
-    fn get_current_agents(&self) -> &Vec<LaunchAgent> {
-        match self.current_tab {
-            TabLocation::User => &self.user_agents,
-            TabLocation::Global => &self.global_agents,
-            TabLocation::Apple => &self.apple_agents,
-        }
+impl PlistData {
+    /// The field's value as the editor shows it, one item per line for lists.
+    /// The value of a key the form doesn't edit.
+    fn extra_value(&self, key: &str) -> Option<&PlistValue> {
+        self.extra
+            .iter()
+            .find(|(other, _)| other == key)
+            .map(|(_, value)| value)
     }
 
-    fn get_current_agents_mut(&mut self) -> &mut Vec<LaunchAgent> {
-        match self.current_tab {
-            TabLocation::User => &mut self.user_agents,
-            TabLocation::Global => &mut self.global_agents,
-            TabLocation::Apple => &mut self.apple_agents,
+    /// Sets or, given `None`, removes a key the form doesn't edit.
+    fn set_extra(&mut self, key: &str, value: Option<PlistValue>) {
+        let existing = self.extra.iter().position(|(other, _)| other == key);
+        match (existing, value) {
+            (Some(i), Some(value)) => self.extra[i].1 = value,
+            (Some(i), None) => {
+                self.extra.remove(i);
+            }
+            (None, Some(value)) => self.extra.push((key.to_string(), value)),
+            (None, None) => {}
         }
     }
 
-    fn get_current_directory(&self) -> &PathBuf {
-        match self.current_tab {
-            TabLocation::User => &self.user_agents_dir,
-            TabLocation::Global => &self.global_agents_dir,
-            TabLocation::Apple => &self.apple_agents_dir,
+    fn field_text(&self, field: &FormField) -> String {
+        match field {
+            FormField::Label => self.label.clone().unwrap_or_default(),
+            FormField::Program => self.program.clone().unwrap_or_default(),
+            FormField::StartInterval => self
+                .start_interval
+                .map(|i| i.to_string())
+                .unwrap_or_default(),
+            FormField::ThrottleInterval => self
+                .throttle_interval
+                .map(|i| i.to_string())
+                .unwrap_or_default(),
+            FormField::RunAtLoad => bool_text(self.run_at_load).to_string(),
+            FormField::KeepAlive => bool_text(self.keep_alive).to_string(),
+            FormField::AbandonProcessGroup => bool_text(self.abandon_process_group).to_string(),
+            FormField::EnablePressuredExit => bool_text(self.enable_pressured_exit).to_string(),
+            FormField::EnableTransactions => bool_text(self.enable_transactions).to_string(),
+            FormField::EventMonitor => bool_text(self.event_monitor).to_string(),
+            FormField::StandardOutPath => self.standard_out_path.clone().unwrap_or_default(),
+            FormField::StandardErrorPath => self.standard_error_path.clone().unwrap_or_default(),
+            FormField::WorkingDirectory => self.working_directory.clone().unwrap_or_default(),
+            FormField::POSIXSpawnType => self.posix_spawn_type.clone().unwrap_or_default(),
+            FormField::ProcessType => self.process_type.clone().unwrap_or_default(),
+            FormField::ProgramArguments => {
+                if let Some(args) = &self.program_arguments {
+                    args.join("\n")
+                } else {
+                    String::new()
+                }
+            }
+            FormField::AssociatedBundleIdentifiers => {
+                if let Some(ids) = &self.associated_bundle_identifiers {
+                    ids.join("\n")
+                } else {
+                    String::new()
+                }
+            }
+            FormField::LimitLoadToSessionType => match &self.limit_load_to_session_type {
+                Some(LimitLoadToSessionType::Single(s)) => s.clone(),
+                Some(LimitLoadToSessionType::Multiple(v)) => v.join("\n"),
+                None => String::new(),
+            },
+            FormField::EnvironmentVariables => {
+                if let Some(env_vars) = &self.environment_variables {
+                    env_vars
+                        .iter()
+                        .map(|(k, v)| format!("{}={}", k, v))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                } else {
+                    String::new()
+                }
+            }
         }
     }
 
-    fn load_launch_agents(dir: &PathBuf) -> Result<Vec<LaunchAgent>> {
-        let mut agents = Vec::new();
-
-        if dir.exists() {
-            for entry in fs::read_dir(dir)? {
-                let entry = entry?;
-                let path = entry.path();
-
-                if path.is_file()
-                    && path.extension().is_some_and(|ext| ext == "plist")
-                    && let Some(filename) = path.file_name().and_then(|n| n.to_str())
-                {
-                    let label = Self::extract_label_from_file(&path)
-                        .unwrap_or_else(|| filename.replace(".plist", ""));
-
-                    let status = Self::check_agent_status(&label);
-                    let enabled = Self::check_agent_enabled(&label);
+    /// Parses text typed into the editor back into the field, leaving the
+    /// plist untouched and describing the problem when it isn't valid.
+    fn set_field_text(&mut self, field: &FormField, text: &str) -> Result<(), String> {
+        let string = || (!text.is_empty()).then(|| text.to_string());
+        let lines = || -> Vec<String> {
+            text.lines()
+                .map(|line| line.trim().to_string())
+                .filter(|line| !line.is_empty())
+                .collect()
+        };
 
-                    agents.push(LaunchAgent {
-                        filename: filename.to_string(),
-                        label: Some(label),
-                        status,
-                        enabled,
-                    });
+        match field {
+            FormField::Label => {
+                if text.trim().is_empty() {
+                    return Err(
+                        "Label is required, launchd won't load an agent without one".to_string()
+                    );
+                }
+                self.label = Some(text.to_string());
+            }
+            FormField::Program => self.program = string(),
+            FormField::StartInterval => self.start_interval = parse_interval(field, text)?,
+            FormField::ThrottleInterval => self.throttle_interval = parse_interval(field, text)?,
+            FormField::RunAtLoad
+            | FormField::KeepAlive
+            | FormField::AbandonProcessGroup
+            | FormField::EnablePressuredExit
+            | FormField::EnableTransactions
+            | FormField::EventMonitor => {
+                let value = match text.trim() {
+                    "true" => Some(true),
+                    "false" => Some(false),
+                    "" => None,
+                    _ => return Err(format!("{} must be true or false", field.name())),
+                };
+                if let Some(slot) = self.bool_field_mut(field) {
+                    *slot = value;
+                }
+            }
+            FormField::StandardOutPath => self.standard_out_path = string(),
+            FormField::StandardErrorPath => self.standard_error_path = string(),
+            FormField::WorkingDirectory => self.working_directory = string(),
+            FormField::POSIXSpawnType => self.posix_spawn_type = string(),
+            FormField::ProcessType => self.process_type = string(),
+            FormField::ProgramArguments => {
+                let args = lines();
+                self.program_arguments = (!args.is_empty()).then_some(args);
+            }
+            FormField::AssociatedBundleIdentifiers => {
+                let ids = lines();
+                self.associated_bundle_identifiers = (!ids.is_empty()).then_some(ids);
+            }
+            FormField::LimitLoadToSessionType => {
+                let mut sessions = lines();
+                self.limit_load_to_session_type = match sessions.len() {
+                    0 => None,
+                    1 => sessions.pop().map(LimitLoadToSessionType::Single),
+                    _ => Some(LimitLoadToSessionType::Multiple(sessions)),
+                };
+            }
+            FormField::EnvironmentVariables => {
+                let mut env_vars = Vec::new();
+                for (number, line) in text.lines().enumerate() {
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    // Values are kept verbatim, whitespace included
+                    let (name, value) = line
+                        .split_once('=')
+                        .ok_or_else(|| format!("Line {} needs the form NAME=value", number + 1))?;
+                    validate_env_name(name.trim())?;
+                    env_vars.push((name.trim().to_string(), value.to_string()));
                 }
+                self.environment_variables = (!env_vars.is_empty()).then_some(env_vars);
             }
         }
+        Ok(())
+    }
 
-        agents.sort_by(|a, b| a.filename.cmp(&b.filename));
-        Ok(agents)
+    fn bool_field_mut(&mut self, field: &FormField) -> Option<&mut Option<bool>> {
+        match field {
+            FormField::RunAtLoad => Some(&mut self.run_at_load),
+            FormField::KeepAlive => Some(&mut self.keep_alive),
+            FormField::AbandonProcessGroup => Some(&mut self.abandon_process_group),
+            FormField::EnablePressuredExit => Some(&mut self.enable_pressured_exit),
+            FormField::EnableTransactions => Some(&mut self.enable_transactions),
+            FormField::EventMonitor => Some(&mut self.event_monitor),
+            _ => None,
+        }
     }
 
-    fn extract_label_from_file(path: &PathBuf) -> Option<String> {
-        fs::read_to_string(path)
-            .ok()
-            .and_then(|content| parse_plist_xml(&content).ok())
-            .map(|plist| plist.label)?
+    /// Maps a parsed plist onto the fields lam edits. Everything else ends up
+    /// in `extra`.
+    fn from_value(value: &PlistValue) -> Result<PlistData> {
+        let PlistValue::Dict(entries) = value else {
+            return Err(color_eyre::eyre::eyre!(
+                "Expected a dictionary at the top of the plist, found {}",
+                value.kind()
+            ));
+        };
+        let mut plist = PlistData::default();
+        for (key, value) in entries {
+            if !plist.set_value(key, value) {
+                plist.extra.push((key.clone(), value.clone()));
+            }
+            if !plist.key_order.contains(key) {
+                plist.key_order.push(key.clone());
+            }
+        }
+        Ok(plist)
     }
 
-    fn check_agent_status(label: &str) -> AgentStatus {
-        // Check if agent is running using launchctl
-        let uid = std::process::Command::new("id")
-            .arg("-u")
-            .output()
-            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
-            .unwrap_or_else(|_| "501".to_string());
-
-        if let Ok(output) = std::process::Command::new("launchctl")
-            .args(["print", &format!("gui/{}/{}", uid, label)])
-            .output()
-        {
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            match output_str.trim() {
-                "No such service" => return AgentStatus::Stopped,
-                _ if output_str.contains("state = running") => return AgentStatus::Running,
-                _ if output_str.contains("state = stopped") => return AgentStatus::Stopped,
-                _ => return AgentStatus::Error,
+    /// The plist as a value tree. Keys keep the file's order, and keys that
+    /// weren't in the file follow in launchd order.
+    fn to_value(&self) -> PlistValue {
+        let empty = PlistData::default();
+        let mut entries: Vec<(String, PlistValue)> = FormField::ALL
+            .iter()
+            .filter_map(|field| {
+                let value = self.field_value(field)?;
+                Some((field.key().to_string(), value))
+            })
+            .collect();
+        for (key, value) in &self.extra {
+            // A field set in the form replaces a mistyped value for its key
+            if !FormField::ALL
+                .iter()
+                .any(|field| field.key() == key && !self.field_eq(&empty, field))
+            {
+                entries.push((key.clone(), value.clone()));
             }
         }
-        AgentStatus::Unknown
+        entries.sort_by_key(|(key, _)| {
+            match self.key_order.iter().position(|other| other == key) {
+                Some(position) => (0, position),
+                None => (1, launchd_key_rank(key)),
+            }
+        });
+        PlistValue::Dict(entries)
     }
 
-    fn check_agent_enabled(label: &str) -> bool {
-        let uid = std::process::Command::new("id")
-            .arg("-u")
-            .output()
-            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
-            .unwrap_or_else(|_| "501".to_string());
+    fn field_value(&self, field: &FormField) -> Option<PlistValue> {
+        let string = |value: &Option<String>| value.clone().map(PlistValue::String);
+        let strings = |values: &Option<Vec<String>>| {
+            values.as_ref().map(|values| {
+                PlistValue::Array(values.iter().cloned().map(PlistValue::String).collect())
+            })
+        };
+        let integer = |value: Option<i32>| value.map(|n| PlistValue::Integer(n.into()));
+        let boolean = |value: Option<bool>| value.map(PlistValue::Boolean);
+        match field {
+            FormField::Label => string(&self.label),
+            FormField::ProgramArguments => strings(&self.program_arguments),
+            FormField::Program => string(&self.program),
+            FormField::StartInterval => integer(self.start_interval),
+            FormField::RunAtLoad => boolean(self.run_at_load),
+            FormField::KeepAlive => boolean(self.keep_alive),
+            FormField::StandardOutPath => string(&self.standard_out_path),
+            FormField::StandardErrorPath => string(&self.standard_error_path),
+            FormField::WorkingDirectory => string(&self.working_directory),
+            FormField::EnvironmentVariables => {
+                self.environment_variables.as_ref().map(|env_vars| {
+                    PlistValue::Dict(
+                        env_vars
+                            .iter()
+                            .map(|(name, value)| (name.clone(), PlistValue::String(value.clone())))
+                            .collect(),
+                    )
+                })
+            }
+            FormField::LimitLoadToSessionType => {
+                self.limit_load_to_session_type
+                    .as_ref()
+                    .map(|sessions| match sessions {
+                        LimitLoadToSessionType::Single(session) => {
+                            PlistValue::String(session.clone())
+                        }
+                        LimitLoadToSessionType::Multiple(sessions) => PlistValue::Array(
+                            sessions.iter().cloned().map(PlistValue::String).collect(),
+                        ),
+                    })
+            }
+            FormField::AbandonProcessGroup => boolean(self.abandon_process_group),
+            FormField::AssociatedBundleIdentifiers => strings(&self.associated_bundle_identifiers),
+            FormField::ThrottleInterval => integer(self.throttle_interval),
+            FormField::POSIXSpawnType => string(&self.posix_spawn_type),
+            FormField::ProcessType => string(&self.process_type),
+            FormField::EnablePressuredExit => boolean(self.enable_pressured_exit),
+            FormField::EnableTransactions => boolean(self.enable_transactions),
+            FormField::EventMonitor => boolean(self.event_monitor),
+        }
+    }
 
-        // Check if agent is enabled/loaded
-        if let Ok(output) = std::process::Command::new("launchctl")
-            .args(["print-disabled", &format!("gui/{}", uid)])
-            .output()
-        {
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            !output_str.contains(&format!("\"{}\": false", label))
-        } else {
-            // If launchctl command fails, assume it's not enabled
-            false
+    /// Stores `value` in the field for `key`, returning false when lam has no
+    /// field for the key or the value has the wrong type for it.
+    fn set_value(&mut self, key: &str, value: &PlistValue) -> bool {
+        match (key, value) {
+            ("Label", PlistValue::String(s)) => self.label = Some(s.clone()),
+            ("Program", PlistValue::String(s)) => self.program = Some(s.clone()),
+            ("StandardOutPath", PlistValue::String(s)) => self.standard_out_path = Some(s.clone()),
+            ("StandardErrorPath", PlistValue::String(s)) => {
+                self.standard_error_path = Some(s.clone())
+            }
+            ("WorkingDirectory", PlistValue::String(s)) => self.working_directory = Some(s.clone()),
+            ("POSIXSpawnType", PlistValue::String(s)) => self.posix_spawn_type = Some(s.clone()),
+            ("ProcessType", PlistValue::String(s)) => self.process_type = Some(s.clone()),
+            ("StartInterval", PlistValue::Integer(n)) => match i32::try_from(*n) {
+                Ok(n) => self.start_interval = Some(n),
+                Err(_) => return false,
+            },
+            ("ThrottleInterval", PlistValue::Integer(n)) => match i32::try_from(*n) {
+                Ok(n) => self.throttle_interval = Some(n),
+                Err(_) => return false,
+            },
+            ("RunAtLoad", PlistValue::Boolean(b)) => self.run_at_load = Some(*b),
+            ("KeepAlive", PlistValue::Boolean(b)) => self.keep_alive = Some(*b),
+            ("AbandonProcessGroup", PlistValue::Boolean(b)) => {
+                self.abandon_process_group = Some(*b)
+            }
+            ("EnablePressuredExit", PlistValue::Boolean(b)) => {
+                self.enable_pressured_exit = Some(*b)
+            }
+            ("EnableTransactions", PlistValue::Boolean(b)) => self.enable_transactions = Some(*b),
+            ("EventMonitor", PlistValue::Boolean(b)) => self.event_monitor = Some(*b),
+            ("ProgramArguments", PlistValue::Array(items)) => match string_items(items) {
+                Some(args) => self.program_arguments = Some(args),
+                None => return false,
+            },
+            ("AssociatedBundleIdentifiers", PlistValue::Array(items)) => {
+                match string_items(items) {
+                    Some(ids) => self.associated_bundle_identifiers = Some(ids),
+                    None => return false,
+                }
+            }
+            ("LimitLoadToSessionType", PlistValue::String(s)) => {
+                self.limit_load_to_session_type = Some(LimitLoadToSessionType::Single(s.clone()))
+            }
+            ("LimitLoadToSessionType", PlistValue::Array(items)) => match string_items(items) {
+                Some(sessions) => {
+                    self.limit_load_to_session_type =
+                        Some(LimitLoadToSessionType::Multiple(sessions))
+                }
+                None => return false,
+            },
+            ("EnvironmentVariables", PlistValue::Dict(entries)) => {
+                let env_vars: Option<Vec<(String, String)>> = entries
+                    .iter()
+                    .map(|(name, value)| match value {
+                        PlistValue::String(value) => Some((name.clone(), value.clone())),
+                        _ => None,
+                    })
+                    .collect();
+                match env_vars {
+                    Some(env_vars) => self.environment_variables = Some(env_vars),
+                    None => return false,
+                }
+            }
+            _ => return false,
         }
+        true
     }
 
-    fn load_selected_plist(&mut self) -> Result<()> {
-        if let Some(selected) = self.list_state.selected() {
-            let filtered_agents = self.get_filtered_agents();
-            if let Some(agent) = filtered_agents.get(selected) {
-                let file_path = self.get_current_directory().join(&agent.filename);
-                let content = fs::read_to_string(file_path)?;
+    /// Compares one field, telling an unset value apart from an explicit default.
+    fn field_eq(&self, other: &PlistData, field: &FormField) -> bool {
+        let mut probe = self.clone();
+        probe.copy_field(other, field);
+        probe == *self
+    }
 
-                let plist_data = self.parse_plist(&content)?;
-                self.selected_plist = Some(plist_data);
-                self.form_scroll_offset = 0;
-                self.log_lines.clear();
-                self.log_scroll_offset = 0;
+    fn copy_field(&mut self, from: &PlistData, field: &FormField) {
+        match field {
+            FormField::Label => self.label = from.label.clone(),
+            FormField::ProgramArguments => self.program_arguments = from.program_arguments.clone(),
+            FormField::Program => self.program = from.program.clone(),
+            FormField::StartInterval => self.start_interval = from.start_interval,
+            FormField::RunAtLoad => self.run_at_load = from.run_at_load,
+            FormField::KeepAlive => self.keep_alive = from.keep_alive,
+            FormField::StandardOutPath => self.standard_out_path = from.standard_out_path.clone(),
+            FormField::StandardErrorPath => {
+                self.standard_error_path = from.standard_error_path.clone()
+            }
+            FormField::WorkingDirectory => self.working_directory = from.working_directory.clone(),
+            FormField::EnvironmentVariables => {
+                self.environment_variables = from.environment_variables.clone()
             }
+            FormField::LimitLoadToSessionType => {
+                self.limit_load_to_session_type = from.limit_load_to_session_type.clone()
+            }
+            FormField::AbandonProcessGroup => {
+                self.abandon_process_group = from.abandon_process_group
+            }
+            FormField::AssociatedBundleIdentifiers => {
+                self.associated_bundle_identifiers = from.associated_bundle_identifiers.clone()
+            }
+            FormField::ThrottleInterval => self.throttle_interval = from.throttle_interval,
+            FormField::POSIXSpawnType => self.posix_spawn_type = from.posix_spawn_type.clone(),
+            FormField::ProcessType => self.process_type = from.process_type.clone(),
+            FormField::EnablePressuredExit => {
+                self.enable_pressured_exit = from.enable_pressured_exit
+            }
+            FormField::EnableTransactions => self.enable_transactions = from.enable_transactions,
+            FormField::EventMonitor => self.event_monitor = from.event_monitor,
         }
-        Ok(())
     }
+}
 
-    pub fn parse_plist(&self, content: &str) -> Result<PlistData> {
-        parse_plist_xml(content)
+/// An editable text buffer with a grapheme-aware cursor, readline-style
+/// bindings and shift-selection. Newlines in typed or pasted text become
+/// spaces.
+#[derive(Debug, Clone, Default)]
+struct TextInput {
+    text: String,
+    /// Byte offset of the cursor, always on a grapheme boundary.
+    cursor: usize,
+    /// Where a shift-selection started, if one is active.
+    anchor: Option<usize>,
+}
+
+impl TextInput {
+    fn new(text: String) -> Self {
+        Self {
+            cursor: text.len(),
+            text,
+            anchor: None,
+        }
     }
 
-    pub async fn run_with_loading(mut terminal: DefaultTerminal) -> Result<()> {
-        // Create app with loading state
-        let mut app = App::new_with_loading();
-        app.running = true;
+    fn text(&self) -> &str {
+        &self.text
+    }
 
-        // Show loading screen and load data asynchronously
-        let loading_task = tokio::spawn(async move { App::new().await });
+    /// The selected byte range, if the selection isn't empty.
+    fn selection(&self) -> Option<std::ops::Range<usize>> {
+        let anchor = self.anchor?;
+        (anchor != self.cursor).then(|| anchor.min(self.cursor)..anchor.max(self.cursor))
+    }
 
-        // Keep showing loading screen until data is loaded
-        loop {
-            terminal.draw(|frame| app.draw_loading_screen(frame))?;
+    /// Handles an editing key, returning false for keys the input doesn't use.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
 
-            // Handle any key events during loading (like quit)
-            if let Ok(event) = tokio::time::timeout(
-                tokio::time::Duration::from_millis(50),
-                app.event_stream.next(),
-            )
-            .await
-            {
-                if let Some(Ok(crossterm::event::Event::Key(key))) = event {
-                    if matches!(
-                        key.code,
-                        crossterm::event::KeyCode::Esc | crossterm::event::KeyCode::Char('q')
-                    ) && key.kind == crossterm::event::KeyEventKind::Press
-                    {
-                        app.running = false;
-                        break;
-                    }
-                }
+        match key.code {
+            KeyCode::Char('a') if ctrl => self.move_to(self.line_start(), shift),
+            KeyCode::Char('e') if ctrl => self.move_to(self.line_end(), shift),
+            KeyCode::Char('w') if ctrl => self.delete_to(self.word_start()),
+            KeyCode::Char('u') if ctrl => self.delete_to(self.line_start()),
+            KeyCode::Char('k') if ctrl => self.delete_to(self.line_end()),
+            KeyCode::Char('b') if alt => self.move_to(self.word_start(), shift),
+            KeyCode::Char('f') if alt => self.move_to(self.word_end(), shift),
+            KeyCode::Char(_) if ctrl || alt => return false,
+            KeyCode::Char(c) => self.insert(&c.to_string()),
+            KeyCode::Backspace if ctrl || alt => self.delete_to(self.word_start()),
+            KeyCode::Backspace => {
+                let previous = self.previous_boundary();
+                self.delete_to(previous);
             }
-
-            // Check if loading is complete
-            if loading_task.is_finished() {
-                match loading_task.await {
-                    Ok(Ok(loaded_app)) => {
-                        app = loaded_app;
-                        app.running = true;
-                        break;
-                    }
-                    Ok(Err(e)) => return Err(e),
-                    Err(e) => return Err(color_eyre::eyre::eyre!("Loading task failed: {}", e)),
-                }
+            KeyCode::Delete => {
+                let next = self.next_boundary();
+                self.delete_to(next);
             }
-
-            // Update loading animation
-            app.loading_step = app.loading_step.wrapping_add(1);
-
-            // Small delay for animation
-            tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+            KeyCode::Left if ctrl || alt => self.move_to(self.word_start(), shift),
+            KeyCode::Right if ctrl || alt => self.move_to(self.word_end(), shift),
+            KeyCode::Left => self.move_to(self.previous_boundary(), shift),
+            KeyCode::Right => self.move_to(self.next_boundary(), shift),
+            KeyCode::Home => self.move_to(self.line_start(), shift),
+            KeyCode::End => self.move_to(self.line_end(), shift),
+            _ => return false,
         }
+        true
+    }
 
-        // Run the main application if not quit during loading
-        if app.running {
-            app.run(terminal).await
+    /// Inserts `text` at the cursor, replacing the selection, with newlines
+    /// turned into spaces.
+    fn insert(&mut self, text: &str) {
+        self.delete_selection();
+        let text = text.replace("\r\n", " ").replace(['\n', '\r'], " ");
+        self.text.insert_str(self.cursor, &text);
+        self.cursor += text.len();
+    }
+
+    fn move_to(&mut self, position: usize, extend_selection: bool) {
+        if extend_selection {
+            self.anchor.get_or_insert(self.cursor);
         } else {
-            Ok(())
+            self.anchor = None;
         }
+        self.cursor = position;
     }
 
-    pub async fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
-        self.running = true;
-        while self.running {
-            terminal.draw(|frame| self.draw(frame))?;
-            self.handle_crossterm_events().await?;
+    /// Deletes from the cursor to `position`, or the selection if there is one.
+    fn delete_to(&mut self, position: usize) {
+        if self.delete_selection() {
+            return;
         }
-        Ok(())
+        let range = position.min(self.cursor)..position.max(self.cursor);
+        self.cursor = range.start;
+        self.text.replace_range(range, "");
     }
 
-    fn draw(&mut self, frame: &mut Frame) {
-        // If loading, show loading screen instead
-        if self.loading {
-            self.draw_loading_screen(frame);
-            return;
+    fn delete_selection(&mut self) -> bool {
+        let selection = self.selection();
+        self.anchor = None;
+        match selection {
+            Some(range) => {
+                self.cursor = range.start;
+                self.text.replace_range(range, "");
+                true
+            }
+            None => false,
         }
+    }
 
-        // Clear background with theme color
-        let background = Block::default().style(Style::default().bg(Theme::BACKGROUND));
-        frame.render_widget(background, frame.area());
+    fn previous_boundary(&self) -> usize {
+        self.text[..self.cursor]
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(i, _)| i)
+    }
 
-        let main_chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(3), // Tab bar
-                Constraint::Length(3), // Search bar
-                Constraint::Min(5),    // Main content (minimum height)
-                Constraint::Length(3), // Status bar
-            ])
-            .margin(1) // Add margin around the entire layout
-            .split(frame.area());
+    fn next_boundary(&self) -> usize {
+        self.text[self.cursor..]
+            .graphemes(true)
+            .next()
+            .map_or(self.cursor, |g| self.cursor + g.len())
+    }
 
-        let content_chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
-            .spacing(1) // Add space between panels
-            .split(main_chunks[2]);
+    fn line_start(&self) -> usize {
+        self.text[..self.cursor].rfind('\n').map_or(0, |i| i + 1)
+    }
 
-        self.draw_tab_bar(frame, main_chunks[0]);
-        self.draw_search_bar(frame, main_chunks[1]);
-        self.draw_sidebar(frame, content_chunks[0]);
-        if self.focus == Focus::Logs {
-            self.draw_log_panel(frame, content_chunks[1]);
-        } else {
-            self.draw_main_panel(frame, content_chunks[1]);
-        }
-        self.draw_status_bar(frame, main_chunks[3]);
+    fn line_end(&self) -> usize {
+        self.text[self.cursor..]
+            .find('\n')
+            .map_or(self.text.len(), |i| self.cursor + i)
+    }
 
-        // Draw exit confirmation dialog if showing
-        if self.showing_exit_confirmation {
-            self.draw_exit_confirmation(frame);
+    /// Start of the word before the cursor, skipping any separators first.
+    fn word_start(&self) -> usize {
+        let mut position = self.cursor;
+        let mut in_word = false;
+        for (i, grapheme) in self.text[..self.cursor].grapheme_indices(true).rev() {
+            if is_word(grapheme) {
+                in_word = true;
+            } else if in_word {
+                break;
+            }
+            position = i;
         }
+        position
     }
 
-    fn draw_tab_bar(&mut self, frame: &mut Frame, area: Rect) {
-        let tabs = [TabLocation::User, TabLocation::Global, TabLocation::Apple];
-        let tab_width = area.width / 3;
-
-        let tab_chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Length(tab_width),
-                Constraint::Length(tab_width),
-                Constraint::Length(tab_width),
-            ])
-            .split(area);
-
-        for (i, tab) in tabs.iter().enumerate() {
-            let is_active = *tab == self.current_tab;
-            let agent_count = match tab {
-                TabLocation::User => self.user_agents.len(),
-                TabLocation::Global => self.global_agents.len(),
-                TabLocation::Apple => self.apple_agents.len(),
-            };
+    /// End of the word after the cursor, skipping any separators first.
+    fn word_end(&self) -> usize {
+        let mut position = self.cursor;
+        let mut in_word = false;
+        for grapheme in self.text[self.cursor..].graphemes(true) {
+            if is_word(grapheme) {
+                in_word = true;
+            } else if in_word {
+                break;
+            }
+            position += grapheme.len();
+        }
+        position
+    }
 
-            let (border_style, title_style, bg_style) = if is_active {
-                (
-                    Style::default().fg(Theme::BORDER_FOCUSED),
-                    Style::default()
-                        .fg(Theme::ACCENT_PRIMARY)
-                        .add_modifier(Modifier::BOLD),
-                    Style::default().bg(Theme::HIGHLIGHT),
-                )
+    /// The text as styled lines, with the cursor drawn as a reversed cell and
+    /// the selection in `selection_style`.
+    fn to_lines(&self, style: Style, selection_style: Style) -> Vec<Line<'static>> {
+        let selection = self.selection().unwrap_or(0..0);
+        let cursor_style = style.add_modifier(Modifier::REVERSED);
+        let mut lines = Vec::new();
+        let mut spans = Vec::new();
+        let mut offset = 0;
+
+        for grapheme in self.text.graphemes(true) {
+            let at_cursor = offset == self.cursor;
+            if grapheme == "\n" {
+                if at_cursor {
+                    spans.push(Span::styled(" ", cursor_style));
+                }
+                lines.push(Line::from(std::mem::take(&mut spans)));
             } else {
-                (
-                    Style::default().fg(Theme::BORDER_UNFOCUSED),
-                    Style::default().fg(Theme::TEXT_DIM),
-                    Style::default().bg(Theme::BACKGROUND),
-                )
-            };
+                let grapheme_style = if at_cursor {
+                    cursor_style
+                } else if selection.contains(&offset) {
+                    selection_style
+                } else {
+                    style
+                };
+                spans.push(Span::styled(grapheme.to_string(), grapheme_style));
+            }
+            offset += grapheme.len();
+        }
+        if self.cursor == self.text.len() {
+            spans.push(Span::styled(" ", cursor_style));
+        }
+        lines.push(Line::from(spans));
+        lines
+    }
+}
 
-            let title = format!("{} ({})", tab.get_display_name(), agent_count);
-            let hint = format!("[{}]", i + 1);
+fn is_word(grapheme: &str) -> bool {
+    grapheme.chars().any(|c| c.is_alphanumeric() || c == '_')
+}
 
-            let tab_content = vec![Line::from(vec![
-                Span::styled(hint, Style::default().fg(Theme::ACCENT_MUTED)),
-                Span::raw(" "),
-                Span::styled(title, title_style),
-            ])];
+/// Row-by-row editor for array fields, and a name/value table for
+/// `EnvironmentVariables`.
+#[derive(Debug, Clone)]
+struct ListEditor {
+    field: FormField,
+    columns: &'static [&'static str],
+    rows: Vec<Vec<String>>,
+    selected: usize,
+    column: usize,
+    /// The cell being edited, if any.
+    input: Option<TextInput>,
+    /// Why the cell being edited can't be committed.
+    error: Option<String>,
+    /// Set while filling in a row that was just added, so cancelling drops it.
+    adding: bool,
+    /// Set while the input holds a whole command line for `ProgramArguments`.
+    command_line: bool,
+}
 
-            let tab_widget = Paragraph::new(tab_content)
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .border_type(BorderType::Rounded)
-                        .border_style(border_style)
-                        .style(bg_style),
-                )
-                .alignment(ratatui::layout::Alignment::Center);
+impl ListEditor {
+    fn new(field: FormField, plist: &PlistData) -> Self {
+        let list = |items: &Option<Vec<String>>| {
+            items
+                .iter()
+                .flatten()
+                .map(|item| vec![item.clone()])
+                .collect::<Vec<_>>()
+        };
+        let (columns, rows): (&'static [&'static str], _) = match field {
+            FormField::ProgramArguments => (&["Argument"], list(&plist.program_arguments)),
+            FormField::AssociatedBundleIdentifiers => (
+                &["Bundle Identifier"],
+                list(&plist.associated_bundle_identifiers),
+            ),
+            _ => {
+                let rows = plist
+                    .environment_variables
+                    .iter()
+                    .flatten()
+                    .map(|(name, value)| vec![name.clone(), value.clone()])
+                    .collect();
+                (&["Name", "Value"], rows)
+            }
+        };
 
-            frame.render_widget(tab_widget, tab_chunks[i]);
+        Self {
+            field,
+            columns,
+            rows,
+            selected: 0,
+            column: 0,
+            input: None,
+            error: None,
+            adding: false,
+            command_line: false,
         }
     }
 
-    fn draw_search_bar(&mut self, frame: &mut Frame, area: Rect) {
-        let search_text = if self.focus == Focus::Search {
-            if self.filter_text.is_empty() {
-                "│".to_string()
-            } else {
-                format!("{}│", self.filter_text)
+    /// Writes the rows back into their field, unsetting it when empty.
+    fn apply(&self, plist: &mut PlistData) {
+        let values: Vec<String> = self.rows.iter().map(|row| row[0].clone()).collect();
+        match self.field {
+            FormField::ProgramArguments => {
+                plist.program_arguments = (!values.is_empty()).then_some(values);
             }
-        } else if self.filter_text.is_empty() {
-            "Type to filter agents...".to_string()
-        } else {
-            self.filter_text.clone()
-        };
+            FormField::AssociatedBundleIdentifiers => {
+                plist.associated_bundle_identifiers = (!values.is_empty()).then_some(values);
+            }
+            _ => {
+                let env_vars: Vec<(String, String)> = self
+                    .rows
+                    .iter()
+                    .filter(|row| !row[0].is_empty())
+                    .map(|row| (row[0].clone(), row[1].clone()))
+                    .collect();
+                plist.environment_variables = (!env_vars.is_empty()).then_some(env_vars);
+            }
+        }
+    }
 
-        let (search_style, border_style, title_style) = if self.focus == Focus::Search {
-            (
-                Style::default()
-                    .fg(Theme::BACKGROUND)
-                    .bg(Theme::ACCENT_PRIMARY),
-                Style::default().fg(Theme::BORDER_FOCUSED),
+    /// Edits the arguments as one shell command line, starting from `text`.
+    fn start_command_line(&mut self, text: String) {
+        self.input = Some(TextInput::new(text));
+        self.command_line = true;
+        self.error = None;
+    }
+
+    /// Handles bracketed paste. Pasting into `ProgramArguments` while browsing
+    /// opens the command line with the pasted text.
+    fn paste(&mut self, text: &str) {
+        // Backslash-newline continues a command across lines in the shell,
+        // whichever line endings the clipboard used
+        let text = text
+            .replace("\r\n", "\n")
+            .replace('\r', "\n")
+            .trim_end_matches('\n')
+            .replace("\\\n", "");
+        if let Some(input) = &mut self.input {
+            input.insert(&text);
+            self.error = None;
+        } else if self.field == FormField::ProgramArguments {
+            self.start_command_line(text);
+        }
+    }
+
+    fn start_cell_edit(&mut self) {
+        if let Some(row) = self.rows.get(self.selected) {
+            self.input = Some(TextInput::new(row[self.column].clone()));
+        }
+    }
+
+    /// Handles a key while browsing rows, returning false for keys the
+    /// editor doesn't use.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if let Some(input) = &mut self.input {
+            match key.code {
+                KeyCode::Enter if self.command_line => match shell_words::split(input.text()) {
+                    Ok(args) => {
+                        self.rows = args.into_iter().map(|arg| vec![arg]).collect();
+                        self.selected = 0;
+                        self.input = None;
+                        self.command_line = false;
+                        self.error = None;
+                    }
+                    Err(e) => self.error = Some(format!("Can't split the command line: {}", e)),
+                },
+                KeyCode::Enter => {
+                    if self.columns.len() > 1
+                        && self.column == 0
+                        && let Err(error) = validate_env_name(input.text())
+                    {
+                        self.error = Some(error);
+                        return true;
+                    }
+                    self.rows[self.selected][self.column] = input.text().to_string();
+                    self.input = None;
+                    self.error = None;
+                    // New name/value rows go straight on to the value
+                    if self.adding && self.column + 1 < self.columns.len() {
+                        self.column += 1;
+                        self.start_cell_edit();
+                    } else {
+                        self.adding = false;
+                    }
+                }
+                KeyCode::Esc => {
+                    self.input = None;
+                    self.error = None;
+                    self.command_line = false;
+                    if self.adding {
+                        self.rows.remove(self.selected);
+                        self.selected = self.selected.saturating_sub(1);
+                        self.column = 0;
+                        self.adding = false;
+                    }
+                }
+                _ => {
+                    if input.handle_key(key) {
+                        self.error = None;
+                    }
+                }
+            }
+            return true;
+        }
+
+        let count = self.rows.len();
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down if count > 0 => {
+                self.selected = (self.selected + 1) % count;
+            }
+            KeyCode::Char('k') | KeyCode::Up if count > 0 => {
+                self.selected = (self.selected + count - 1) % count;
+            }
+            KeyCode::Char('h') | KeyCode::Left => {
+                self.column = self.column.saturating_sub(1);
+            }
+            KeyCode::Char('l') | KeyCode::Right => {
+                self.column = (self.column + 1).min(self.columns.len() - 1);
+            }
+            KeyCode::Char('J') if self.selected + 1 < count => {
+                self.rows.swap(self.selected, self.selected + 1);
+                self.selected += 1;
+            }
+            KeyCode::Char('K') if self.selected > 0 => {
+                self.rows.swap(self.selected, self.selected - 1);
+                self.selected -= 1;
+            }
+            KeyCode::Char('a') | KeyCode::Char('o') => {
+                let position = if count == 0 { 0 } else { self.selected + 1 };
+                self.rows
+                    .insert(position, vec![String::new(); self.columns.len()]);
+                self.selected = position;
+                self.column = 0;
+                self.adding = true;
+                self.start_cell_edit();
+            }
+            KeyCode::Char('d') | KeyCode::Delete if count > 0 => {
+                self.rows.remove(self.selected);
+                self.selected = self.selected.min(count.saturating_sub(2));
+            }
+            KeyCode::Enter | KeyCode::Char('e') if count > 0 => {
+                self.start_cell_edit();
+            }
+            KeyCode::Char('c') if self.field == FormField::ProgramArguments => {
+                let args: Vec<&str> = self.rows.iter().map(|row| row[0].as_str()).collect();
+                self.start_command_line(shell_words::join(args));
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn to_lines(&self) -> Vec<Line<'static>> {
+        let selection_style = Style::default()
+            .fg(Theme::BACKGROUND)
+            .bg(Theme::ACCENT_PRIMARY);
+        let mut lines = Vec::new();
+
+        if self.command_line
+            && let Some(input) = &self.input
+        {
+            let mut spans = vec![Span::styled(
+                "  $ ",
                 Style::default()
                     .fg(Theme::ACCENT_PRIMARY)
                     .add_modifier(Modifier::BOLD),
-            )
-        } else {
-            (
-                Style::default().fg(Theme::FOREGROUND).bg(Theme::BACKGROUND),
-                Style::default().fg(Theme::BORDER_UNFOCUSED),
-                Style::default().fg(Theme::TEXT_DIM),
-            )
+            )];
+            spans.extend(
+                input
+                    .to_lines(Style::default().fg(Theme::FOREGROUND), selection_style)
+                    .into_iter()
+                    .flat_map(|line| line.spans),
+            );
+            lines.push(Line::from(spans));
+            if let Some(error) = &self.error {
+                lines.push(Line::from(vec![Span::styled(
+                    format!("    ⚠ {}", error),
+                    Style::default().fg(Theme::ACCENT_ERROR),
+                )]));
+            }
+            lines.push(Line::from(vec![Span::styled(
+                "    Split with shell quoting rules, so quote arguments that contain spaces",
+                Style::default()
+                    .fg(Theme::TEXT_DIM)
+                    .add_modifier(Modifier::ITALIC),
+            )]));
+            return lines;
+        }
+
+        if self.columns.len() > 1 {
+            let mut header = vec![Span::raw("      ")];
+            for column in self.columns {
+                header.push(Span::styled(
+                    format!("{:<24}", column),
+                    Style::default()
+                        .fg(Theme::ACCENT_MUTED)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+            lines.push(Line::from(header));
+        }
+        if self.rows.is_empty() {
+            lines.push(Line::from(vec![Span::styled(
+                "  No entries, press a to add one",
+                Style::default()
+                    .fg(Theme::TEXT_DIM)
+                    .add_modifier(Modifier::ITALIC),
+            )]));
+        }
+
+        for (i, row) in self.rows.iter().enumerate() {
+            let is_selected = i == self.selected;
+            let mut spans = vec![
+                Span::styled(
+                    if is_selected { "▶ " } else { "  " },
+                    Style::default().fg(Theme::ACCENT_PRIMARY),
+                ),
+                Span::styled(format!("[{}] ", i), Style::default().fg(Theme::TEXT_DIM)),
+            ];
+            for (column, cell) in row.iter().enumerate() {
+                if column > 0 {
+                    spans.push(Span::styled(" = ", Style::default().fg(Theme::TEXT_DIM)));
+                }
+                let is_current = is_selected && column == self.column;
+                let style = if is_current {
+                    Style::default()
+                        .fg(Theme::ACCENT_PRIMARY)
+                        .bg(Theme::HIGHLIGHT)
+                } else {
+                    Style::default().fg(Theme::FOREGROUND)
+                };
+                match &self.input {
+                    Some(input) if is_current && !self.command_line => spans.extend(
+                        input
+                            .to_lines(style, selection_style)
+                            .into_iter()
+                            .flat_map(|line| line.spans),
+                    ),
+                    _ if cell.is_empty() => spans.push(Span::styled(
+                        "(empty)",
+                        style.fg(Theme::TEXT_DIM).add_modifier(Modifier::ITALIC),
+                    )),
+                    _ => spans.push(Span::styled(cell.clone(), style)),
+                }
+            }
+            if self.columns.len() > 1 && self.rows[..i].iter().any(|other| other[0] == row[0]) {
+                spans.push(Span::styled(
+                    "  ⚠ duplicate",
+                    Style::default().fg(Theme::ACCENT_WARNING),
+                ));
+            }
+            lines.push(Line::from(spans));
+            if is_selected && let Some(error) = &self.error {
+                lines.push(Line::from(vec![Span::styled(
+                    format!("      ⚠ {}", error),
+                    Style::default().fg(Theme::ACCENT_ERROR),
+                )]));
+            }
+        }
+        lines
+    }
+}
+
+/// Known `POSIXSpawnType` values.
+const POSIX_SPAWN_TYPES: [&str; 5] = ["Adaptive", "App", "Background", "Interactive", "Standard"];
+
+/// `ProcessType` values from launchd.plist(5).
+const PROCESS_TYPES: [&str; 4] = ["Background", "Standard", "Adaptive", "Interactive"];
+
+/// `LimitLoadToSessionType` values from launchd.plist(5).
+const SESSION_TYPES: [&str; 5] = ["Aqua", "Background", "LoginWindow", "StandardIO", "System"];
+
+/// Chooses a value for a field with a known set of values, or several for
+/// `LimitLoadToSessionType`.
+#[derive(Debug, Clone)]
+struct Picker {
+    field: FormField,
+    /// Known values, followed by any other value the plist already had.
+    options: Vec<String>,
+    checked: Vec<bool>,
+    multiple: bool,
+    /// Row under the cursor. Single-value pickers start with an "unset" row.
+    selected: usize,
+}
+
+impl Picker {
+    fn new(field: FormField, plist: &PlistData) -> Self {
+        let (known, current, multiple): (&[&str], Vec<String>, bool) = match field {
+            FormField::POSIXSpawnType => (
+                &POSIX_SPAWN_TYPES,
+                plist.posix_spawn_type.iter().cloned().collect(),
+                false,
+            ),
+            FormField::ProcessType => (
+                &PROCESS_TYPES,
+                plist.process_type.iter().cloned().collect(),
+                false,
+            ),
+            _ => (
+                &SESSION_TYPES,
+                match &plist.limit_load_to_session_type {
+                    Some(LimitLoadToSessionType::Single(session)) => vec![session.clone()],
+                    Some(LimitLoadToSessionType::Multiple(sessions)) => sessions.clone(),
+                    None => Vec::new(),
+                },
+                true,
+            ),
         };
 
-        let title = if self.filter_text.is_empty() {
-            "🔍 Search"
+        let mut options: Vec<String> = known.iter().map(|value| value.to_string()).collect();
+        for value in &current {
+            if !options.contains(value) {
+                options.push(value.clone());
+            }
+        }
+        let checked: Vec<bool> = options
+            .iter()
+            .map(|option| current.contains(option))
+            .collect();
+        let selected = if multiple {
+            0
         } else {
-            "🔍 Filtering"
+            checked.iter().position(|&c| c).map_or(0, |i| i + 1)
         };
 
-        let search_widget = Paragraph::new(search_text)
-            .block(
-                Block::default()
-                    .title(Line::from(vec![Span::styled(title, title_style)]))
-                    .borders(Borders::ALL)
-                    .border_type(BorderType::Rounded)
-                    .border_style(border_style)
-                    .style(Style::default().bg(Theme::BACKGROUND)),
-            )
-            .style(search_style);
+        Self {
+            field,
+            options,
+            checked,
+            multiple,
+            selected,
+        }
+    }
 
-        frame.render_widget(search_widget, area);
+    fn row_count(&self) -> usize {
+        self.options.len() + usize::from(!self.multiple)
     }
 
-    fn get_filtered_agents(&self) -> Vec<&LaunchAgent> {
-        let current_agents = self.get_current_agents();
-        if self.filter_text.is_empty() {
-            current_agents.iter().collect()
-        } else {
-            current_agents
+    /// Handles a key, returning true once the choice should be applied.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let count = self.row_count();
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => self.selected = (self.selected + 1) % count,
+            KeyCode::Char('k') | KeyCode::Up => self.selected = (self.selected + count - 1) % count,
+            KeyCode::Char(' ') if self.multiple => {
+                self.checked[self.selected] = !self.checked[self.selected];
+            }
+            KeyCode::Char(' ') | KeyCode::Enter => return true,
+            _ => {}
+        }
+        false
+    }
+
+    fn apply(&self, plist: &mut PlistData) {
+        if self.multiple {
+            let mut chosen: Vec<String> = self
+                .options
                 .iter()
-                .filter(|agent| {
-                    let search_text = self.filter_text.to_lowercase();
-                    agent.filename.to_lowercase().contains(&search_text)
-                        || agent
-                            .label
-                            .as_ref()
-                            .map(|label| label.to_lowercase().contains(&search_text))
-                            .unwrap_or(false)
-                })
-                .collect()
+                .zip(&self.checked)
+                .filter(|(_, checked)| **checked)
+                .map(|(option, _)| option.clone())
+                .collect();
+            plist.limit_load_to_session_type = match chosen.len() {
+                0 => None,
+                1 => chosen.pop().map(LimitLoadToSessionType::Single),
+                _ => Some(LimitLoadToSessionType::Multiple(chosen)),
+            };
+            return;
+        }
+
+        let value = self
+            .selected
+            .checked_sub(1)
+            .and_then(|i| self.options.get(i))
+            .cloned();
+        match self.field {
+            FormField::POSIXSpawnType => plist.posix_spawn_type = value,
+            FormField::ProcessType => plist.process_type = value,
+            _ => {}
         }
     }
 
-    fn draw_sidebar(&mut self, frame: &mut Frame, area: Rect) {
-        let filtered_agents: Vec<LaunchAgent> =
-            self.get_filtered_agents().into_iter().cloned().collect();
-        let items: Vec<ListItem> = filtered_agents
-            .iter()
-            .map(|agent| {
-                let (status_icon, status_color) = match agent.status {
-                    AgentStatus::Running => ("●", Theme::ACCENT_SECONDARY),
-                    AgentStatus::Stopped => ("●", Theme::ACCENT_ERROR),
-                    AgentStatus::Error => ("✗", Theme::ACCENT_ERROR),
-                    AgentStatus::Unknown => ("?", Theme::SUBTLE),
-                };
+    fn to_lines(&self) -> Vec<Line<'static>> {
+        let mut rows: Vec<(String, bool)> = Vec::new();
+        if !self.multiple {
+            rows.push(("unset".to_string(), self.checked.iter().all(|c| !c)));
+        }
+        rows.extend(
+            self.options
+                .iter()
+                .cloned()
+                .zip(self.checked.iter().copied()),
+        );
 
-                let (enabled_icon, enabled_color) = if agent.enabled {
-                    ("◉", Theme::ACCENT_MUTED)
-                } else {
-                    ("○", Theme::SUBTLE)
+        rows.into_iter()
+            .enumerate()
+            .map(|(i, (option, checked))| {
+                let is_selected = i == self.selected;
+                let mark = match (self.multiple, checked) {
+                    (true, true) => "[x] ",
+                    (true, false) => "[ ] ",
+                    (false, true) => "(•) ",
+                    (false, false) => "( ) ",
                 };
-
-                let label = agent.label.as_deref().unwrap_or(&agent.filename);
-                let display_name = if label.len() > 35 {
-                    format!("{}...", &label[..32])
+                let style = if is_selected {
+                    Style::default()
+                        .fg(Theme::ACCENT_PRIMARY)
+                        .bg(Theme::HIGHLIGHT)
+                        .add_modifier(Modifier::BOLD)
+                } else if checked {
+                    Style::default().fg(Theme::ACCENT_SECONDARY)
                 } else {
-                    label.to_string()
+                    Style::default().fg(Theme::FOREGROUND)
                 };
-
-                ListItem::new(Line::from(vec![
+                Line::from(vec![
                     Span::styled(
-                        status_icon,
-                        Style::default()
-                            .fg(status_color)
-                            .add_modifier(Modifier::BOLD),
+                        if is_selected { "▶ " } else { "  " },
+                        Style::default().fg(Theme::ACCENT_PRIMARY),
                     ),
-                    Span::raw(" "),
-                    Span::styled(enabled_icon, Style::default().fg(enabled_color)),
-                    Span::raw("  "),
-                    Span::styled(display_name, Style::default().fg(Theme::FOREGROUND)),
-                ]))
+                    Span::styled(mark, Style::default().fg(Theme::ACCENT_MUTED)),
+                    Span::styled(option, style),
+                ])
             })
-            .collect();
+            .collect()
+    }
+}
 
-        let (border_style, title_style) = if self.focus == Focus::Sidebar {
-            (
-                Style::default().fg(Theme::BORDER_FOCUSED),
-                Style::default()
-                    .fg(Theme::ACCENT_PRIMARY)
-                    .add_modifier(Modifier::BOLD),
-            )
-        } else {
-            (
-                Style::default().fg(Theme::BORDER_UNFOCUSED),
-                Style::default().fg(Theme::TEXT_DIM),
-            )
-        };
+/// Undo steps kept per agent before the oldest are dropped.
+const UNDO_LIMIT: usize = 100;
 
-        let current_agents_count = self.get_current_agents().len();
-        let title = if self.filter_text.is_empty() {
-            format!("📋 Agents ({})", current_agents_count)
-        } else {
-            format!(
-                "📋 Agents ({}/{})",
-                filtered_agents.len(),
-                current_agents_count
-            )
-        };
+/// One change to the form. `field` is the field to return to on undo, if the
+/// change had a single one.
+#[derive(Debug, Clone)]
+struct FormChange {
+    field: Option<FormField>,
+    /// Shown in the undo and redo status messages.
+    name: &'static str,
+    before: PlistData,
+    after: PlistData,
+}
 
-        let list = List::new(items)
-            .block(
-                Block::default()
-                    .title(Line::from(vec![Span::styled(title, title_style)]))
-                    .borders(Borders::ALL)
-                    .border_type(BorderType::Rounded)
-                    .border_style(border_style)
-                    .style(Style::default().bg(Theme::BACKGROUND)),
-            )
-            .highlight_style(
-                Style::default()
-                    .bg(Theme::HIGHLIGHT)
-                    .add_modifier(Modifier::BOLD),
-            )
-            .highlight_symbol("▶ ");
+#[derive(Debug, Clone, Default)]
+struct EditHistory {
+    undo: Vec<FormChange>,
+    redo: Vec<FormChange>,
+}
 
-        frame.render_stateful_widget(list, area, &mut self.list_state);
+impl EditHistory {
+    fn record(&mut self, change: FormChange) {
+        self.undo.push(change);
+        if self.undo.len() > UNDO_LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
     }
 
-    fn draw_main_panel(&mut self, frame: &mut Frame, area: Rect) {
-        let (border_style, title_style) = if self.focus == Focus::Form {
-            (
-                Style::default().fg(Theme::BORDER_FOCUSED),
-                Style::default()
-                    .fg(Theme::ACCENT_PRIMARY)
-                    .add_modifier(Modifier::BOLD),
-            )
-        } else {
-            (
-                Style::default().fg(Theme::BORDER_UNFOCUSED),
-                Style::default().fg(Theme::TEXT_DIM),
-            )
-        };
+    fn undo(&mut self) -> Option<&FormChange> {
+        let change = self.undo.pop()?;
+        self.redo.push(change);
+        self.redo.last()
+    }
 
-        if let Some(plist) = &self.selected_plist {
-            let mut text = Vec::new();
+    fn redo(&mut self) -> Option<&FormChange> {
+        let change = self.redo.pop()?;
+        self.undo.push(change);
+        self.undo.last()
+    }
+}
 
-            let start_interval_str = plist
-                .start_interval
-                .map(|i| i.to_string())
-                .unwrap_or_default();
-            let throttle_interval_str = plist
-                .throttle_interval
-                .map(|i| i.to_string())
-                .unwrap_or_default();
-            let run_at_load_str = if plist.run_at_load.unwrap_or(false) {
-                "true"
-            } else {
-                "false"
-            };
-            let keep_alive_str = if plist.keep_alive.unwrap_or(false) {
-                "true"
-            } else {
-                "false"
-            };
-            let abandon_process_group_str = if plist.abandon_process_group.unwrap_or(false) {
-                "true"
-            } else {
-                "false"
-            };
-            let enable_pressured_exit_str = if plist.enable_pressured_exit.unwrap_or(false) {
-                "true"
-            } else {
-                "false"
-            };
-            let enable_transactions_str = if plist.enable_transactions.unwrap_or(false) {
-                "true"
+/// Intervals are seconds, stored by launchd as a non-negative 32-bit integer.
+fn parse_interval(field: &FormField, text: &str) -> Result<Option<i32>, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    let value: i64 = text.parse().map_err(|_| {
+        format!(
+            "{} must be a whole number of seconds, like 300",
+            field.name()
+        )
+    })?;
+    if value < 0 {
+        return Err(format!("{} can't be negative", field.name()));
+    }
+    i32::try_from(value)
+        .map(Some)
+        .map_err(|_| format!("{} can be at most {} seconds", field.name(), i32::MAX))
+}
+
+/// Names that appear more than once, in order of first appearance. launchd
+/// only sees the last value for each.
+fn duplicate_env_names(env_vars: &[(String, String)]) -> Vec<&str> {
+    let mut duplicates: Vec<&str> = Vec::new();
+    for (i, (name, _)) in env_vars.iter().enumerate() {
+        if env_vars[..i].iter().any(|(other, _)| other == name)
+            && !duplicates.contains(&name.as_str())
+        {
+            duplicates.push(name);
+        }
+    }
+    duplicates
+}
+
+fn validate_env_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        Err("Variable name can't be empty".to_string())
+    } else if name.contains('=') {
+        Err("Variable name can't contain '='".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+/// Booleans show as `true`, `false`, or nothing when the key is absent and
+/// launchd applies its default.
+fn bool_text(value: Option<bool>) -> &'static str {
+    match value {
+        Some(true) => "true",
+        Some(false) => "false",
+        None => "",
+    }
+}
+
+/// Like [`bool_text`], but spells out an absent key for the form.
+fn bool_label(value: Option<bool>) -> &'static str {
+    value.map_or("unset", |value| bool_text(Some(value)))
+}
+
+/// Cycles a boolean through unset, true and false.
+fn cycle_bool(value: Option<bool>) -> Option<bool> {
+    match value {
+        None => Some(true),
+        Some(true) => Some(false),
+        Some(false) => None,
+    }
+}
+
+/// What a plist looked like when lam last read or wrote it.
+#[derive(Debug, Clone, PartialEq)]
+struct FileFingerprint {
+    modified: Option<std::time::SystemTime>,
+    len: u64,
+    hash: u64,
+}
+
+impl FileFingerprint {
+    fn read(path: &Path) -> std::io::Result<Self> {
+        let contents = fs::read(path)?;
+        Ok(Self {
+            modified: fs::metadata(path)?.modified().ok(),
+            len: contents.len() as u64,
+            hash: Self::hash(&contents),
+        })
+    }
+
+    fn hash(contents: &[u8]) -> u64 {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        contents.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Whether `path` no longer holds what this fingerprint recorded. The
+    /// contents are only hashed when the size or modification time moved.
+    fn is_stale(&self, path: &Path) -> bool {
+        let Ok(metadata) = fs::metadata(path) else {
+            return true;
+        };
+        if metadata.len() == self.len && metadata.modified().ok() == self.modified {
+            return false;
+        }
+        fs::read(path).map_or(true, |contents| Self::hash(&contents) != self.hash)
+    }
+}
+
+/// What a merge row is about: a form field, or a key the form doesn't edit.
+#[derive(Debug, Clone, PartialEq)]
+enum MergeKey {
+    Field(FormField),
+    Extra(String),
+}
+
+impl MergeKey {
+    fn name(&self) -> &str {
+        match self {
+            MergeKey::Field(field) => field.name(),
+            MergeKey::Extra(key) => key,
+        }
+    }
+}
+
+/// One field or other key that changed on disk, in the form, or both, since
+/// the plist was loaded.
+#[derive(Debug, Clone)]
+struct MergeRow {
+    key: MergeKey,
+    base: String,
+    ours: String,
+    theirs: String,
+    take_theirs: bool,
+    conflicting: bool,
+}
+
+/// The loaded plist changed on disk underneath unsaved or about-to-be-saved
+/// edits.
+#[derive(Debug, Clone)]
+struct Conflict {
+    theirs: Option<PlistData>,
+    while_saving: bool,
+    merge: Option<Vec<MergeRow>>,
+    merge_selected: usize,
+}
+
+/// Three-way merge of the form (`ours`) and the file on disk (`theirs`)
+/// against what was loaded (`base`), field by field and then key by key for
+/// the keys the form doesn't edit. Whatever only one side touched takes that
+/// side; what both changed differently defaults to ours.
+fn merge_rows(base: &PlistData, ours: &PlistData, theirs: &PlistData) -> Vec<MergeRow> {
+    let fields = FormField::ALL.iter().filter_map(|field| {
+        let ours_changed = !ours.field_eq(base, field);
+        let theirs_changed = !theirs.field_eq(base, field);
+        (ours_changed || theirs_changed).then(|| MergeRow {
+            key: MergeKey::Field(field.clone()),
+            base: base.field_text(field),
+            ours: ours.field_text(field),
+            theirs: theirs.field_text(field),
+            take_theirs: theirs_changed && !ours_changed,
+            conflicting: ours_changed && theirs_changed && !ours.field_eq(theirs, field),
+        })
+    });
+
+    let mut keys: Vec<&String> = Vec::new();
+    for (key, _) in base.extra.iter().chain(&ours.extra).chain(&theirs.extra) {
+        if !keys.contains(&key) && !FormField::ALL.iter().any(|field| field.key() == key) {
+            keys.push(key);
+        }
+    }
+    let text = |value: Option<&PlistValue>| {
+        value
+            .map(|value| plist_value_json(value).to_string())
+            .unwrap_or_default()
+    };
+    let extras = keys.into_iter().filter_map(|key| {
+        let (base_value, ours_value, theirs_value) = (
+            base.extra_value(key),
+            ours.extra_value(key),
+            theirs.extra_value(key),
+        );
+        let ours_changed = ours_value != base_value;
+        let theirs_changed = theirs_value != base_value;
+        (ours_changed || theirs_changed).then(|| MergeRow {
+            key: MergeKey::Extra(key.clone()),
+            base: text(base_value),
+            ours: text(ours_value),
+            theirs: text(theirs_value),
+            take_theirs: theirs_changed && !ours_changed,
+            conflicting: ours_changed && theirs_changed && ours_value != theirs_value,
+        })
+    });
+    fields.chain(extras).collect()
+}
+
+fn apply_merge(ours: &PlistData, theirs: &PlistData, rows: &[MergeRow]) -> PlistData {
+    let mut merged = ours.clone();
+    for row in rows.iter().filter(|row| row.take_theirs) {
+        match &row.key {
+            MergeKey::Field(field) => merged.copy_field(theirs, field),
+            MergeKey::Extra(key) => merged.set_extra(key, theirs.extra_value(key).cloned()),
+        }
+    }
+    merged
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Severity {
+    Warning,
+    Error,
+}
+
+impl Severity {
+    fn name(&self) -> &'static str {
+        match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+
+    fn icon(&self) -> &'static str {
+        match self {
+            Severity::Warning => "⚠",
+            Severity::Error => "✗",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Severity::Warning => Theme::ACCENT_WARNING,
+            Severity::Error => Theme::ACCENT_ERROR,
+        }
+    }
+}
+
+struct LintRule {
+    id: &'static str,
+    severity: Severity,
+}
+
+const LINT_RULES: [LintRule; 15] = [
+    LintRule {
+        id: "invalid-plist",
+        severity: Severity::Error,
+    },
+    LintRule {
+        id: "missing-label",
+        severity: Severity::Error,
+    },
+    LintRule {
+        id: "missing-program",
+        severity: Severity::Error,
+    },
+    LintRule {
+        id: "program-mismatch",
+        severity: Severity::Warning,
+    },
+    LintRule {
+        id: "label-filename-mismatch",
+        severity: Severity::Warning,
+    },
+    LintRule {
+        id: "interval-with-keepalive",
+        severity: Severity::Warning,
+    },
+    LintRule {
+        id: "short-throttle-interval",
+        severity: Severity::Warning,
+    },
+    LintRule {
+        id: "relative-path",
+        severity: Severity::Warning,
+    },
+    LintRule {
+        id: "unknown-key",
+        severity: Severity::Warning,
+    },
+    LintRule {
+        id: "wrong-type",
+        severity: Severity::Error,
+    },
+    LintRule {
+        id: "program-not-found",
+        severity: Severity::Error,
+    },
+    LintRule {
+        id: "program-not-executable",
+        severity: Severity::Error,
+    },
+    LintRule {
+        id: "missing-working-directory",
+        severity: Severity::Error,
+    },
+    LintRule {
+        id: "missing-log-directory",
+        severity: Severity::Error,
+    },
+    LintRule {
+        id: "log-not-writable",
+        severity: Severity::Warning,
+    },
+];
+
+/// The PATH launchd gives jobs, used to find a program named without a slash.
+/// The PATH from the user's shell doesn't apply.
+const LAUNCHD_PATH: &str = "/usr/bin:/bin:/usr/sbin:/sbin";
+
+/// ThrottleInterval values below this many seconds are flagged.
+const MIN_THROTTLE_INTERVAL: i32 = 5;
+
+fn lint_rule(id: &str) -> &'static LintRule {
+    LINT_RULES
+        .iter()
+        .find(|rule| rule.id == id)
+        .expect("lint rule ids are defined in LINT_RULES")
+}
+
+/// A key from launchd.plist(5), the value types launchd accepts for it, and
+/// the type of its items when it's an array or dictionary.
+struct LaunchdKey {
+    name: &'static str,
+    types: &'static [&'static str],
+    items: Option<&'static str>,
+}
+
+const fn launchd_key(
+    name: &'static str,
+    types: &'static [&'static str],
+    items: Option<&'static str>,
+) -> LaunchdKey {
+    LaunchdKey { name, types, items }
+}
+
+const STRING: &[&str] = &["string"];
+const INTEGER: &[&str] = &["integer"];
+const BOOLEAN: &[&str] = &["boolean"];
+const ARRAY: &[&str] = &["array"];
+const DICT: &[&str] = &["dict"];
+
+const LAUNCHD_KEYS: &[LaunchdKey] = &[
+    launchd_key("Label", STRING, None),
+    launchd_key("Disabled", BOOLEAN, None),
+    launchd_key("UserName", STRING, None),
+    launchd_key("GroupName", STRING, None),
+    launchd_key("inetdCompatibility", DICT, None),
+    launchd_key("LimitLoadToHosts", ARRAY, Some("string")),
+    launchd_key("LimitLoadFromHosts", ARRAY, Some("string")),
+    launchd_key(
+        "LimitLoadToSessionType",
+        &["string", "array"],
+        Some("string"),
+    ),
+    launchd_key("LimitLoadToHardware", DICT, None),
+    launchd_key("LimitLoadFromHardware", DICT, None),
+    launchd_key("Program", STRING, None),
+    launchd_key("BundleProgram", STRING, None),
+    launchd_key("ProgramArguments", ARRAY, Some("string")),
+    launchd_key("EnableGlobbing", BOOLEAN, None),
+    launchd_key("EnableTransactions", BOOLEAN, None),
+    launchd_key("EnablePressuredExit", BOOLEAN, None),
+    launchd_key("OnDemand", BOOLEAN, None),
+    launchd_key("ServiceIPC", BOOLEAN, None),
+    launchd_key("KeepAlive", &["boolean", "dict"], None),
+    launchd_key("RunAtLoad", BOOLEAN, None),
+    launchd_key("RootDirectory", STRING, None),
+    launchd_key("WorkingDirectory", STRING, None),
+    launchd_key("EnvironmentVariables", DICT, Some("string")),
+    launchd_key("Umask", &["integer", "string"], None),
+    launchd_key("TimeOut", INTEGER, None),
+    launchd_key("ExitTimeOut", INTEGER, None),
+    launchd_key("ThrottleInterval", INTEGER, None),
+    launchd_key("InitGroups", BOOLEAN, None),
+    launchd_key("WatchPaths", ARRAY, Some("string")),
+    launchd_key("QueueDirectories", ARRAY, Some("string")),
+    launchd_key("StartOnMount", BOOLEAN, None),
+    launchd_key("StartInterval", INTEGER, None),
+    launchd_key("StartCalendarInterval", &["dict", "array"], None),
+    launchd_key("StandardInPath", STRING, None),
+    launchd_key("StandardOutPath", STRING, None),
+    launchd_key("StandardErrorPath", STRING, None),
+    launchd_key("Debug", BOOLEAN, None),
+    launchd_key("WaitForDebugger", BOOLEAN, None),
+    launchd_key("SoftResourceLimits", DICT, Some("integer")),
+    launchd_key("HardResourceLimits", DICT, Some("integer")),
+    launchd_key("Nice", INTEGER, None),
+    launchd_key("ProcessType", STRING, None),
+    launchd_key("AbandonProcessGroup", BOOLEAN, None),
+    launchd_key("LowPriorityIO", BOOLEAN, None),
+    launchd_key("LowPriorityBackgroundIO", BOOLEAN, None),
+    launchd_key("MaterializeDatalessFiles", BOOLEAN, None),
+    launchd_key("LaunchOnlyOnce", BOOLEAN, None),
+    launchd_key("MachServices", DICT, None),
+    launchd_key("Sockets", DICT, None),
+    launchd_key("LaunchEvents", DICT, None),
+    launchd_key("SessionCreate", BOOLEAN, None),
+    launchd_key("LegacyTimers", BOOLEAN, None),
+    launchd_key(
+        "AssociatedBundleIdentifiers",
+        &["string", "array"],
+        Some("string"),
+    ),
+    launchd_key("POSIXSpawnType", STRING, None),
+    launchd_key("EventMonitor", BOOLEAN, None),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+struct Diagnostic {
+    rule: &'static str,
+    severity: Severity,
+    /// The top-level key the problem is about, if any.
+    key: Option<String>,
+    message: String,
+    fix: Option<QuickFix>,
+}
+
+impl Diagnostic {
+    fn new(rule: &'static str, key: Option<&str>, message: String) -> Self {
+        Self {
+            rule,
+            severity: lint_rule(rule).severity,
+            key: key.map(str::to_string),
+            message,
+            fix: None,
+        }
+    }
+
+    fn with_fix(mut self, fix: QuickFix) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+}
+
+/// An automatic fix offered with a diagnostic. Fixes to the plist's contents
+/// edit the form, so they can be undone and are saved like any other edit.
+#[derive(Debug, Clone, PartialEq)]
+enum QuickFix {
+    /// Rename the plist file on disk to this name.
+    RenameFile(String),
+    /// Replace the leading `~` of the path under this key with the home
+    /// directory.
+    ExpandTilde(&'static str),
+    CreateLogDirectories,
+    /// Put Program in ProgramArguments[0] and remove Program.
+    MergeProgramIntoArguments,
+    RemoveKey(String),
+}
+
+impl QuickFix {
+    fn description(&self) -> String {
+        match self {
+            QuickFix::RenameFile(filename) => format!("Rename the file to {}", filename),
+            QuickFix::ExpandTilde(key) => format!("Expand ~ in {}", key),
+            QuickFix::CreateLogDirectories => "Create the missing log directories".to_string(),
+            QuickFix::MergeProgramIntoArguments => {
+                "Use Program as ProgramArguments[0] and remove Program".to_string()
+            }
+            QuickFix::RemoveKey(key) => format!("Remove the {} key", key),
+        }
+    }
+
+    /// The form field the fix changes.
+    fn field(&self) -> Option<FormField> {
+        match self {
+            QuickFix::ExpandTilde(key) => FormField::ALL
+                .iter()
+                .find(|field| field.key() == *key)
+                .cloned(),
+            QuickFix::MergeProgramIntoArguments => Some(FormField::ProgramArguments),
+            _ => None,
+        }
+    }
+
+    /// Applies a fix that edits the plist. Returns false, leaving `plist`
+    /// alone, for fixes that act on files instead.
+    fn apply(&self, plist: &mut PlistData) -> bool {
+        match self {
+            QuickFix::ExpandTilde(key) => {
+                let path = match *key {
+                    "Program" => plist.program.as_mut(),
+                    "StandardOutPath" => plist.standard_out_path.as_mut(),
+                    "StandardErrorPath" => plist.standard_error_path.as_mut(),
+                    "WorkingDirectory" => plist.working_directory.as_mut(),
+                    "ProgramArguments" => plist
+                        .program_arguments
+                        .as_mut()
+                        .and_then(|args| args.first_mut()),
+                    _ => None,
+                };
+                if let Some(path) = path
+                    && let Some(expanded) = expand_tilde(path)
+                {
+                    *path = expanded;
+                }
+            }
+            QuickFix::MergeProgramIntoArguments => {
+                if let Some(program) = plist.program.take() {
+                    match &mut plist.program_arguments {
+                        Some(args) if !args.is_empty() => args[0] = program,
+                        _ => plist.program_arguments = Some(vec![program]),
+                    }
+                }
+            }
+            QuickFix::RemoveKey(key) => plist.extra.retain(|(other, _)| other != key),
+            QuickFix::RenameFile(_) | QuickFix::CreateLogDirectories => return false,
+        }
+        true
+    }
+}
+
+/// Whether `path` is `~` or starts with `~/`. `~user` paths aren't expanded.
+fn has_home_prefix(path: &str) -> bool {
+    path == "~" || path.starts_with("~/")
+}
+
+fn expand_tilde(path: &str) -> Option<String> {
+    if !has_home_prefix(path) {
+        return None;
+    }
+    let home = dirs::home_dir()?;
+    Some(format!("{}{}", home.display(), &path[1..]))
+}
+
+/// Quick fixes offered for the loaded agent's diagnostics.
+#[derive(Debug, Clone)]
+struct FixMenu {
+    fixes: Vec<QuickFix>,
+    selected: usize,
+}
+
+/// Checks `plist` against launchd's rules. `filename` is the plist's file
+/// name, when it has one, for the label check.
+fn lint_plist(plist: &PlistData, filename: Option<&str>) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    let label = plist.label.as_deref().filter(|label| !label.is_empty());
+    if label.is_none() {
+        diagnostics.push(Diagnostic::new(
+            "missing-label",
+            Some("Label"),
+            "Label is missing, launchd won't load the job".to_string(),
+        ));
+    }
+
+    let program = plist.program.as_deref().filter(|p| !p.is_empty());
+    let first_argument = plist
+        .program_arguments
+        .as_ref()
+        .and_then(|args| args.first())
+        .map(String::as_str);
+    match (program, first_argument) {
+        (None, None) => diagnostics.push(Diagnostic::new(
+            "missing-program",
+            Some("ProgramArguments"),
+            "Set Program or ProgramArguments so launchd knows what to run".to_string(),
+        )),
+        (Some(program), Some(first))
+            if Path::new(program).file_name() != Path::new(first).file_name() =>
+        {
+            diagnostics.push(
+                Diagnostic::new(
+                    "program-mismatch",
+                    Some("Program"),
+                    format!(
+                        "Program runs {} but ProgramArguments[0] is {}",
+                        program, first
+                    ),
+                )
+                .with_fix(QuickFix::MergeProgramIntoArguments),
+            )
+        }
+        _ => {}
+    }
+
+    if let (Some(label), Some(filename)) = (label, filename) {
+        let stem = filename.strip_suffix(".plist").unwrap_or(filename);
+        if stem != label {
+            let diagnostic = Diagnostic::new(
+                "label-filename-mismatch",
+                Some("Label"),
+                format!(
+                    "{} should be named {}.plist to match its Label",
+                    filename, label
+                ),
+            );
+            diagnostics.push(if label.contains(['/', '\\']) || label.starts_with('.') {
+                diagnostic
             } else {
-                "false"
-            };
-            let event_monitor_str = if plist.event_monitor.unwrap_or(false) {
-                "true"
+                diagnostic.with_fix(QuickFix::RenameFile(format!("{}.plist", label)))
+            });
+        }
+    }
+
+    if plist.start_interval.is_some() && plist.keep_alive == Some(true) {
+        diagnostics.push(Diagnostic::new(
+            "interval-with-keepalive",
+            Some("StartInterval"),
+            "StartInterval has no effect while KeepAlive is true".to_string(),
+        ));
+    }
+
+    if let Some(throttle) = plist.throttle_interval
+        && throttle < MIN_THROTTLE_INTERVAL
+    {
+        diagnostics.push(Diagnostic::new(
+            "short-throttle-interval",
+            Some("ThrottleInterval"),
+            format!(
+                "ThrottleInterval of {}s lets a crashing job respawn almost immediately",
+                throttle
+            ),
+        ));
+    }
+
+    let mut paths = vec![
+        ("Program", program),
+        ("StandardOutPath", plist.standard_out_path.as_deref()),
+        ("StandardErrorPath", plist.standard_error_path.as_deref()),
+        ("WorkingDirectory", plist.working_directory.as_deref()),
+    ];
+    // Without Program, a bare name in ProgramArguments[0] is looked up in PATH
+    if program.is_none() {
+        paths.push((
+            "ProgramArguments",
+            first_argument.filter(|p| p.contains('/')),
+        ));
+    }
+    for (key, path) in paths {
+        let Some(path) = path.filter(|path| !path.is_empty()) else {
+            continue;
+        };
+        if path.starts_with('~') {
+            let diagnostic = Diagnostic::new(
+                "relative-path",
+                Some(key),
+                format!("{} starts with ~, which launchd doesn't expand", key),
+            );
+            diagnostics.push(if has_home_prefix(path) {
+                diagnostic.with_fix(QuickFix::ExpandTilde(key))
             } else {
-                "false"
-            };
+                diagnostic
+            });
+        } else if !path.starts_with('/') {
+            diagnostics.push(Diagnostic::new(
+                "relative-path",
+                Some(key),
+                format!("{} is a relative path: {}", key, path),
+            ));
+        }
+    }
 
-            let fields = vec![
-                (
-                    FormField::Label,
-                    "🏷️  Label",
-                    plist.label.as_deref().unwrap_or(""),
-                ),
-                (
-                    FormField::Program,
-                    "⚙️  Program",
-                    plist.program.as_deref().unwrap_or(""),
-                ),
-                (
-                    FormField::StartInterval,
-                    "⏰ Start Interval",
-                    &start_interval_str,
-                ),
-                (
-                    FormField::ThrottleInterval,
-                    "⏱️  Throttle Interval",
-                    &throttle_interval_str,
-                ),
-                (FormField::RunAtLoad, "🚀 Run At Load", run_at_load_str),
-                (FormField::KeepAlive, "💓 Keep Alive", keep_alive_str),
-                (
-                    FormField::AbandonProcessGroup,
-                    "🔄 Abandon Process Group",
-                    abandon_process_group_str,
-                ),
-                (
-                    FormField::StandardOutPath,
-                    "📄 Stdout Path",
-                    plist.standard_out_path.as_deref().unwrap_or(""),
-                ),
-                (
-                    FormField::StandardErrorPath,
-                    "📄 Stderr Path",
-                    plist.standard_error_path.as_deref().unwrap_or(""),
-                ),
-                (
-                    FormField::WorkingDirectory,
-                    "📁 Working Directory",
-                    plist.working_directory.as_deref().unwrap_or(""),
-                ),
-                (
-                    FormField::POSIXSpawnType,
-                    "🔧 POSIX Spawn Type",
-                    plist.posix_spawn_type.as_deref().unwrap_or(""),
-                ),
-                (
-                    FormField::EnablePressuredExit,
-                    "🚪 Enable Pressured Exit",
-                    enable_pressured_exit_str,
-                ),
-                (
-                    FormField::EnableTransactions,
-                    "🔒 Enable Transactions",
-                    enable_transactions_str,
+    for (key, value) in &plist.extra {
+        let Some(known) = LAUNCHD_KEYS.iter().find(|known| known.name == key) else {
+            diagnostics.push(
+                Diagnostic::new(
+                    "unknown-key",
+                    Some(key),
+                    format!("{} isn't a launchd key and will be ignored", key),
+                )
+                .with_fix(QuickFix::RemoveKey(key.clone())),
+            );
+            continue;
+        };
+        if !known.types.contains(&value.kind()) {
+            diagnostics.push(Diagnostic::new(
+                "wrong-type",
+                Some(key),
+                format!(
+                    "{} should be {} but is {}",
+                    key,
+                    known.types.join(" or "),
+                    value.kind()
                 ),
-                (
-                    FormField::EventMonitor,
-                    "👁️  Event Monitor",
-                    event_monitor_str,
+            ));
+            continue;
+        }
+        let items: Vec<&PlistValue> = match value {
+            PlistValue::Array(items) => items.iter().collect(),
+            PlistValue::Dict(entries) => entries.iter().map(|(_, value)| value).collect(),
+            _ => Vec::new(),
+        };
+        if let Some(expected) = known.items
+            && let Some(item) = items.iter().find(|item| item.kind() != expected)
+        {
+            diagnostics.push(Diagnostic::new(
+                "wrong-type",
+                Some(key),
+                format!(
+                    "{} should only contain {} values but has {}",
+                    key,
+                    expected,
+                    item.kind()
                 ),
-            ];
+            ));
+        }
+    }
 
-            for (i, (field, label, value)) in fields.iter().enumerate() {
-                let is_current = self.focus == Focus::Form && self.current_field == *field;
-                let is_editing = self.editing && self.editing_field.as_ref() == Some(field);
+    diagnostics
+}
 
-                let (label_style, value_style) = if is_editing {
-                    (
-                        Style::default()
-                            .fg(Theme::ACCENT_WARNING)
-                            .add_modifier(Modifier::BOLD),
-                        Style::default()
-                            .fg(Theme::BACKGROUND)
-                            .bg(Theme::ACCENT_WARNING)
-                            .add_modifier(Modifier::BOLD),
-                    )
+/// How suspicious an audit finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Risk {
+    Low,
+    Medium,
+    High,
+}
+
+impl Risk {
+    fn name(&self) -> &'static str {
+        match self {
+            Risk::Low => "low",
+            Risk::Medium => "medium",
+            Risk::High => "high",
+        }
+    }
+
+    fn icon(&self) -> &'static str {
+        match self {
+            Risk::Low => "○",
+            Risk::Medium => "◐",
+            Risk::High => "●",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Risk::Low => Theme::ACCENT_MUTED,
+            Risk::Medium => Theme::ACCENT_WARNING,
+            Risk::High => Theme::ACCENT_ERROR,
+        }
+    }
+}
+
+struct AuditRule {
+    id: &'static str,
+    risk: Risk,
+}
+
+const AUDIT_RULES: [AuditRule; 9] = [
+    AuditRule {
+        id: "program-in-temp-dir",
+        risk: Risk::High,
+    },
+    AuditRule {
+        id: "program-in-world-writable-dir",
+        risk: Risk::High,
+    },
+    AuditRule {
+        id: "program-owned-by-other-user",
+        risk: Risk::Medium,
+    },
+    AuditRule {
+        id: "plist-writable-by-others",
+        risk: Risk::High,
+    },
+    AuditRule {
+        id: "hidden-program",
+        risk: Risk::Medium,
+    },
+    AuditRule {
+        id: "script-from-temp-dir",
+        risk: Risk::High,
+    },
+    AuditRule {
+        id: "persistent-unknown-vendor",
+        risk: Risk::Medium,
+    },
+    AuditRule {
+        id: "unparseable-plist",
+        risk: Risk::Medium,
+    },
+    AuditRule {
+        id: "recently-created",
+        risk: Risk::Low,
+    },
+];
+
+fn audit_rule(id: &str) -> &'static AuditRule {
+    AUDIT_RULES
+        .iter()
+        .find(|rule| rule.id == id)
+        .expect("audit rule ids are defined in AUDIT_RULES")
+}
+
+/// Directories anyone can write to, where a program shouldn't live.
+const TEMP_DIRS: [&str; 7] = [
+    "/tmp",
+    "/private/tmp",
+    "/var/tmp",
+    "/private/var/tmp",
+    "/var/folders",
+    "/private/var/folders",
+    "/Users/Shared",
+];
+
+/// Programs that run a script given as an argument.
+const INTERPRETERS: [&str; 17] = [
+    "sh",
+    "bash",
+    "zsh",
+    "dash",
+    "ksh",
+    "csh",
+    "tcsh",
+    "fish",
+    "python",
+    "perl",
+    "ruby",
+    "node",
+    "osascript",
+    "php",
+    "lua",
+    "tclsh",
+    "env",
+];
+
+/// Label prefixes of vendors whose always-running agents are expected. More
+/// can be added with `trusted_vendors` in the config file.
+const TRUSTED_VENDORS: [&str; 4] = ["com.apple", "com.google", "com.microsoft", "homebrew.mxcl"];
+
+/// Plists created within this many days are flagged for review.
+const RECENT_AGENT_DAYS: u64 = 7;
+
+#[derive(Debug, Clone, PartialEq)]
+struct AuditFinding {
+    rule: &'static str,
+    risk: Risk,
+    message: String,
+}
+
+impl AuditFinding {
+    fn new(rule: &'static str, message: String) -> Self {
+        Self {
+            rule,
+            risk: audit_rule(rule).risk,
+            message,
+        }
+    }
+}
+
+/// An agent's plist and what the audit found in it.
+#[derive(Debug, Clone)]
+struct AuditedAgent {
+    /// The tab the agent is listed in, if it's in one of the standard
+    /// directories.
+    location: Option<TabLocation>,
+    path: PathBuf,
+    label: Option<String>,
+    program: Option<String>,
+    findings: Vec<AuditFinding>,
+}
+
+impl AuditedAgent {
+    fn risk(&self) -> Option<Risk> {
+        self.findings.iter().map(|finding| finding.risk).max()
+    }
+}
+
+/// The settings from the `[audit]` table of the config file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct AuditConfig {
+    /// Extra label prefixes, such as `com.example`, whose always-running
+    /// agents are expected.
+    trusted_vendors: Vec<String>,
+}
+
+fn in_temp_dir(path: &Path) -> bool {
+    TEMP_DIRS.iter().any(|dir| path.starts_with(dir))
+}
+
+fn is_interpreter(program: &Path) -> bool {
+    let Some(name) = program.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    // python3, python3.12, perl5.34 and so on
+    let name = name.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    INTERPRETERS.contains(&name)
+}
+
+fn is_trusted_vendor(label: &str, trusted_vendors: &[String]) -> bool {
+    TRUSTED_VENDORS
+        .iter()
+        .copied()
+        .chain(trusted_vendors.iter().map(String::as_str))
+        .map(|vendor| vendor.trim_end_matches('.'))
+        .any(|vendor| {
+            label == vendor
+                || label
+                    .strip_prefix(vendor)
+                    .is_some_and(|rest| rest.starts_with('.'))
+        })
+}
+
+/// The owner's uid and the permission bits of `path`.
+#[cfg(unix)]
+fn owner_and_mode(path: &Path) -> Option<(u32, u32)> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.uid(), metadata.mode() & 0o7777))
+}
+
+#[cfg(not(unix))]
+fn owner_and_mode(_path: &Path) -> Option<(u32, u32)> {
+    None
+}
+
+/// Checks the agent at `path` for signs of unwanted persistence.
+fn audit_plist(
+    path: &Path,
+    plist: &PlistData,
+    trusted_vendors: &[String],
+    now: std::time::SystemTime,
+) -> Vec<AuditFinding> {
+    let mut findings = Vec::new();
+    let plist_owner = owner_and_mode(path);
+    if let Some((_, mode)) = plist_owner
+        && mode & 0o022 != 0
+    {
+        findings.push(AuditFinding::new(
+            "plist-writable-by-others",
+            format!(
+                "The plist is writable by users other than its owner (mode {:o})",
+                mode
+            ),
+        ));
+    }
+
+    let arguments = plist.program_arguments.as_deref().unwrap_or_default();
+    if let Some(program) = plist
+        .program
+        .as_deref()
+        .or(arguments.first().map(String::as_str))
+    {
+        let path = resolve_program(program).unwrap_or_else(|| PathBuf::from(program));
+        if in_temp_dir(&path) {
+            findings.push(AuditFinding::new(
+                "program-in-temp-dir",
+                format!("Program {} is in a temporary directory", path.display()),
+            ));
+        } else if let Some(dir) = path.parent()
+            && let Some((_, mode)) = owner_and_mode(dir)
+            && mode & 0o002 != 0
+        {
+            findings.push(AuditFinding::new(
+                "program-in-world-writable-dir",
+                format!(
+                    "Program {} is in world-writable {}",
+                    path.display(),
+                    dir.display()
+                ),
+            ));
+        }
+        if let (Some((program_uid, _)), Some((plist_uid, _))) = (owner_and_mode(&path), plist_owner)
+            && program_uid != plist_uid
+            && program_uid != 0
+        {
+            findings.push(AuditFinding::new(
+                "program-owned-by-other-user",
+                format!(
+                    "Program {} is owned by uid {}, not the plist's owner (uid {})",
+                    path.display(),
+                    program_uid,
+                    plist_uid
+                ),
+            ));
+        }
+        let hidden = path.components().any(|component| {
+            matches!(component, std::path::Component::Normal(name) if name.to_string_lossy().starts_with('.'))
+        });
+        if hidden {
+            findings.push(AuditFinding::new(
+                "hidden-program",
+                format!(
+                    "Program {} is hidden or in a hidden directory",
+                    path.display()
+                ),
+            ));
+        }
+        if is_interpreter(&path) {
+            // Arguments can be scripts or inline commands like `sh -c "..."`
+            let script = arguments.iter().skip(1).find(|argument| {
+                in_temp_dir(Path::new(argument.as_str()))
+                    || TEMP_DIRS
+                        .iter()
+                        .any(|dir| argument.contains(&format!(" {}/", dir)))
+            });
+            if let Some(script) = script {
+                findings.push(AuditFinding::new(
+                    "script-from-temp-dir",
+                    format!(
+                        "{} runs code from a temporary directory: {}",
+                        path.display(),
+                        script
+                    ),
+                ));
+            }
+        }
+    }
+
+    // A KeepAlive dictionary keeps the agent alive under its conditions
+    let kept_alive = plist.keep_alive == Some(true)
+        || plist
+            .extra
+            .iter()
+            .any(|(key, value)| key == "KeepAlive" && matches!(value, PlistValue::Dict(_)));
+    if plist.run_at_load == Some(true)
+        && kept_alive
+        && !plist
+            .label
+            .as_deref()
+            .is_some_and(|label| is_trusted_vendor(label, trusted_vendors))
+    {
+        findings.push(AuditFinding::new(
+            "persistent-unknown-vendor",
+            format!(
+                "{} runs at load and is kept alive, but isn't from a known vendor",
+                plist.label.as_deref().unwrap_or("This agent")
+            ),
+        ));
+    }
+
+    let created = fs::metadata(path)
+        .ok()
+        .and_then(|metadata| metadata.created().or_else(|_| metadata.modified()).ok());
+    if let Some(age) = created.and_then(|created| now.duration_since(created).ok())
+        && age.as_secs() < RECENT_AGENT_DAYS * 86_400
+    {
+        let days = age.as_secs() / 86_400;
+        findings.push(AuditFinding::new(
+            "recently-created",
+            match days {
+                0 => "The plist was created today".to_string(),
+                1 => "The plist was created yesterday".to_string(),
+                _ => format!("The plist was created {} days ago", days),
+            },
+        ));
+    }
+    findings
+}
+
+/// Audits the plist at `path`. Files that can't be parsed are flagged, and
+/// still get the checks that don't need their contents.
+fn audit_file(
+    path: &Path,
+    location: Option<TabLocation>,
+    trusted_vendors: &[String],
+) -> AuditedAgent {
+    let parsed = fs::read(path)
+        .map_err(color_eyre::Report::from)
+        .and_then(|bytes| {
+            if bytes.starts_with(b"bplist") {
+                return Err(color_eyre::eyre::eyre!("Binary plists aren't supported"));
+            }
+            parse_plist_xml(&String::from_utf8(bytes)?)
+        });
+    let mut findings = Vec::new();
+    let plist = parsed.unwrap_or_else(|e| {
+        findings.push(AuditFinding::new(
+            "unparseable-plist",
+            format!(
+                "The plist couldn't be read, so its program wasn't checked: {}",
+                e
+            ),
+        ));
+        PlistData::default()
+    });
+    findings.extend(audit_plist(
+        path,
+        &plist,
+        trusted_vendors,
+        std::time::SystemTime::now(),
+    ));
+    AuditedAgent {
+        location,
+        path: path.to_path_buf(),
+        findings,
+        program: plist
+            .program
+            .clone()
+            .or_else(|| plist.program_arguments.as_ref()?.first().cloned()),
+        label: plist.label,
+    }
+}
+
+/// Flagged agents, riskiest first.
+fn flagged_agents(agents: &[AuditedAgent]) -> Vec<&AuditedAgent> {
+    let mut flagged: Vec<&AuditedAgent> = agents
+        .iter()
+        .filter(|agent| !agent.findings.is_empty())
+        .collect();
+    flagged.sort_by(|a, b| b.risk().cmp(&a.risk()).then_with(|| a.path.cmp(&b.path)));
+    flagged
+}
+
+fn format_audit_json(agents: &[AuditedAgent]) -> String {
+    use serde_json::json;
+
+    let count = |risk: Risk| {
+        agents
+            .iter()
+            .flat_map(|agent| &agent.findings)
+            .filter(|finding| finding.risk == risk)
+            .count()
+    };
+    let flagged: Vec<serde_json::Value> = flagged_agents(agents)
+        .into_iter()
+        .map(|agent| {
+            json!({
+                "path": agent.path.display().to_string(),
+                "location": agent.location.map(|location| location.name()),
+                "label": agent.label,
+                "program": agent.program,
+                "risk": agent.risk().map(|risk| risk.name()),
+                "findings": agent.findings.iter().map(|finding| json!({
+                    "rule": finding.rule,
+                    "risk": finding.risk.name(),
+                    "message": finding.message,
+                })).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    format!(
+        "{:#}\n",
+        json!({
+            "scanned": agents.len(),
+            "flagged": flagged.len(),
+            "findings": {
+                "high": count(Risk::High),
+                "medium": count(Risk::Medium),
+                "low": count(Risk::Low),
+            },
+            "agents": flagged,
+        })
+    )
+}
+
+/// Finds `program` the way launchd does: as given when it contains a slash,
+/// otherwise in [`LAUNCHD_PATH`].
+fn resolve_program(program: &str) -> Option<PathBuf> {
+    if program.contains('/') {
+        let path = PathBuf::from(program);
+        return path.exists().then_some(path);
+    }
+    LAUNCHD_PATH
+        .split(':')
+        .map(|dir| Path::new(dir).join(program))
+        .find(|path| path.exists())
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path)
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Absolute log paths, StandardOutPath first, with the same file listed once.
+fn log_paths(plist: &PlistData) -> Vec<(&'static str, &Path)> {
+    let mut paths: Vec<(&'static str, &Path)> = Vec::new();
+    for (key, path) in [
+        ("StandardOutPath", plist.standard_out_path.as_deref()),
+        ("StandardErrorPath", plist.standard_error_path.as_deref()),
+    ] {
+        if let Some(path) = path.map(Path::new).filter(|path| path.is_absolute())
+            && !paths.iter().any(|(_, other)| *other == path)
+        {
+            paths.push((key, path));
+        }
+    }
+    paths
+}
+
+/// Parent directories of the log paths that don't exist yet.
+fn missing_log_directories(plist: &PlistData) -> Vec<PathBuf> {
+    let mut directories: Vec<PathBuf> = Vec::new();
+    for (_, path) in log_paths(plist) {
+        if let Some(parent) = path.parent()
+            && !parent.is_dir()
+            && !directories.iter().any(|dir| dir == parent)
+        {
+            directories.push(parent.to_path_buf());
+        }
+    }
+    directories
+}
+
+/// Checks the paths in `plist` against the filesystem: that the program can
+/// be found and run, and that the working and log directories exist. Relative
+/// paths are left to the `relative-path` rule.
+fn check_plist_paths(plist: &PlistData) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    let (key, program) = match plist.program.as_deref().filter(|p| !p.is_empty()) {
+        Some(program) => ("Program", Some(program)),
+        None => (
+            "ProgramArguments",
+            plist
+                .program_arguments
+                .as_ref()
+                .and_then(|args| args.first())
+                .map(String::as_str),
+        ),
+    };
+    if let Some(program) = program.filter(|p| p.starts_with('/') || !p.contains('/')) {
+        match resolve_program(program) {
+            None if program.contains('/') => diagnostics.push(Diagnostic::new(
+                "program-not-found",
+                Some(key),
+                format!("{} doesn't exist", program),
+            )),
+            None => diagnostics.push(Diagnostic::new(
+                "program-not-found",
+                Some(key),
+                format!(
+                    "{} isn't in launchd's PATH ({}), use an absolute path",
+                    program, LAUNCHD_PATH
+                ),
+            )),
+            Some(path) if !is_executable(&path) => diagnostics.push(Diagnostic::new(
+                "program-not-executable",
+                Some(key),
+                format!("{} isn't an executable file", path.display()),
+            )),
+            Some(_) => {}
+        }
+    }
+
+    if let Some(dir) = plist.working_directory.as_deref().map(Path::new)
+        && dir.is_absolute()
+        && !dir.is_dir()
+    {
+        diagnostics.push(Diagnostic::new(
+            "missing-working-directory",
+            Some("WorkingDirectory"),
+            format!("WorkingDirectory {} doesn't exist", dir.display()),
+        ));
+    }
+
+    let missing = missing_log_directories(plist);
+    for (key, path) in log_paths(plist) {
+        let Some(parent) = path.parent() else {
+            continue;
+        };
+        if missing.iter().any(|dir| dir == parent) {
+            diagnostics.push(
+                Diagnostic::new(
+                    "missing-log-directory",
+                    Some(key),
+                    format!(
+                        "{} can't be created because {} doesn't exist",
+                        path.display(),
+                        parent.display()
+                    ),
+                )
+                .with_fix(QuickFix::CreateLogDirectories),
+            );
+        } else if !is_writable(if path.exists() { path } else { parent }) {
+            diagnostics.push(Diagnostic::new(
+                "log-not-writable",
+                Some(key),
+                format!("{} isn't writable by you", path.display()),
+            ));
+        }
+    }
+
+    diagnostics
+}
+
+impl App {
+    pub async fn new() -> Result<Self> {
+        let user_agents_dir = TabLocation::User.get_directory()?;
+        let global_agents_dir = TabLocation::Global.get_directory()?;
+        let apple_agents_dir = TabLocation::Apple.get_directory()?;
+
+        // Create the app in loading state first
+        let mut app = Self {
+            running: false,
+            event_stream: EventStream::new(),
+            user_agents: Vec::new(),
+            global_agents: Vec::new(),
+            apple_agents: Vec::new(),
+            current_tab: TabLocation::User,
+            list_state: ListState::default(),
+            selected_plist: None,
+            selected_access: Access::ReadOnly,
+            selected_path: None,
+            pristine_plist: None,
+            unsaved_prompt: None,
+            navigation_after_save: None,
+            save_preview: None,
+            loaded_fingerprint: None,
+            conflict: None,
+            edit_history: std::collections::HashMap::new(),
+            user_agents_dir,
+            global_agents_dir,
+            apple_agents_dir,
+            focus: Focus::Sidebar,
+            current_field: FormField::Label,
+            editing: false,
+            editing_field: None,
+            edit_input: TextInput::default(),
+            edit_error: None,
+            list_editor: None,
+            picker: None,
+            fix_menu: None,
+            compare_mark: None,
+            comparison: None,
+            status_message: String::new(),
+            status_timer: 0,
+            filter_text: String::new(),
+            showing_exit_confirmation: false,
+            pending_transfer: None,
+            pending_privileged_action: None,
+            elevation_prompt: None,
+            form_scroll_offset: 0,
+            log_view: LogView::Stdout,
+            log_scroll_offset: 0,
+            log_lines: Vec::new(),
+            backup_entries: Vec::new(),
+            backup_list_state: ListState::default(),
+            backup_diff: Vec::new(),
+            backup_diff_scroll: 0,
+            audit_agents: Vec::new(),
+            audit_list_state: ListState::default(),
+            watcher: None,
+            disk_changed: None,
+            acknowledged_snapshot: Snapshot::default(),
+            snapshot_error: false,
+            current_snapshot: Snapshot::default(),
+            agent_changes: Vec::new(),
+            loading: true,
+            loading_message: "Initializing Launch Agent Manager...".to_string(),
+            loading_progress: 0.0,
+            loading_step: 1,
+        };
+
+        // Load agents with progress updates
+        app.loading_message = "📂 Loading User LaunchAgents...".to_string();
+        app.loading_progress = 0.1;
+        let user_agents = Self::load_launch_agents(&app.user_agents_dir)?;
+
+        app.loading_message = "🌐 Loading Global LaunchAgents...".to_string();
+        app.loading_progress = 0.4;
+        let global_agents = Self::load_launch_agents(&app.global_agents_dir)?;
+
+        app.loading_message = "🍎 Loading Apple LaunchAgents...".to_string();
+        app.loading_progress = 0.7;
+        let apple_agents = Self::load_launch_agents(&app.apple_agents_dir)?;
+
+        app.loading_message = "✨ Finalizing interface...".to_string();
+        app.loading_progress = 0.9;
+
+        // Update the app with loaded data
+        app.user_agents = user_agents;
+        app.global_agents = global_agents;
+        app.apple_agents = apple_agents;
+
+        let mut list_state = ListState::default();
+        if !app.user_agents.is_empty() {
+            list_state.select(Some(0));
+        }
+        app.list_state = list_state;
+
+        // Without a readable snapshot, changes are tracked from startup only
+        let dirs = app.agent_directories();
+        let current = Snapshot::take(&dirs);
+        let (acknowledged, error) = match Snapshot::acknowledged(&dirs) {
+            Ok(snapshot) => (snapshot, None),
+            Err(e) => (current.clone(), Some(e)),
+        };
+        app.acknowledged_snapshot = acknowledged;
+        app.current_snapshot = current;
+        if let Some(e) = error {
+            app.snapshot_error = true;
+            app.set_status_message(format!(
+                "⚠ Not tracking agent changes across sessions: {}",
+                e
+            ));
+        }
+        app.refresh_agent_changes();
+
+        match AgentWatcher::new(&app.agent_directories()) {
+            Ok(watcher) => app.watcher = Some(watcher),
+            Err(e) => {
+                app.set_status_message(format!("⚠ Not watching the agent directories: {}", e))
+            }
+        }
+
+        // Complete loading
+        app.loading = false;
+        app.loading_progress = 1.0;
+
+        Ok(app)
+    }
+
+    pub fn new_with_loading() -> Self {
+        Self {
+            running: false,
+            event_stream: EventStream::new(),
+            user_agents: Vec::new(),
+            global_agents: Vec::new(),
+            apple_agents: Vec::new(),
+            current_tab: TabLocation::User,
+            list_state: ListState::default(),
+            selected_plist: None,
+            selected_access: Access::ReadOnly,
+            selected_path: None,
+            pristine_plist: None,
+            unsaved_prompt: None,
+            navigation_after_save: None,
+            save_preview: None,
+            loaded_fingerprint: None,
+            conflict: None,
+            edit_history: std::collections::HashMap::new(),
+            user_agents_dir: PathBuf::new(),
+            global_agents_dir: PathBuf::new(),
+            apple_agents_dir: PathBuf::new(),
+            focus: Focus::Sidebar,
+            current_field: FormField::Label,
+            editing: false,
+            editing_field: None,
+            edit_input: TextInput::default(),
+            edit_error: None,
+            list_editor: None,
+            picker: None,
+            fix_menu: None,
+            compare_mark: None,
+            comparison: None,
+            status_message: String::new(),
+            status_timer: 0,
+            filter_text: String::new(),
+            showing_exit_confirmation: false,
+            pending_transfer: None,
+            pending_privileged_action: None,
+            elevation_prompt: None,
+            form_scroll_offset: 0,
+            log_view: LogView::Stdout,
+            log_scroll_offset: 0,
+            log_lines: Vec::new(),
+            backup_entries: Vec::new(),
+            backup_list_state: ListState::default(),
+            backup_diff: Vec::new(),
+            backup_diff_scroll: 0,
+            audit_agents: Vec::new(),
+            audit_list_state: ListState::default(),
+            watcher: None,
+            disk_changed: None,
+            acknowledged_snapshot: Snapshot::default(),
+            snapshot_error: false,
+            current_snapshot: Snapshot::default(),
+            agent_changes: Vec::new(),
+            loading: true,
+            loading_message: "🚀 Starting Launch Agent Manager...".to_string(),
+            loading_progress: 0.0,
+            loading_step: 0,
+        }
+    }
+
+    fn get_current_agents(&self) -> &Vec<LaunchAgent> {
+        self.get_tab_agents(self.current_tab)
+    }
+
+    fn get_current_agents_mut(&mut self) -> &mut Vec<LaunchAgent> {
+        self.get_tab_agents_mut(self.current_tab)
+    }
+
+    fn get_current_directory(&self) -> &PathBuf {
+        self.get_tab_directory(self.current_tab)
+    }
+
+    fn get_tab_agents(&self, tab: TabLocation) -> &Vec<LaunchAgent> {
+        match tab {
+            TabLocation::User => &self.user_agents,
+            TabLocation::Global => &self.global_agents,
+            TabLocation::Apple => &self.apple_agents,
+        }
+    }
+
+    fn get_tab_agents_mut(&mut self, tab: TabLocation) -> &mut Vec<LaunchAgent> {
+        match tab {
+            TabLocation::User => &mut self.user_agents,
+            TabLocation::Global => &mut self.global_agents,
+            TabLocation::Apple => &mut self.apple_agents,
+        }
+    }
+
+    fn get_tab_directory(&self, tab: TabLocation) -> &PathBuf {
+        match tab {
+            TabLocation::User => &self.user_agents_dir,
+            TabLocation::Global => &self.global_agents_dir,
+            TabLocation::Apple => &self.apple_agents_dir,
+        }
+    }
+
+    fn get_selected_filename(&self) -> Option<String> {
+        let selected = self.list_state.selected()?;
+        self.get_filtered_agents()
+            .get(selected)
+            .map(|agent| agent.filename.clone())
+    }
+
+    /// Re-reads a tab's directory, keeping the sidebar selection on the same
+    /// file when it still exists.
+    fn reload_tab_agents(&mut self, tab: TabLocation) {
+        let selected_filename = if tab == self.current_tab {
+            self.get_selected_filename()
+        } else {
+            None
+        };
+
+        let agents = Self::load_launch_agents(self.get_tab_directory(tab)).unwrap_or_default();
+        *self.get_tab_agents_mut(tab) = agents;
+
+        if tab == self.current_tab {
+            self.reselect(selected_filename);
+        }
+    }
+
+    /// Re-reads only `paths` in a tab's agent list, adding, updating or
+    /// dropping each one, and keeps the sidebar selection on the same file.
+    fn update_tab_agents(&mut self, tab: TabLocation, paths: &[&PathBuf]) {
+        let selected_filename = if tab == self.current_tab {
+            self.get_selected_filename()
+        } else {
+            None
+        };
+
+        let agents = self.get_tab_agents_mut(tab);
+        for path in paths {
+            let Some(filename) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            agents.retain(|agent| agent.filename != filename);
+            if let Some(agent) = Self::load_launch_agent(path) {
+                agents.push(agent);
+            }
+        }
+        agents.sort_by(|a, b| a.filename.cmp(&b.filename));
+
+        if tab == self.current_tab {
+            self.reselect(selected_filename);
+        }
+    }
+
+    /// Selects `filename` in the sidebar, or the row nearest the current
+    /// selection when it's gone.
+    fn reselect(&mut self, filename: Option<String>) {
+        let filtered_agents = self.get_filtered_agents();
+        let selection = filename
+            .and_then(|filename| {
+                filtered_agents
+                    .iter()
+                    .position(|agent| agent.filename == filename)
+            })
+            .or_else(|| {
+                let last = filtered_agents.len().checked_sub(1)?;
+                Some(self.list_state.selected().unwrap_or(0).min(last))
+            });
+        self.list_state.select(selection);
+    }
+
+    /// Applies filesystem events from the agent directories: the agent lists
+    /// pick up added, changed and removed plists, and the open form is
+    /// flagged when its file changed underneath it.
+    fn apply_watch_events(&mut self) {
+        let Some(watcher) = &self.watcher else {
+            return;
+        };
+        let paths = watcher.changed_plists();
+        if paths.is_empty() {
+            return;
+        }
+
+        for tab in [TabLocation::User, TabLocation::Global, TabLocation::Apple] {
+            let dir = self.get_tab_directory(tab).clone();
+            let changed: Vec<&PathBuf> = paths
+                .iter()
+                .filter(|path| path.parent() == Some(dir.as_path()))
+                .collect();
+            if !changed.is_empty() {
+                self.update_tab_agents(tab, &changed);
+            }
+        }
+
+        if let Some(path) = self.selected_path.clone()
+            && paths.contains(&path)
+            && self.loaded_fingerprint.is_some()
+            && self.disk_changed != self.loaded_fingerprint
+            && self.changed_on_disk()
+        {
+            self.disk_changed = self.loaded_fingerprint.clone();
+            let filename = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            self.set_status_message(if path.exists() {
+                format!("⚠ {} changed on disk, press r to reload", filename)
+            } else {
+                format!("⚠ {} was deleted from disk", filename)
+            });
+        }
+        for path in &paths {
+            self.current_snapshot.update(path);
+        }
+        self.refresh_agent_changes();
+    }
+
+    /// Whether the open agent's file changed since it was loaded, as noticed
+    /// by the watcher.
+    fn open_file_changed(&self) -> bool {
+        self.disk_changed.is_some() && self.disk_changed == self.loaded_fingerprint
+    }
+
+    fn load_launch_agents(dir: &PathBuf) -> Result<Vec<LaunchAgent>> {
+        let mut agents = Vec::new();
+
+        if dir.exists() {
+            for entry in fs::read_dir(dir)? {
+                if let Some(agent) = Self::load_launch_agent(&entry?.path()) {
+                    agents.push(agent);
+                }
+            }
+        }
+
+        agents.sort_by(|a, b| a.filename.cmp(&b.filename));
+        Ok(agents)
+    }
+
+    /// Reads the agent at `path`, or `None` if it isn't a plist file.
+    fn load_launch_agent(path: &Path) -> Option<LaunchAgent> {
+        if !(path.is_file() && path.extension().is_some_and(|ext| ext == "plist")) {
+            return None;
+        }
+        let filename = path.file_name()?.to_str()?;
+        let parsed = fs::read_to_string(path)
+            .map_err(color_eyre::Report::from)
+            .and_then(|content| parse_plist_xml(&content));
+        let (label, diagnostics) = match parsed {
+            Ok(plist) => {
+                let mut diagnostics = lint_plist(&plist, Some(filename));
+                diagnostics.extend(check_plist_paths(&plist));
+                (plist.label.clone(), diagnostics)
+            }
+            Err(e) => (
+                None,
+                vec![Diagnostic::new("invalid-plist", None, e.to_string())],
+            ),
+        };
+        let label = label.unwrap_or_else(|| filename.replace(".plist", ""));
+
+        let status = Self::check_agent_status(&label);
+        let enabled = Self::check_agent_enabled(&label);
+
+        Some(LaunchAgent {
+            filename: filename.to_string(),
+            label: Some(label),
+            status,
+            enabled,
+            diagnostics,
+        })
+    }
+
+    fn extract_label_from_file(path: &Path) -> Option<String> {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| parse_plist_xml(&content).ok())
+            .map(|plist| plist.label)?
+    }
+
+    fn check_agent_status(label: &str) -> AgentStatus {
+        // Check if agent is running using launchctl
+        let uid = std::process::Command::new("id")
+            .arg("-u")
+            .output()
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+            .unwrap_or_else(|_| "501".to_string());
+
+        if let Ok(output) = std::process::Command::new("launchctl")
+            .args(["print", &format!("gui/{}/{}", uid, label)])
+            .output()
+        {
+            let output_str = String::from_utf8_lossy(&output.stdout);
+            match output_str.trim() {
+                "No such service" => return AgentStatus::Stopped,
+                _ if output_str.contains("state = running") => return AgentStatus::Running,
+                _ if output_str.contains("state = stopped") => return AgentStatus::Stopped,
+                _ => return AgentStatus::Error,
+            }
+        }
+        AgentStatus::Unknown
+    }
+
+    fn check_agent_enabled(label: &str) -> bool {
+        let uid = std::process::Command::new("id")
+            .arg("-u")
+            .output()
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+            .unwrap_or_else(|_| "501".to_string());
+
+        // Check if agent is enabled/loaded
+        if let Ok(output) = std::process::Command::new("launchctl")
+            .args(["print-disabled", &format!("gui/{}", uid)])
+            .output()
+        {
+            let output_str = String::from_utf8_lossy(&output.stdout);
+            !output_str.contains(&format!("\"{}\": false", label))
+        } else {
+            // If launchctl command fails, assume it's not enabled
+            false
+        }
+    }
+
+    fn load_selected_plist(&mut self) -> Result<()> {
+        if let Some(selected) = self.list_state.selected() {
+            let filtered_agents = self.get_filtered_agents();
+            if let Some(agent) = filtered_agents.get(selected) {
+                let file_path = self.get_current_directory().join(&agent.filename);
+                self.load_plist_from(file_path)?;
+                self.form_scroll_offset = 0;
+                self.log_lines.clear();
+                self.log_scroll_offset = 0;
+                let duplicates = self
+                    .selected_plist
+                    .as_ref()
+                    .and_then(|plist| plist.environment_variables.as_deref())
+                    .map(duplicate_env_names)
+                    .unwrap_or_default();
+                if !duplicates.is_empty() {
+                    self.set_status_message(format!(
+                        "⚠ Duplicate environment variables: {} (launchd uses the last value)",
+                        duplicates.join(", ")
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn load_plist_from(&mut self, file_path: PathBuf) -> Result<()> {
+        let content = fs::read_to_string(&file_path)?;
+
+        let plist_data = self.parse_plist(&content)?;
+        // History only carries over while the file still matches its last step
+        let history_applies = self
+            .edit_history
+            .get(&file_path)
+            .and_then(|history| history.undo.last())
+            .is_some_and(|change| change.after == plist_data);
+        if !history_applies {
+            self.edit_history.remove(&file_path);
+        }
+        self.selected_access = self.current_tab.access_for(&file_path);
+        self.pristine_plist = Some(plist_data.clone());
+        self.selected_plist = Some(plist_data);
+        self.loaded_fingerprint = FileFingerprint::read(&file_path).ok();
+        self.selected_path = Some(file_path);
+        Ok(())
+    }
+
+    /// Lint results for the loaded plist, unsaved edits included.
+    fn selected_diagnostics(&self) -> Vec<Diagnostic> {
+        let filename = self
+            .selected_path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .and_then(|name| name.to_str());
+        self.selected_plist
+            .as_ref()
+            .map(|plist| {
+                let mut diagnostics = lint_plist(plist, filename);
+                diagnostics.extend(check_plist_paths(plist));
+                diagnostics
+            })
+            .unwrap_or_default()
+    }
+
+    /// Quick fix for `missing-log-directory`.
+    fn create_missing_log_directories(&mut self) {
+        let Some(plist) = &self.selected_plist else {
+            return;
+        };
+        let directories = missing_log_directories(plist);
+        if directories.is_empty() {
+            self.set_status_message("✓ All log directories exist".to_string());
+            return;
+        }
+        for dir in &directories {
+            if let Err(e) = fs::create_dir_all(dir) {
+                self.set_status_message(format!("✗ Failed to create {}: {}", dir.display(), e));
+                return;
+            }
+        }
+        let created: Vec<String> = directories
+            .iter()
+            .map(|dir| dir.display().to_string())
+            .collect();
+        self.set_status_message(format!("✓ Created {}", created.join(", ")));
+    }
+
+    fn open_fix_menu(&mut self) {
+        let mut fixes: Vec<QuickFix> = Vec::new();
+        for fix in self
+            .selected_diagnostics()
+            .into_iter()
+            .filter_map(|diagnostic| diagnostic.fix)
+        {
+            if !fixes.contains(&fix) {
+                fixes.push(fix);
+            }
+        }
+        if fixes.is_empty() {
+            self.set_status_message("✓ No quick fixes for this agent".to_string());
+            return;
+        }
+        self.fix_menu = Some(FixMenu { fixes, selected: 0 });
+    }
+
+    fn handle_fix_menu_keys(&mut self, key: KeyEvent) {
+        let Some(menu) = &mut self.fix_menu else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.fix_menu = None,
+            KeyCode::Char('j') | KeyCode::Down => {
+                menu.selected = (menu.selected + 1).min(menu.fixes.len() - 1);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                menu.selected = menu.selected.saturating_sub(1);
+            }
+            KeyCode::Enter => {
+                let fix = menu.fixes[menu.selected].clone();
+                self.fix_menu = None;
+                self.apply_fix(fix);
+            }
+            _ => {}
+        }
+    }
+
+    fn apply_fix(&mut self, fix: QuickFix) {
+        match &fix {
+            QuickFix::CreateLogDirectories => self.create_missing_log_directories(),
+            QuickFix::RenameFile(filename) => self.rename_selected_file(filename),
+            _ => {
+                if self.selected_access == Access::ReadOnly {
+                    self.set_status_message(
+                        "✗ This agent is read-only, press o to copy it to User as an override"
+                            .to_string(),
+                    );
+                    return;
+                }
+                let Some(plist) = &mut self.selected_plist else {
+                    return;
+                };
+                let before = plist.clone();
+                fix.apply(plist);
+                self.set_status_message(format!("✓ {}", fix.description()));
+                self.record_named_change(fix.field(), "quick fix", before);
+            }
+        }
+    }
+
+    /// Quick fix for `label-filename-mismatch`. The rename happens on disk
+    /// right away, so it needs a writable agent without unsaved edits.
+    fn rename_selected_file(&mut self, filename: &str) {
+        let Some(path) = self.selected_path.clone() else {
+            return;
+        };
+        if self.selected_access != Access::Writable {
+            self.set_status_message(format!(
+                "✗ Can't rename {}, it isn't writable by you",
+                path.display()
+            ));
+            return;
+        }
+        if self.is_dirty() {
+            self.set_status_message(
+                "✗ Save or discard your edits before renaming the file".to_string(),
+            );
+            return;
+        }
+        let target = path.with_file_name(filename);
+        if target.exists() {
+            self.set_status_message(format!("✗ {} already exists", target.display()));
+            return;
+        }
+        if let Err(e) = fs::rename(&path, &target) {
+            self.set_status_message(format!("✗ Failed to rename: {}", e));
+            return;
+        }
+
+        if let Some(history) = self.edit_history.remove(&path) {
+            self.edit_history.insert(target.clone(), history);
+        }
+        self.acknowledge_own_write(&path);
+        self.acknowledge_own_write(&target);
+        self.selected_path = Some(target.clone());
+        self.loaded_fingerprint = FileFingerprint::read(&target).ok();
+        self.reload_tab_agents(self.current_tab);
+        if let Some(position) = self
+            .get_filtered_agents()
+            .iter()
+            .position(|agent| agent.filename == filename)
+        {
+            self.list_state.select(Some(position));
+        }
+        self.set_status_message(format!("✓ Renamed to {}", filename));
+    }
+
+    /// Whether the loaded plist was changed by something other than lam.
+    fn changed_on_disk(&self) -> bool {
+        match (&self.selected_path, &self.loaded_fingerprint) {
+            (Some(path), Some(fingerprint)) => fingerprint.is_stale(path),
+            (Some(path), None) => path.exists(),
+            _ => false,
+        }
+    }
+
+    fn open_conflict(&mut self, while_saving: bool) {
+        let theirs = self
+            .selected_path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| self.parse_plist(&content).ok());
+        self.conflict = Some(Conflict {
+            theirs,
+            while_saving,
+            merge: None,
+            merge_selected: 0,
+        });
+    }
+
+    /// Re-reads the loaded plist, asking first if that would lose edits.
+    fn refresh_from_disk(&mut self) -> Result<()> {
+        let Some(path) = self.selected_path.clone() else {
+            return Ok(());
+        };
+        let filename = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !self.changed_on_disk() {
+            self.set_status_message(format!("✓ {} is unchanged on disk", filename));
+        } else if self.is_dirty() {
+            self.open_conflict(false);
+        } else {
+            self.load_plist_from(path)?;
+            self.set_status_message(format!("↻ Reloaded {} from disk", filename));
+        }
+        Ok(())
+    }
+
+    fn clear_selected_plist(&mut self) {
+        self.selected_plist = None;
+        self.pristine_plist = None;
+        self.selected_path = None;
+        self.loaded_fingerprint = None;
+    }
+
+    /// Whether the form holds edits that haven't been written to disk.
+    fn is_dirty(&self) -> bool {
+        self.selected_plist.is_some() && self.selected_plist != self.pristine_plist
+    }
+
+    /// Runs `navigation` right away, or asks what to do with unsaved edits first.
+    fn navigate(&mut self, navigation: PendingNavigation) -> Result<()> {
+        if self.is_dirty() {
+            self.unsaved_prompt = Some(navigation);
+            Ok(())
+        } else {
+            self.perform_navigation(navigation)
+        }
+    }
+
+    /// Carries on with the navigation the unsaved-changes prompt saved for
+    /// once the save is confirmed and done, including one that had to go
+    /// through sudo. A save that failed keeps the edits and drops the
+    /// navigation.
+    fn continue_navigation(&mut self) -> Result<()> {
+        if self.save_preview.is_some()
+            || self.elevation_prompt.is_some()
+            || self.pending_privileged_action.is_some()
+        {
+            return Ok(());
+        }
+        if let Some(navigation) = self.navigation_after_save.take()
+            && !self.is_dirty()
+        {
+            self.perform_navigation(navigation)?;
+        }
+        Ok(())
+    }
+
+    fn perform_navigation(&mut self, navigation: PendingNavigation) -> Result<()> {
+        match navigation {
+            PendingNavigation::LoadAgent => self.load_selected_plist()?,
+            PendingNavigation::BrowseBackups => {
+                self.load_selected_plist()?;
+                self.open_backup_browser();
+            }
+            PendingNavigation::SwitchTab(tab) => self.switch_to_tab(tab),
+            PendingNavigation::OpenAgent(tab, filename) => {
+                self.switch_to_tab(tab);
+                self.filter_text.clear();
+                let index = self
+                    .get_filtered_agents()
+                    .iter()
+                    .position(|agent| agent.filename == filename);
+                if let Some(index) = index {
+                    self.list_state.select(Some(index));
+                    self.load_selected_plist()?;
+                    self.focus = Focus::Form;
+                }
+            }
+            PendingNavigation::Transfer(mode) => self.request_transfer(mode),
+            PendingNavigation::RestoreBackup => self.restore_selected_backup(),
+            PendingNavigation::Quit => self.quit(),
+        }
+        Ok(())
+    }
+
+    pub fn parse_plist(&self, content: &str) -> Result<PlistData> {
+        parse_plist_xml(content)
+    }
+
+    pub async fn run_with_loading(mut terminal: DefaultTerminal) -> Result<()> {
+        // Create app with loading state
+        let mut app = App::new_with_loading();
+        app.running = true;
+
+        // Show loading screen and load data asynchronously
+        let loading_task = tokio::spawn(async move { App::new().await });
+
+        // Keep showing loading screen until data is loaded
+        loop {
+            terminal.draw(|frame| app.draw_loading_screen(frame))?;
+
+            // Handle any key events during loading (like quit)
+            if let Ok(Some(Ok(crossterm::event::Event::Key(key)))) = tokio::time::timeout(
+                tokio::time::Duration::from_millis(50),
+                app.event_stream.next(),
+            )
+            .await
+                && matches!(
+                    key.code,
+                    crossterm::event::KeyCode::Esc | crossterm::event::KeyCode::Char('q')
+                )
+                && key.kind == crossterm::event::KeyEventKind::Press
+            {
+                app.running = false;
+                break;
+            }
+
+            // Check if loading is complete
+            if loading_task.is_finished() {
+                match loading_task.await {
+                    Ok(Ok(loaded_app)) => {
+                        app = loaded_app;
+                        app.running = true;
+                        break;
+                    }
+                    Ok(Err(e)) => return Err(e),
+                    Err(e) => return Err(color_eyre::eyre::eyre!("Loading task failed: {}", e)),
+                }
+            }
+
+            // Update loading animation
+            app.loading_step = app.loading_step.wrapping_add(1);
+
+            // Small delay for animation
+            tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+        }
+
+        // Run the main application if not quit during loading
+        if app.running {
+            app.run(terminal).await
+        } else {
+            Ok(())
+        }
+    }
+
+    pub async fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
+        self.running = true;
+        while self.running {
+            terminal.draw(|frame| self.draw(frame))?;
+            self.handle_crossterm_events().await?;
+            self.apply_watch_events();
+
+            if let Some(action) = self.pending_privileged_action.take() {
+                drop(terminal);
+                let authenticated = self.authenticate_sudo();
+                terminal = init_terminal();
+                if authenticated {
+                    self.perform_privileged_action(action);
+                } else {
+                    self.set_status_message("✗ Administrator authentication failed".to_string());
+                }
+                self.continue_navigation()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Leaves the alternate screen so `sudo` can prompt for a password on the
+    /// terminal. Credentials are cached by sudo, so the privileged commands
+    /// that follow can run non-interactively with `sudo -n`.
+    fn authenticate_sudo(&mut self) -> bool {
+        // Dropping the event stream stops its reader thread so it can't swallow
+        // the password keystrokes meant for sudo.
+        drop(std::mem::replace(
+            &mut self.event_stream,
+            EventStream::new(),
+        ));
+        restore_terminal();
+        println!("🔐 lam needs administrator privileges to modify /Library/LaunchAgents");
+        std::process::Command::new("sudo")
+            .args(["-v", "-p", "Password for %u: "])
+            .status()
+            .is_ok_and(|status| status.success())
+    }
+
+    /// Runs a privileged action right away when sudo already has cached
+    /// credentials, otherwise hands it to the run loop to authenticate first.
+    fn request_privileged_action(&mut self, action: PrivilegedAction) {
+        if Self::is_root() || Self::sudo_credentials_cached() {
+            self.perform_privileged_action(action);
+        } else {
+            self.pending_privileged_action = Some(action);
+        }
+    }
+
+    fn perform_privileged_action(&mut self, action: PrivilegedAction) {
+        match action {
+            PrivilegedAction::Transfer(transfer) => self.finish_transfer(transfer),
+            PrivilegedAction::Save {
+                filename,
+                path,
+                contents,
+            } => {
+                if let Err(e) = backup_plist(&path) {
+                    self.set_status_message(format!("✗ Backup failed, not saving: {}", e));
+                    return;
+                }
+                let target = path.to_string_lossy().to_string();
+                match Self::run_helper_as_root(&["write", &target], Some(contents.as_bytes())) {
+                    Ok(()) => {
+                        self.reload_after_save(&filename, path.clone());
+                        // A restored backup differs from what the form shows
+                        if self.selected_path.as_ref() == Some(&path) {
+                            let _ = self.load_plist_from(path);
+                            self.refresh_backups();
+                        }
+                    }
+                    Err(e) => self.set_status_message(format!("✗ Failed to save: {}", e)),
+                }
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        // If loading, show loading screen instead
+        if self.loading {
+            self.draw_loading_screen(frame);
+            return;
+        }
+
+        // Clear background with theme color
+        let background = Block::default().style(Style::default().bg(Theme::BACKGROUND));
+        frame.render_widget(background, frame.area());
+
+        let main_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3), // Tab bar
+                Constraint::Length(3), // Search bar
+                Constraint::Min(5),    // Main content (minimum height)
+                Constraint::Length(3), // Status bar
+            ])
+            .margin(1) // Add margin around the entire layout
+            .split(frame.area());
+
+        let content_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+            .spacing(1) // Add space between panels
+            .split(main_chunks[2]);
+
+        self.draw_tab_bar(frame, main_chunks[0]);
+        self.draw_search_bar(frame, main_chunks[1]);
+        self.draw_sidebar(frame, content_chunks[0]);
+        if self.focus == Focus::Logs {
+            self.draw_log_panel(frame, content_chunks[1]);
+        } else if self.focus == Focus::Backups {
+            self.draw_backup_panel(frame, content_chunks[1]);
+        } else if self.focus == Focus::Audit {
+            self.draw_audit_panel(frame, content_chunks[1]);
+        } else {
+            self.draw_main_panel(frame, content_chunks[1]);
+        }
+        self.draw_status_bar(frame, main_chunks[3]);
+
+        if let Some(editor) = &self.list_editor {
+            self.draw_list_editor(frame, editor.clone());
+        }
+
+        if let Some(menu) = &self.fix_menu {
+            self.draw_fix_menu(frame, menu.clone());
+        }
+
+        if let Some(picker) = &self.picker {
+            self.draw_picker(frame, picker.clone());
+        }
+
+        if let Some(transfer) = &self.pending_transfer {
+            self.draw_transfer_confirmation(frame, transfer.clone());
+        }
+
+        if let Some(PrivilegedAction::Save { path, .. }) = &self.elevation_prompt {
+            self.draw_elevation_prompt(frame, path.clone());
+        }
+
+        if self.unsaved_prompt.is_some() {
+            self.draw_unsaved_prompt(frame);
+        }
+
+        if let Some(preview) = &self.save_preview {
+            self.draw_save_preview(frame, preview.clone());
+        }
+
+        if let Some(conflict) = &self.conflict {
+            self.draw_conflict(frame, conflict.clone());
+        }
+
+        if let Some(comparison) = &self.comparison {
+            self.draw_comparison(frame, comparison.clone());
+        }
+
+        // Draw exit confirmation dialog if showing
+        if self.showing_exit_confirmation {
+            self.draw_exit_confirmation(frame);
+        }
+    }
+
+    fn draw_tab_bar(&mut self, frame: &mut Frame, area: Rect) {
+        let tabs = [TabLocation::User, TabLocation::Global, TabLocation::Apple];
+        let tab_width = area.width / 3;
+
+        let tab_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Length(tab_width),
+                Constraint::Length(tab_width),
+                Constraint::Length(tab_width),
+            ])
+            .split(area);
+
+        for (i, tab) in tabs.iter().enumerate() {
+            let is_active = *tab == self.current_tab;
+            let agent_count = match tab {
+                TabLocation::User => self.user_agents.len(),
+                TabLocation::Global => self.global_agents.len(),
+                TabLocation::Apple => self.apple_agents.len(),
+            };
+
+            let (border_style, title_style, bg_style) = if is_active {
+                (
+                    Style::default().fg(Theme::BORDER_FOCUSED),
+                    Style::default()
+                        .fg(Theme::ACCENT_PRIMARY)
+                        .add_modifier(Modifier::BOLD),
+                    Style::default().bg(Theme::HIGHLIGHT),
+                )
+            } else {
+                (
+                    Style::default().fg(Theme::BORDER_UNFOCUSED),
+                    Style::default().fg(Theme::TEXT_DIM),
+                    Style::default().bg(Theme::BACKGROUND),
+                )
+            };
+
+            let title = format!("{} ({})", tab.get_display_name(), agent_count);
+            let hint = format!("[{}]", i + 1);
+
+            let tab_content = vec![Line::from(vec![
+                Span::styled(hint, Style::default().fg(Theme::ACCENT_MUTED)),
+                Span::raw(" "),
+                Span::styled(title, title_style),
+            ])];
+
+            let tab_widget = Paragraph::new(tab_content)
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded)
+                        .border_style(border_style)
+                        .style(bg_style),
+                )
+                .alignment(ratatui::layout::Alignment::Center);
+
+            frame.render_widget(tab_widget, tab_chunks[i]);
+        }
+    }
+
+    fn draw_search_bar(&mut self, frame: &mut Frame, area: Rect) {
+        let search_text = if self.focus == Focus::Search {
+            if self.filter_text.is_empty() {
+                "│".to_string()
+            } else {
+                format!("{}│", self.filter_text)
+            }
+        } else if self.filter_text.is_empty() {
+            "Type to filter agents...".to_string()
+        } else {
+            self.filter_text.clone()
+        };
+
+        let (search_style, border_style, title_style) = if self.focus == Focus::Search {
+            (
+                Style::default()
+                    .fg(Theme::BACKGROUND)
+                    .bg(Theme::ACCENT_PRIMARY),
+                Style::default().fg(Theme::BORDER_FOCUSED),
+                Style::default()
+                    .fg(Theme::ACCENT_PRIMARY)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            (
+                Style::default().fg(Theme::FOREGROUND).bg(Theme::BACKGROUND),
+                Style::default().fg(Theme::BORDER_UNFOCUSED),
+                Style::default().fg(Theme::TEXT_DIM),
+            )
+        };
+
+        let title = if self.filter_text.is_empty() {
+            "🔍 Search"
+        } else {
+            "🔍 Filtering"
+        };
+
+        let search_widget = Paragraph::new(search_text)
+            .block(
+                Block::default()
+                    .title(Line::from(vec![Span::styled(title, title_style)]))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(border_style)
+                    .style(Style::default().bg(Theme::BACKGROUND)),
+            )
+            .style(search_style);
+
+        frame.render_widget(search_widget, area);
+    }
+
+    fn get_filtered_agents(&self) -> Vec<&LaunchAgent> {
+        let current_agents = self.get_current_agents();
+        if self.filter_text.is_empty() {
+            current_agents.iter().collect()
+        } else {
+            current_agents
+                .iter()
+                .filter(|agent| {
+                    let search_text = self.filter_text.to_lowercase();
+                    agent.filename.to_lowercase().contains(&search_text)
+                        || agent
+                            .label
+                            .as_ref()
+                            .map(|label| label.to_lowercase().contains(&search_text))
+                            .unwrap_or(false)
+                })
+                .collect()
+        }
+    }
+
+    fn draw_sidebar(&mut self, frame: &mut Frame, area: Rect) {
+        let filtered_agents: Vec<LaunchAgent> =
+            self.get_filtered_agents().into_iter().cloned().collect();
+        let current_directory = self.get_current_directory().clone();
+        let modified_path = self.selected_path.clone().filter(|_| self.is_dirty());
+        let compare_mark = self.compare_mark.clone();
+        let agent_changes = self.agent_changes.clone();
+        let selected_diagnostics = self
+            .selected_path
+            .clone()
+            .map(|path| (path, self.selected_diagnostics()));
+        let items: Vec<ListItem> = filtered_agents
+            .iter()
+            .map(|agent| {
+                let (status_icon, status_color) = match agent.status {
+                    AgentStatus::Running => ("●", Theme::ACCENT_SECONDARY),
+                    AgentStatus::Stopped => ("●", Theme::ACCENT_ERROR),
+                    AgentStatus::Error => ("✗", Theme::ACCENT_ERROR),
+                    AgentStatus::Unknown => ("?", Theme::SUBTLE),
+                };
+
+                let (enabled_icon, enabled_color) = if agent.enabled {
+                    ("◉", Theme::ACCENT_MUTED)
+                } else {
+                    ("○", Theme::SUBTLE)
+                };
+
+                let label = agent.label.as_deref().unwrap_or(&agent.filename);
+                let display_name = if label.len() > 35 {
+                    format!("{}...", &label[..32])
+                } else {
+                    label.to_string()
+                };
+
+                let mut spans = vec![
+                    Span::styled(
+                        status_icon,
+                        Style::default()
+                            .fg(status_color)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(" "),
+                    Span::styled(enabled_icon, Style::default().fg(enabled_color)),
+                    Span::raw("  "),
+                    Span::styled(display_name, Style::default().fg(Theme::FOREGROUND)),
+                ];
+                let agent_path = current_directory.join(&agent.filename);
+                if modified_path.as_ref() == Some(&agent_path) {
+                    spans.push(Span::styled(
+                        " ✎",
+                        Style::default()
+                            .fg(Theme::ACCENT_WARNING)
+                            .add_modifier(Modifier::BOLD),
+                    ));
+                }
+                if let Some((_, change)) =
+                    agent_changes.iter().find(|(path, _)| *path == agent_path)
+                {
+                    spans.push(Span::styled(
+                        format!(" {}", change.name()),
+                        Style::default()
+                            .fg(change.color())
+                            .add_modifier(Modifier::BOLD),
+                    ));
+                }
+                if compare_mark.as_ref() == Some(&agent_path) {
+                    spans.push(Span::styled(
+                        " ⇄",
+                        Style::default()
+                            .fg(Theme::ACCENT_PRIMARY)
+                            .add_modifier(Modifier::BOLD),
+                    ));
+                }
+                // The open agent is linted with its unsaved edits
+                let diagnostics = match &selected_diagnostics {
+                    Some((path, diagnostics)) if *path == agent_path => diagnostics,
+                    _ => &agent.diagnostics,
+                };
+                if let Some(worst) = diagnostics.iter().map(|d| d.severity).max() {
+                    spans.push(Span::styled(
+                        format!(" {}{}", worst.icon(), diagnostics.len()),
+                        Style::default().fg(worst.color()),
+                    ));
+                }
+
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let (border_style, title_style) = if self.focus == Focus::Sidebar {
+            (
+                Style::default().fg(Theme::BORDER_FOCUSED),
+                Style::default()
+                    .fg(Theme::ACCENT_PRIMARY)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            (
+                Style::default().fg(Theme::BORDER_UNFOCUSED),
+                Style::default().fg(Theme::TEXT_DIM),
+            )
+        };
+
+        let current_agents_count = self.get_current_agents().len();
+        let mut title = if self.filter_text.is_empty() {
+            format!("📋 Agents ({})", current_agents_count)
+        } else {
+            format!(
+                "📋 Agents ({}/{})",
+                filtered_agents.len(),
+                current_agents_count
+            )
+        };
+        let removed = agent_changes
+            .iter()
+            .filter(|(path, change)| {
+                *change == AgentChange::Removed
+                    && path.parent() == Some(current_directory.as_path())
+            })
+            .count();
+        if removed > 0 {
+            title.push_str(&format!(" · {} removed", removed));
+        }
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .title(Line::from(vec![Span::styled(title, title_style)]))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(border_style)
+                    .style(Style::default().bg(Theme::BACKGROUND)),
+            )
+            .highlight_style(
+                Style::default()
+                    .bg(Theme::HIGHLIGHT)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("▶ ");
+
+        frame.render_stateful_widget(list, area, &mut self.list_state);
+    }
+
+    fn draw_main_panel(&mut self, frame: &mut Frame, area: Rect) {
+        let (border_style, title_style) = if self.focus == Focus::Form {
+            (
+                Style::default().fg(Theme::BORDER_FOCUSED),
+                Style::default()
+                    .fg(Theme::ACCENT_PRIMARY)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            (
+                Style::default().fg(Theme::BORDER_UNFOCUSED),
+                Style::default().fg(Theme::TEXT_DIM),
+            )
+        };
+
+        if let Some(plist) = &self.selected_plist {
+            let mut text = Vec::new();
+            let value_color = if self.selected_access == Access::ReadOnly {
+                Theme::TEXT_DIM
+            } else {
+                Theme::FOREGROUND
+            };
+
+            let start_interval_str = plist
+                .start_interval
+                .map(|i| i.to_string())
+                .unwrap_or_default();
+            let throttle_interval_str = plist
+                .throttle_interval
+                .map(|i| i.to_string())
+                .unwrap_or_default();
+            let run_at_load_str = bool_label(plist.run_at_load);
+            let keep_alive_str = bool_label(plist.keep_alive);
+            let abandon_process_group_str = bool_label(plist.abandon_process_group);
+            let enable_pressured_exit_str = bool_label(plist.enable_pressured_exit);
+            let enable_transactions_str = bool_label(plist.enable_transactions);
+            let event_monitor_str = bool_label(plist.event_monitor);
+
+            let fields = vec![
+                (
+                    FormField::Label,
+                    "🏷️  Label",
+                    plist.label.as_deref().unwrap_or(""),
+                ),
+                (
+                    FormField::Program,
+                    "⚙️  Program",
+                    plist.program.as_deref().unwrap_or(""),
+                ),
+                (
+                    FormField::StartInterval,
+                    "⏰ Start Interval",
+                    &start_interval_str,
+                ),
+                (
+                    FormField::ThrottleInterval,
+                    "⏱️  Throttle Interval",
+                    &throttle_interval_str,
+                ),
+                (FormField::RunAtLoad, "🚀 Run At Load", run_at_load_str),
+                (FormField::KeepAlive, "💓 Keep Alive", keep_alive_str),
+                (
+                    FormField::AbandonProcessGroup,
+                    "🔄 Abandon Process Group",
+                    abandon_process_group_str,
+                ),
+                (
+                    FormField::StandardOutPath,
+                    "📄 Stdout Path",
+                    plist.standard_out_path.as_deref().unwrap_or(""),
+                ),
+                (
+                    FormField::StandardErrorPath,
+                    "📄 Stderr Path",
+                    plist.standard_error_path.as_deref().unwrap_or(""),
+                ),
+                (
+                    FormField::WorkingDirectory,
+                    "📁 Working Directory",
+                    plist.working_directory.as_deref().unwrap_or(""),
+                ),
+                (
+                    FormField::POSIXSpawnType,
+                    "🔧 POSIX Spawn Type",
+                    plist.posix_spawn_type.as_deref().unwrap_or(""),
+                ),
+                (
+                    FormField::ProcessType,
+                    "🎚️  Process Type",
+                    plist.process_type.as_deref().unwrap_or(""),
+                ),
+                (
+                    FormField::EnablePressuredExit,
+                    "🚪 Enable Pressured Exit",
+                    enable_pressured_exit_str,
+                ),
+                (
+                    FormField::EnableTransactions,
+                    "🔒 Enable Transactions",
+                    enable_transactions_str,
+                ),
+                (
+                    FormField::EventMonitor,
+                    "👁️  Event Monitor",
+                    event_monitor_str,
+                ),
+            ];
+
+            for (i, (field, label, value)) in fields.iter().enumerate() {
+                let is_current = self.focus == Focus::Form && self.current_field == *field;
+                let is_editing = self.editing && self.editing_field.as_ref() == Some(field);
+
+                let (label_style, value_style) = if is_editing {
+                    (
+                        Style::default()
+                            .fg(Theme::ACCENT_WARNING)
+                            .add_modifier(Modifier::BOLD),
+                        Style::default()
+                            .fg(Theme::BACKGROUND)
+                            .bg(Theme::ACCENT_WARNING)
+                            .add_modifier(Modifier::BOLD),
+                    )
                 } else if is_current {
                     (
                         Style::default()
-                            .fg(Theme::ACCENT_PRIMARY)
+                            .fg(Theme::ACCENT_PRIMARY)
+                            .add_modifier(Modifier::BOLD),
+                        Style::default()
+                            .fg(Theme::ACCENT_PRIMARY)
+                            .bg(Theme::HIGHLIGHT)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                } else {
+                    (
+                        Style::default()
+                            .fg(Theme::ACCENT_MUTED)
+                            .add_modifier(Modifier::BOLD),
+                        Style::default().fg(value_color),
+                    )
+                };
+
+                let mut value_spans = vec![Span::raw("  ")];
+                if is_editing {
+                    let selection_style = Style::default()
+                        .fg(Theme::BACKGROUND)
+                        .bg(Theme::ACCENT_PRIMARY);
+                    value_spans.extend(
+                        self.edit_input
+                            .to_lines(value_style, selection_style)
+                            .into_iter()
+                            .flat_map(|line| line.spans),
+                    );
+                } else {
+                    value_spans.push(Span::styled(value.to_string(), value_style));
+                }
+
+                // Add spacing between fields
+                if i > 0 {
+                    text.push(Line::from(""));
+                }
+
+                // Label on its own line
+                text.push(Line::from(vec![Span::styled(*label, label_style)]));
+
+                // Value on next line with indentation
+                text.push(Line::from(value_spans));
+                if is_editing && let Some(error) = &self.edit_error {
+                    text.push(Line::from(vec![Span::styled(
+                        format!("  ⚠ {}", error),
+                        Style::default().fg(Theme::ACCENT_ERROR),
+                    )]));
+                }
+            }
+
+            text.push(Line::from(""));
+            text.push(Line::from(""));
+
+            if let Some(args) = &plist.program_arguments {
+                let is_current =
+                    self.focus == Focus::Form && self.current_field == FormField::ProgramArguments;
+                let is_editing = self.editing
+                    && self.editing_field.as_ref() == Some(&FormField::ProgramArguments);
+
+                let label_style = if is_current {
+                    Style::default()
+                        .fg(Theme::ACCENT_PRIMARY)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                        .fg(Theme::ACCENT_MUTED)
+                        .add_modifier(Modifier::BOLD)
+                };
+
+                text.push(Line::from(vec![Span::styled(
+                    "⚙️  Program Arguments:",
+                    label_style,
+                )]));
+                text.push(Line::from(""));
+
+                for (i, arg) in args.iter().enumerate() {
+                    let arg_style = if is_editing {
+                        Style::default()
+                            .fg(Theme::BACKGROUND)
+                            .bg(Theme::ACCENT_WARNING)
+                    } else if is_current {
+                        Style::default()
+                            .fg(Theme::ACCENT_PRIMARY)
+                            .bg(Theme::HIGHLIGHT)
+                    } else {
+                        Style::default().fg(value_color)
+                    };
+                    text.push(Line::from(vec![
+                        Span::raw("    "),
+                        Span::styled(format!("[{}] ", i), Style::default().fg(Theme::TEXT_DIM)),
+                        Span::styled(arg, arg_style),
+                    ]));
+                }
+
+                // The same run as a shell command, ready to copy
+                text.push(Line::from(""));
+                text.push(Line::from(vec![
+                    Span::styled("    $ ", Style::default().fg(Theme::TEXT_DIM)),
+                    Span::styled(
+                        shell_words::join(args),
+                        Style::default().fg(Theme::ACCENT_MUTED),
+                    ),
+                ]));
+            }
+
+            // Display Associated Bundle Identifiers
+            if let Some(ids) = &plist.associated_bundle_identifiers {
+                let is_current = self.focus == Focus::Form
+                    && self.current_field == FormField::AssociatedBundleIdentifiers;
+                let is_editing = self.editing
+                    && self.editing_field.as_ref() == Some(&FormField::AssociatedBundleIdentifiers);
+
+                let label_style = if is_current {
+                    Style::default()
+                        .fg(Theme::ACCENT_PRIMARY)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                        .fg(Theme::ACCENT_MUTED)
+                        .add_modifier(Modifier::BOLD)
+                };
+
+                text.push(Line::from(""));
+                text.push(Line::from(vec![Span::styled(
+                    "📦 Associated Bundle Identifiers:",
+                    label_style,
+                )]));
+                text.push(Line::from(""));
+
+                for (i, id) in ids.iter().enumerate() {
+                    let id_style = if is_editing {
+                        Style::default()
+                            .fg(Theme::BACKGROUND)
+                            .bg(Theme::ACCENT_WARNING)
+                    } else if is_current {
+                        Style::default()
+                            .fg(Theme::ACCENT_PRIMARY)
+                            .bg(Theme::HIGHLIGHT)
+                    } else {
+                        Style::default().fg(value_color)
+                    };
+                    text.push(Line::from(vec![
+                        Span::raw("    "),
+                        Span::styled(format!("[{}] ", i), Style::default().fg(Theme::TEXT_DIM)),
+                        Span::styled(id, id_style),
+                    ]));
+                }
+            }
+
+            // Display Limit Load To Session Type
+            if let Some(session_type) = &plist.limit_load_to_session_type {
+                let is_current = self.focus == Focus::Form
+                    && self.current_field == FormField::LimitLoadToSessionType;
+                let is_editing = self.editing
+                    && self.editing_field.as_ref() == Some(&FormField::LimitLoadToSessionType);
+
+                let label_style = if is_current {
+                    Style::default()
+                        .fg(Theme::ACCENT_PRIMARY)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                        .fg(Theme::ACCENT_MUTED)
+                        .add_modifier(Modifier::BOLD)
+                };
+
+                text.push(Line::from(""));
+                text.push(Line::from(vec![Span::styled(
+                    "🔒 Limit Load To Session Type:",
+                    label_style,
+                )]));
+                text.push(Line::from(""));
+
+                match session_type {
+                    LimitLoadToSessionType::Single(s) => {
+                        let session_style = if is_editing {
+                            Style::default()
+                                .fg(Theme::BACKGROUND)
+                                .bg(Theme::ACCENT_WARNING)
+                        } else if is_current {
+                            Style::default()
+                                .fg(Theme::ACCENT_PRIMARY)
+                                .bg(Theme::HIGHLIGHT)
+                        } else {
+                            Style::default().fg(value_color)
+                        };
+                        text.push(Line::from(vec![
+                            Span::raw("    "),
+                            Span::styled(s, session_style),
+                        ]));
+                    }
+                    LimitLoadToSessionType::Multiple(sessions) => {
+                        for (i, session) in sessions.iter().enumerate() {
+                            let session_style = if is_editing {
+                                Style::default()
+                                    .fg(Theme::BACKGROUND)
+                                    .bg(Theme::ACCENT_WARNING)
+                            } else if is_current {
+                                Style::default()
+                                    .fg(Theme::ACCENT_PRIMARY)
+                                    .bg(Theme::HIGHLIGHT)
+                            } else {
+                                Style::default().fg(value_color)
+                            };
+                            text.push(Line::from(vec![
+                                Span::raw("    "),
+                                Span::styled(
+                                    format!("[{}] ", i),
+                                    Style::default().fg(Theme::TEXT_DIM),
+                                ),
+                                Span::styled(session, session_style),
+                            ]));
+                        }
+                    }
+                }
+            }
+
+            // Display Environment Variables
+            if let Some(env_vars) = &plist.environment_variables {
+                let is_current = self.focus == Focus::Form
+                    && self.current_field == FormField::EnvironmentVariables;
+                let is_editing = self.editing
+                    && self.editing_field.as_ref() == Some(&FormField::EnvironmentVariables);
+
+                let label_style = if is_current {
+                    Style::default()
+                        .fg(Theme::ACCENT_PRIMARY)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                        .fg(Theme::ACCENT_MUTED)
+                        .add_modifier(Modifier::BOLD)
+                };
+
+                text.push(Line::from(""));
+                text.push(Line::from(vec![Span::styled(
+                    "🌍 Environment Variables:",
+                    label_style,
+                )]));
+                text.push(Line::from(""));
+
+                let duplicates = duplicate_env_names(env_vars);
+                for (i, (key, value)) in env_vars.iter().enumerate() {
+                    let env_style = if is_editing {
+                        Style::default()
+                            .fg(Theme::BACKGROUND)
+                            .bg(Theme::ACCENT_WARNING)
+                    } else if is_current {
+                        Style::default()
+                            .fg(Theme::ACCENT_PRIMARY)
+                            .bg(Theme::HIGHLIGHT)
+                    } else {
+                        Style::default().fg(value_color)
+                    };
+                    let mut spans = vec![
+                        Span::raw("    "),
+                        Span::styled(
+                            format!("{}=", key),
+                            Style::default().fg(Theme::ACCENT_MUTED),
+                        ),
+                        Span::styled(value, env_style),
+                    ];
+                    if duplicates.contains(&key.as_str())
+                        && env_vars[i + 1..].iter().any(|(other, _)| other == key)
+                    {
+                        spans.push(Span::styled(
+                            "  ⚠ overridden by a later duplicate",
+                            Style::default().fg(Theme::ACCENT_WARNING),
+                        ));
+                    }
+                    text.push(Line::from(spans));
+                }
+            }
+
+            let diagnostics = self.selected_diagnostics();
+            if !diagnostics.is_empty() {
+                text.push(Line::from(""));
+                text.push(Line::from(vec![Span::styled(
+                    "🩺 Diagnostics:",
+                    Style::default()
+                        .fg(Theme::ACCENT_MUTED)
+                        .add_modifier(Modifier::BOLD),
+                )]));
+                text.push(Line::from(""));
+                for diagnostic in &diagnostics {
+                    let mut spans = vec![
+                        Span::raw("  "),
+                        Span::styled(
+                            format!("{} ", diagnostic.severity.icon()),
+                            Style::default()
+                                .fg(diagnostic.severity.color())
+                                .add_modifier(Modifier::BOLD),
+                        ),
+                        Span::styled(
+                            diagnostic.message.clone(),
+                            Style::default().fg(Theme::FOREGROUND),
+                        ),
+                        Span::styled(
+                            format!("  [{}]", diagnostic.rule),
+                            Style::default().fg(Theme::TEXT_DIM),
+                        ),
+                    ];
+                    if diagnostic.fix.is_some() {
+                        spans.push(Span::styled(
+                            "  f: fix",
+                            Style::default().fg(Theme::ACCENT_SECONDARY),
+                        ));
+                    }
+                    text.push(Line::from(spans));
+                }
+            }
+
+            // Create title with scroll indicators
+            let total_content_height = text.len() as u16;
+            let viewport_height = 20; // Approximate visible lines
+            let can_scroll_up = self.form_scroll_offset > 0;
+            let can_scroll_down = total_content_height > viewport_height + self.form_scroll_offset;
+
+            let mut title_spans = vec![Span::styled("⚙️  Agent Editor", title_style)];
+            if self.is_dirty() {
+                title_spans.push(Span::styled(
+                    " ✎ Modified",
+                    Style::default()
+                        .fg(Theme::ACCENT_WARNING)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+            if self.open_file_changed() {
+                let deleted = self
+                    .selected_path
+                    .as_ref()
+                    .is_some_and(|path| !path.exists());
+                title_spans.push(Span::styled(
+                    if deleted {
+                        " ⚠ Deleted from disk"
+                    } else {
+                        " ⚠ Changed on disk"
+                    },
+                    Style::default()
+                        .fg(Theme::ACCENT_ERROR)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+            match self.selected_access {
+                Access::ReadOnly => title_spans.push(Span::styled(
+                    " 🔒 Read-only",
+                    Style::default().fg(Theme::ACCENT_WARNING),
+                )),
+                Access::NeedsElevation => title_spans.push(Span::styled(
+                    " 🔐 Saves with sudo",
+                    Style::default().fg(Theme::ACCENT_MUTED),
+                )),
+                Access::Writable => {}
+            }
+            if let Some(worst) = diagnostics.iter().map(|d| d.severity).max() {
+                let count = diagnostics.len();
+                title_spans.push(Span::styled(
+                    format!(
+                        " {} {} issue{}",
+                        worst.icon(),
+                        count,
+                        if count == 1 { "" } else { "s" }
+                    ),
+                    Style::default().fg(worst.color()),
+                ));
+            }
+
+            if can_scroll_up || can_scroll_down {
+                title_spans.push(Span::raw(" "));
+                if can_scroll_up {
+                    title_spans.push(Span::styled(
+                        "↑",
+                        Style::default().fg(Theme::ACCENT_SECONDARY),
+                    ));
+                }
+                if can_scroll_down {
+                    title_spans.push(Span::styled(
+                        "↓",
+                        Style::default().fg(Theme::ACCENT_SECONDARY),
+                    ));
+                }
+                title_spans.push(Span::styled(
+                    " [PgUp/PgDn]",
+                    Style::default().fg(Theme::TEXT_DIM),
+                ));
+            }
+
+            let paragraph = Paragraph::new(text)
+                .block(
+                    Block::default()
+                        .title(Line::from(title_spans))
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded)
+                        .border_style(border_style)
+                        .style(Style::default().bg(Theme::BACKGROUND))
+                        .padding(ratatui::widgets::Padding::uniform(1)),
+                )
+                .wrap(Wrap { trim: true })
+                .scroll((self.form_scroll_offset, 0));
+
+            frame.render_widget(paragraph, area);
+        } else {
+            let paragraph = Paragraph::new(Line::from(vec![
+                Span::styled("📝 ", Style::default().fg(Theme::TEXT_DIM)),
+                Span::styled(
+                    "Select an agent from the sidebar to view and edit its configuration",
+                    Style::default()
+                        .fg(Theme::TEXT_DIM)
+                        .add_modifier(Modifier::ITALIC),
+                ),
+            ]))
+            .block(
+                Block::default()
+                    .title(Line::from(vec![Span::styled(
+                        "⚙️  Agent Editor",
+                        title_style,
+                    )]))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(border_style)
+                    .style(Style::default().bg(Theme::BACKGROUND)),
+            )
+            .style(Style::default().fg(Theme::TEXT_DIM))
+            .alignment(ratatui::layout::Alignment::Center)
+            .wrap(Wrap { trim: true });
+
+            frame.render_widget(paragraph, area);
+        }
+    }
+
+    fn draw_status_bar(&mut self, frame: &mut Frame, area: Rect) {
+        // Update status timer
+        if self.status_timer > 0 {
+            self.status_timer -= 1;
+            if self.status_timer == 0 {
+                self.status_message.clear();
+            }
+        }
+
+        let (status_text, status_style, icon) = if !self.status_message.is_empty() {
+            let icon = if self.status_message.contains('✓') {
+                "✅"
+            } else if self.status_message.contains('✗') {
+                "❌"
+            } else {
+                "ℹ️"
+            };
+            (
+                self.status_message.clone(),
+                Style::default()
+                    .fg(Theme::ACCENT_SECONDARY)
+                    .add_modifier(Modifier::BOLD),
+                icon,
+            )
+        } else if self.editing {
+            (
+                format!(
+                    "EDITING: {} | Enter=Save, Esc=Cancel, Ctrl+A/E=Start/End, Ctrl+W/U/K=Delete Word/Before/After",
+                    self.get_editing_field_name()
+                ),
+                Style::default()
+                    .fg(Theme::ACCENT_WARNING)
+                    .add_modifier(Modifier::BOLD),
+                "✏️",
+            )
+        } else {
+            let (text, icon) = match self.focus {
+                Focus::Search => (
+                    "Type to filter agents | Enter=Focus Sidebar, Tab=Next Panel, 1/2/3=Switch Tabs".to_string(),
+                    "🔍",
+                ),
+                Focus::Sidebar => (
+                    "j/k=Navigate, Enter=Load, m/c=Move/Copy, b=Backups, x/d=Mark/Compare, a=Acknowledge, /=Search, 1/2/3=Switch Tabs".to_string(),
+                    "📋",
+                ),
+                Focus::Form if self.selected_plist.is_some() && self.selected_access == Access::ReadOnly => (
+                    "🔒 Read-only | j/k=Navigate Fields, o=Copy to User as override, PgUp/PgDn=Scroll, l=Logs | Tab=Switch Panel".to_string(),
+                    "⚙️",
+                ),
+                Focus::Form => (
+                    "j/k=Navigate Fields, Enter=Edit, PgUp/PgDn=Scroll, Ctrl+S=Save, u/Ctrl+R=Undo/Redo, r=Reload, f=Fix, ==Format, l=Logs, b=Backups | Tab=Switch Panel, 1/2/3=Switch Tabs".to_string(),
+                    "⚙️",
+                ),
+                Focus::Logs => (
+                    "j/k=Scroll, g/G=Top/Bottom, PgUp/PgDn=Page, t=Toggle stdout/stderr, r=Refresh | Tab=Switch Panel".to_string(),
+                    "📜",
+                ),
+                Focus::Backups => (
+                    "j/k=Select Version, PgUp/PgDn=Scroll Diff, d=Compare, Enter=Restore, b=Back to Editor".to_string(),
+                    "🗄️",
+                ),
+                Focus::Audit => (
+                    "j/k=Select Agent, Enter=Open, e=Export JSON, r=Re-run, A=Close | Tab=Switch Panel".to_string(),
+                    "🛡️",
+                ),
+            };
+            (text, Style::default().fg(Theme::ACCENT_MUTED), icon)
+        };
+
+        let mut status_spans = vec![Span::styled(
+            format!("{} ", icon),
+            Style::default().fg(Theme::ACCENT_PRIMARY),
+        )];
+
+        // Add colored legend for sidebar
+        if self.focus == Focus::Sidebar {
+            // Add status legend with proper colors
+            status_spans.extend(vec![
+                Span::styled("●", Style::default().fg(Theme::ACCENT_SECONDARY)), // Running (Green)
+                Span::styled("=Running ", Style::default().fg(Theme::FOREGROUND)),
+                Span::styled("●", Style::default().fg(Theme::ACCENT_ERROR)), // Stopped (Red)
+                Span::styled("=Stopped ", Style::default().fg(Theme::FOREGROUND)),
+                Span::styled("◉", Style::default().fg(Theme::ACCENT_MUTED)), // Enabled (Cyan)
+                Span::styled("=Enabled | ", Style::default().fg(Theme::FOREGROUND)),
+            ]);
+        }
+
+        status_spans.push(Span::styled(status_text, status_style));
+
+        let status_line = Line::from(status_spans);
+
+        let status_paragraph = Paragraph::new(vec![status_line])
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(Theme::BORDER_UNFOCUSED))
+                    .style(Style::default().bg(Theme::BACKGROUND)),
+            )
+            .style(Style::default().bg(Theme::BACKGROUND));
+
+        frame.render_widget(status_paragraph, area);
+    }
+
+    fn draw_exit_confirmation(&mut self, frame: &mut Frame) {
+        let area = frame.area();
+
+        // Create a centered popup area
+        let popup_area = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Percentage(35),
+                Constraint::Length(9),
+                Constraint::Percentage(35),
+            ])
+            .split(area)[1];
+
+        let popup_area = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(20),
+                Constraint::Percentage(60),
+                Constraint::Percentage(20),
+            ])
+            .split(popup_area)[1];
+
+        // Clear the background
+        frame.render_widget(Clear, popup_area);
+
+        // Create the confirmation dialog
+        let confirmation_text = vec![
+            Line::from(""),
+            Line::from(vec![Span::styled(
+                "  🚪 Quit LaunchAgent Manager?",
+                Style::default()
+                    .fg(Theme::ACCENT_WARNING)
+                    .add_modifier(Modifier::BOLD),
+            )]),
+            Line::from(""),
+            Line::from(vec![
+                Span::styled("  ", Style::default()),
+                Span::styled(
+                    "[Y]",
+                    Style::default()
+                        .fg(Theme::ACCENT_SECONDARY)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled("es  ", Style::default().fg(Theme::FOREGROUND)),
+                Span::styled(
+                    "[N]",
+                    Style::default()
+                        .fg(Theme::ACCENT_ERROR)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled("o  ", Style::default().fg(Theme::FOREGROUND)),
+                Span::styled(
+                    "[Esc]",
+                    Style::default()
+                        .fg(Theme::ACCENT_MUTED)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ]),
+            Line::from(""),
+            Line::from(vec![Span::styled(
+                "  Press any key to choose",
+                Style::default()
+                    .fg(Theme::TEXT_DIM)
+                    .add_modifier(Modifier::ITALIC),
+            )]),
+            Line::from(""),
+        ];
+
+        let confirmation_dialog = Paragraph::new(confirmation_text)
+            .block(
+                Block::default()
+                    .title(Line::from(vec![Span::styled(
+                        " ⚠️  Confirm Exit ",
+                        Style::default()
+                            .fg(Theme::ACCENT_WARNING)
+                            .add_modifier(Modifier::BOLD),
+                    )]))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Thick)
+                    .border_style(Style::default().fg(Theme::ACCENT_WARNING))
+                    .style(Style::default().bg(Theme::BACKGROUND)),
+            )
+            .style(Style::default().bg(Theme::BACKGROUND))
+            .alignment(ratatui::layout::Alignment::Left);
+
+        frame.render_widget(confirmation_dialog, popup_area);
+    }
+
+    fn draw_transfer_confirmation(&mut self, frame: &mut Frame, transfer: AgentTransfer) {
+        let popup_area = centered_rect(frame.area(), 60, 10);
+        frame.render_widget(Clear, popup_area);
+
+        let verb = match transfer.mode {
+            TransferMode::Move => "Move",
+            TransferMode::Copy => "Copy",
+        };
+        let detail = match (transfer.to, transfer.mode) {
+            (TabLocation::Global, _) => "  Owner will be set to root:wheel (644)",
+            (_, TransferMode::Move) => "  The moved agent will be owned by you (644)",
+            (_, TransferMode::Copy) => "  The copy will be owned by you (644)",
+        };
+
+        let mut text = vec![
+            Line::from(""),
+            Line::from(vec![Span::styled(
+                format!(
+                    "  📦 {} {} to {}?",
+                    verb,
+                    transfer.filename,
+                    transfer.to.get_display_name()
+                ),
+                Style::default()
+                    .fg(Theme::ACCENT_WARNING)
+                    .add_modifier(Modifier::BOLD),
+            )]),
+            Line::from(""),
+            Line::from(vec![Span::styled(
+                detail,
+                Style::default().fg(Theme::FOREGROUND),
+            )]),
+            Line::from(vec![Span::styled(
+                "  It is unloaded from the old location and loaded from the new one",
+                Style::default().fg(Theme::TEXT_DIM),
+            )]),
+        ];
+        if transfer.needs_root() {
+            text.push(Line::from(vec![Span::styled(
+                "  Requires administrator privileges (sudo)",
+                Style::default().fg(Theme::TEXT_DIM),
+            )]));
+        }
+        text.push(Line::from(""));
+        text.push(Line::from(vec![
+            Span::styled("  ", Style::default()),
+            Span::styled(
+                "[Y]",
+                Style::default()
+                    .fg(Theme::ACCENT_SECONDARY)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled("es  ", Style::default().fg(Theme::FOREGROUND)),
+            Span::styled(
+                "[N]",
+                Style::default()
+                    .fg(Theme::ACCENT_ERROR)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled("o", Style::default().fg(Theme::FOREGROUND)),
+        ]));
+
+        let dialog = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .title(Line::from(vec![Span::styled(
+                        format!(" 📦 {} Agent ", verb),
+                        Style::default()
+                            .fg(Theme::ACCENT_WARNING)
+                            .add_modifier(Modifier::BOLD),
+                    )]))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Thick)
+                    .border_style(Style::default().fg(Theme::ACCENT_WARNING))
+                    .style(Style::default().bg(Theme::BACKGROUND)),
+            )
+            .style(Style::default().bg(Theme::BACKGROUND));
+
+        frame.render_widget(dialog, popup_area);
+    }
+
+    fn draw_elevation_prompt(&mut self, frame: &mut Frame, path: PathBuf) {
+        let popup_area = centered_rect(frame.area(), 70, 11);
+        frame.render_widget(Clear, popup_area);
+
+        let directory = path
+            .parent()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_default();
+        let text = vec![
+            Line::from(""),
+            Line::from(vec![Span::styled(
+                format!("  🔐 {} is owned by root", directory),
+                Style::default()
+                    .fg(Theme::ACCENT_WARNING)
+                    .add_modifier(Modifier::BOLD),
+            )]),
+            Line::from(""),
+            Line::from(vec![Span::styled(
+                "  Saving needs administrator privileges. lam will run:",
+                Style::default().fg(Theme::FOREGROUND),
+            )]),
+            Line::from(vec![Span::styled(
+                format!("    sudo lam helper write {}", path.display()),
+                Style::default().fg(Theme::ACCENT_MUTED),
+            )]),
+            Line::from(vec![Span::styled(
+                "  and ask for your password if sudo has none cached",
+                Style::default().fg(Theme::TEXT_DIM),
+            )]),
+            Line::from(""),
+            Line::from(vec![
+                Span::styled("  ", Style::default()),
+                Span::styled(
+                    "[Y]",
+                    Style::default()
+                        .fg(Theme::ACCENT_SECONDARY)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled("es  ", Style::default().fg(Theme::FOREGROUND)),
+                Span::styled(
+                    "[N]",
+                    Style::default()
+                        .fg(Theme::ACCENT_ERROR)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled("o", Style::default().fg(Theme::FOREGROUND)),
+            ]),
+        ];
+
+        let dialog = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .title(Line::from(vec![Span::styled(
+                        " 🔐 Elevation Required ",
+                        Style::default()
+                            .fg(Theme::ACCENT_WARNING)
+                            .add_modifier(Modifier::BOLD),
+                    )]))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Thick)
+                    .border_style(Style::default().fg(Theme::ACCENT_WARNING))
+                    .style(Style::default().bg(Theme::BACKGROUND)),
+            )
+            .style(Style::default().bg(Theme::BACKGROUND));
+
+        frame.render_widget(dialog, popup_area);
+    }
+
+    fn draw_list_editor(&mut self, frame: &mut Frame, editor: ListEditor) {
+        let area = frame.area();
+        let lines = editor.to_lines();
+        let height = (lines.len() as u16 + 2).clamp(8, area.height.saturating_sub(4));
+        let popup_area = centered_rect(area, 70, height);
+        frame.render_widget(Clear, popup_area);
+
+        let header_lines = usize::from(editor.columns.len() > 1);
+        let visible_lines = height.saturating_sub(2) as usize;
+        let scroll = (editor.selected + header_lines + 1).saturating_sub(visible_lines) as u16;
+        let hint = if editor.input.is_some() {
+            " Enter=Done, Esc=Cancel "
+        } else if editor.field == FormField::ProgramArguments {
+            " a=Add, d=Delete, J/K=Move, Enter=Edit, c=Command Line, Esc=Done "
+        } else {
+            " a=Add, d=Delete, J/K=Move, Enter=Edit, Esc=Done "
+        };
+        let list = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .title(Line::from(vec![Span::styled(
+                        format!(" ✏️  {} ", editor.field.name()),
+                        Style::default()
+                            .fg(Theme::ACCENT_WARNING)
+                            .add_modifier(Modifier::BOLD),
+                    )]))
+                    .title_bottom(Line::from(vec![Span::styled(
+                        hint,
+                        Style::default().fg(Theme::TEXT_DIM),
+                    )]))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Thick)
+                    .border_style(Style::default().fg(Theme::ACCENT_WARNING))
+                    .style(Style::default().bg(Theme::BACKGROUND)),
+            )
+            .scroll((scroll, 0));
+        frame.render_widget(list, popup_area);
+    }
+
+    fn draw_picker(&mut self, frame: &mut Frame, picker: Picker) {
+        let lines = picker.to_lines();
+        let popup_area = centered_rect(frame.area(), 40, lines.len() as u16 + 2);
+        frame.render_widget(Clear, popup_area);
+
+        let hint = if picker.multiple {
+            " Space=Toggle, Enter=Done, Esc=Cancel "
+        } else {
+            " Enter=Choose, Esc=Cancel "
+        };
+        let list = Paragraph::new(lines).block(
+            Block::default()
+                .title(Line::from(vec![Span::styled(
+                    format!(" ✏️  {} ", picker.field.name()),
+                    Style::default()
+                        .fg(Theme::ACCENT_WARNING)
+                        .add_modifier(Modifier::BOLD),
+                )]))
+                .title_bottom(Line::from(vec![Span::styled(
+                    hint,
+                    Style::default().fg(Theme::TEXT_DIM),
+                )]))
+                .borders(Borders::ALL)
+                .border_type(BorderType::Thick)
+                .border_style(Style::default().fg(Theme::ACCENT_WARNING))
+                .style(Style::default().bg(Theme::BACKGROUND)),
+        );
+        frame.render_widget(list, popup_area);
+    }
+
+    fn draw_fix_menu(&mut self, frame: &mut Frame, menu: FixMenu) {
+        let lines: Vec<Line> = menu
+            .fixes
+            .iter()
+            .enumerate()
+            .map(|(i, fix)| {
+                let is_selected = i == menu.selected;
+                let style = if is_selected {
+                    Style::default()
+                        .fg(Theme::ACCENT_PRIMARY)
+                        .bg(Theme::HIGHLIGHT)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(Theme::FOREGROUND)
+                };
+                Line::from(vec![
+                    Span::styled(
+                        if is_selected { "▶ " } else { "  " },
+                        Style::default().fg(Theme::ACCENT_PRIMARY),
+                    ),
+                    Span::styled(fix.description(), style),
+                ])
+            })
+            .collect();
+        let popup_area = centered_rect(frame.area(), 60, lines.len() as u16 + 2);
+        frame.render_widget(Clear, popup_area);
+
+        let list = Paragraph::new(lines).block(
+            Block::default()
+                .title(Line::from(vec![Span::styled(
+                    " 🩹 Quick Fixes ",
+                    Style::default()
+                        .fg(Theme::ACCENT_WARNING)
+                        .add_modifier(Modifier::BOLD),
+                )]))
+                .title_bottom(Line::from(vec![Span::styled(
+                    " Enter=Apply, Esc=Cancel ",
+                    Style::default().fg(Theme::TEXT_DIM),
+                )]))
+                .borders(Borders::ALL)
+                .border_type(BorderType::Thick)
+                .border_style(Style::default().fg(Theme::ACCENT_WARNING))
+                .style(Style::default().bg(Theme::BACKGROUND)),
+        );
+        frame.render_widget(list, popup_area);
+    }
+
+    fn draw_unsaved_prompt(&mut self, frame: &mut Frame) {
+        let popup_area = centered_rect(frame.area(), 60, 9);
+        frame.render_widget(Clear, popup_area);
+
+        let name = self
+            .selected_path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let text = vec![
+            Line::from(""),
+            Line::from(vec![Span::styled(
+                format!("  ✎ {} has unsaved changes", name),
+                Style::default()
+                    .fg(Theme::ACCENT_WARNING)
+                    .add_modifier(Modifier::BOLD),
+            )]),
+            Line::from(""),
+            Line::from(vec![
+                Span::styled("  ", Style::default()),
+                Span::styled(
+                    "[S]",
+                    Style::default()
+                        .fg(Theme::ACCENT_SECONDARY)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled("ave  ", Style::default().fg(Theme::FOREGROUND)),
+                Span::styled(
+                    "[D]",
+                    Style::default()
+                        .fg(Theme::ACCENT_ERROR)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled("iscard  ", Style::default().fg(Theme::FOREGROUND)),
+                Span::styled(
+                    "[C]",
+                    Style::default()
+                        .fg(Theme::ACCENT_MUTED)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled("ancel", Style::default().fg(Theme::FOREGROUND)),
+            ]),
+            Line::from(""),
+            Line::from(vec![Span::styled(
+                "  Esc also cancels",
+                Style::default()
+                    .fg(Theme::TEXT_DIM)
+                    .add_modifier(Modifier::ITALIC),
+            )]),
+        ];
+
+        let dialog = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .title(Line::from(vec![Span::styled(
+                        " ⚠️  Unsaved Changes ",
+                        Style::default()
+                            .fg(Theme::ACCENT_WARNING)
                             .add_modifier(Modifier::BOLD),
+                    )]))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Thick)
+                    .border_style(Style::default().fg(Theme::ACCENT_WARNING))
+                    .style(Style::default().bg(Theme::BACKGROUND)),
+            )
+            .style(Style::default().bg(Theme::BACKGROUND));
+
+        frame.render_widget(dialog, popup_area);
+    }
+
+    fn draw_conflict(&mut self, frame: &mut Frame, conflict: Conflict) {
+        let key_style = |color| Style::default().fg(color).add_modifier(Modifier::BOLD);
+        let name = self
+            .selected_path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let Some(rows) = &conflict.merge else {
+            let popup_area = centered_rect(frame.area(), 70, 10);
+            frame.render_widget(Clear, popup_area);
+            let text = vec![
+                Line::from(""),
+                Line::from(vec![Span::styled(
+                    format!("  ⚡ {} changed on disk since it was loaded", name),
+                    key_style(Theme::ACCENT_WARNING),
+                )]),
+                Line::from(vec![Span::styled(
+                    "  Another tool or person edited it while it was open here",
+                    Style::default().fg(Theme::TEXT_DIM),
+                )]),
+                Line::from(""),
+                Line::from(vec![
+                    Span::raw("  "),
+                    Span::styled("[R]", key_style(Theme::ACCENT_SECONDARY)),
+                    Span::styled("eload from disk  ", Style::default().fg(Theme::FOREGROUND)),
+                    Span::styled("[O]", key_style(Theme::ACCENT_ERROR)),
+                    Span::styled("verwrite  ", Style::default().fg(Theme::FOREGROUND)),
+                    Span::styled("[M]", key_style(Theme::ACCENT_PRIMARY)),
+                    Span::styled("erge  ", Style::default().fg(Theme::FOREGROUND)),
+                    Span::styled("[C]", key_style(Theme::ACCENT_MUTED)),
+                    Span::styled("ancel", Style::default().fg(Theme::FOREGROUND)),
+                ]),
+                Line::from(""),
+                Line::from(vec![Span::styled(
+                    "  Reload discards your edits, overwrite replaces their changes",
+                    Style::default()
+                        .fg(Theme::TEXT_DIM)
+                        .add_modifier(Modifier::ITALIC),
+                )]),
+            ];
+            let dialog = Paragraph::new(text)
+                .block(
+                    Block::default()
+                        .title(Line::from(vec![Span::styled(
+                            " ⚡ File Changed ",
+                            key_style(Theme::ACCENT_WARNING),
+                        )]))
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Thick)
+                        .border_style(Style::default().fg(Theme::ACCENT_WARNING))
+                        .style(Style::default().bg(Theme::BACKGROUND)),
+                )
+                .style(Style::default().bg(Theme::BACKGROUND));
+            frame.render_widget(dialog, popup_area);
+            return;
+        };
+
+        let area = frame.area();
+        let popup_area = centered_rect(area, 80, area.height.saturating_sub(4));
+        frame.render_widget(Clear, popup_area);
+
+        let mut text = Vec::new();
+        if rows.is_empty() {
+            text.push(Line::from(vec![Span::styled(
+                "No field differences, only formatting changed on disk",
+                Style::default()
+                    .fg(Theme::TEXT_DIM)
+                    .add_modifier(Modifier::ITALIC),
+            )]));
+        }
+        for (i, row) in rows.iter().enumerate() {
+            let selected = i == conflict.merge_selected;
+            let (marker, marker_color) = if row.conflicting {
+                ("⚡", Theme::ACCENT_WARNING)
+            } else {
+                ("•", Theme::TEXT_DIM)
+            };
+            text.push(Line::from(vec![
+                Span::styled(
+                    if selected { "▶ " } else { "  " },
+                    Style::default().fg(Theme::ACCENT_PRIMARY),
+                ),
+                Span::styled(marker, Style::default().fg(marker_color)),
+                Span::styled(
+                    format!(" {}", row.key.name()),
+                    Style::default()
+                        .fg(Theme::ACCENT_PRIMARY)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    if row.take_theirs {
+                        "  → using disk"
+                    } else {
+                        "  → using yours"
+                    },
+                    Style::default().fg(Theme::ACCENT_MUTED),
+                ),
+            ]));
+            for (label, value, chosen) in [
+                ("base ", &row.base, false),
+                ("yours", &row.ours, !row.take_theirs),
+                ("disk ", &row.theirs, row.take_theirs),
+            ] {
+                let style = if chosen {
+                    Style::default().fg(Theme::ACCENT_SECONDARY)
+                } else {
+                    Style::default().fg(Theme::TEXT_DIM)
+                };
+                let value = if value.is_empty() {
+                    "(not set)".to_string()
+                } else {
+                    value.replace('\n', " ⏎ ")
+                };
+                text.push(Line::from(vec![
+                    Span::styled(
+                        format!("     {}  ", label),
+                        Style::default().fg(Theme::SUBTLE),
+                    ),
+                    Span::styled(value, style),
+                ]));
+            }
+        }
+
+        let scroll = (conflict.merge_selected as u16 * 4)
+            .saturating_sub(popup_area.height.saturating_sub(6));
+        let dialog = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .title(Line::from(vec![Span::styled(
+                        format!(" 🔀 Merge {} ", name),
+                        key_style(Theme::ACCENT_WARNING),
+                    )]))
+                    .title_bottom(Line::from(vec![Span::styled(
+                        " j/k=Select, Space=Toggle yours/disk, Enter=Apply, Esc=Back ",
+                        Style::default().fg(Theme::TEXT_DIM),
+                    )]))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Thick)
+                    .border_style(Style::default().fg(Theme::ACCENT_WARNING))
+                    .style(Style::default().bg(Theme::BACKGROUND)),
+            )
+            .style(Style::default().bg(Theme::BACKGROUND))
+            .scroll((scroll, 0));
+        frame.render_widget(dialog, popup_area);
+    }
+
+    fn draw_save_preview(&mut self, frame: &mut Frame, preview: SavePreview) {
+        let area = frame.area();
+        let popup_area = centered_rect(area, 80, area.height.saturating_sub(4));
+        frame.render_widget(Clear, popup_area);
+
+        let mut text = if preview.diff.is_empty() {
+            vec![Line::from(vec![Span::styled(
+                "No changes, saving will rewrite the file as is and reload the agent",
+                Style::default()
+                    .fg(Theme::TEXT_DIM)
+                    .add_modifier(Modifier::ITALIC),
+            )])]
+        } else {
+            diff_to_lines(&preview.diff)
+        };
+        text.insert(
+            0,
+            Line::from(vec![Span::styled(
+                preview.path.display().to_string(),
+                Style::default().fg(Theme::ACCENT_MUTED),
+            )]),
+        );
+        text.insert(1, Line::from(""));
+
+        let dialog = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .title(Line::from(vec![Span::styled(
+                        preview.title,
                         Style::default()
-                            .fg(Theme::ACCENT_PRIMARY)
-                            .bg(Theme::HIGHLIGHT)
+                            .fg(Theme::ACCENT_WARNING)
                             .add_modifier(Modifier::BOLD),
-                    )
+                    )]))
+                    .title_bottom(Line::from(vec![
+                        Span::styled(
+                            " [Y]",
+                            Style::default()
+                                .fg(Theme::ACCENT_SECONDARY)
+                                .add_modifier(Modifier::BOLD),
+                        ),
+                        Span::styled("es  ", Style::default().fg(Theme::FOREGROUND)),
+                        Span::styled(
+                            "[N]",
+                            Style::default()
+                                .fg(Theme::ACCENT_ERROR)
+                                .add_modifier(Modifier::BOLD),
+                        ),
+                        Span::styled("o  ", Style::default().fg(Theme::FOREGROUND)),
+                        Span::styled(
+                            "j/k PgUp/PgDn=Scroll ",
+                            Style::default().fg(Theme::TEXT_DIM),
+                        ),
+                    ]))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Thick)
+                    .border_style(Style::default().fg(Theme::ACCENT_WARNING))
+                    .style(Style::default().bg(Theme::BACKGROUND)),
+            )
+            .style(Style::default().bg(Theme::BACKGROUND))
+            .scroll((preview.scroll, 0));
+
+        frame.render_widget(dialog, popup_area);
+    }
+
+    fn draw_loading_screen(&mut self, frame: &mut Frame) {
+        // Clear background with theme color
+        let background = Block::default().style(Style::default().bg(Theme::BACKGROUND));
+        frame.render_widget(background, frame.area());
+
+        // Create centered loading area
+        let area = frame.area();
+        let loading_area = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Percentage(25),
+                Constraint::Length(12),
+                Constraint::Percentage(25),
+            ])
+            .split(area)[1];
+
+        let loading_area = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(15),
+                Constraint::Percentage(70),
+                Constraint::Percentage(15),
+            ])
+            .split(loading_area)[1];
+
+        // Animated spinner characters
+        let spinner_chars = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+        let spinner_index = (self.loading_step as usize) % spinner_chars.len();
+        let spinner = spinner_chars[spinner_index];
+
+        // Create progress bar
+        let progress_width = loading_area.width.saturating_sub(6) as f32;
+        let filled_width = (progress_width * self.loading_progress) as u16;
+        let progress_bar = "█".repeat(filled_width as usize)
+            + &"░".repeat((progress_width as u16).saturating_sub(filled_width) as usize);
+
+        let loading_content = vec![
+            Line::from(""),
+            Line::from(vec![Span::styled(
+                "🚀 Launch Agent Manager",
+                Style::default()
+                    .fg(Theme::ACCENT_PRIMARY)
+                    .add_modifier(Modifier::BOLD),
+            )]),
+            Line::from(""),
+            Line::from(vec![
+                Span::styled(
+                    format!("{} ", spinner),
+                    Style::default()
+                        .fg(Theme::ACCENT_SECONDARY)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    &self.loading_message,
+                    Style::default().fg(Theme::FOREGROUND),
+                ),
+            ]),
+            Line::from(""),
+            Line::from(vec![Span::styled(
+                format!(
+                    "[{}] {}%",
+                    progress_bar,
+                    (self.loading_progress * 100.0) as u8
+                ),
+                Style::default().fg(Theme::ACCENT_MUTED),
+            )]),
+            Line::from(""),
+            Line::from(vec![Span::styled(
+                "Loading launch agents and checking status...",
+                Style::default()
+                    .fg(Theme::TEXT_DIM)
+                    .add_modifier(Modifier::ITALIC),
+            )]),
+        ];
+
+        let loading_widget = Paragraph::new(loading_content)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(Theme::BORDER_FOCUSED))
+                    .style(Style::default().bg(Theme::BACKGROUND))
+                    .padding(ratatui::widgets::Padding::uniform(1)),
+            )
+            .alignment(ratatui::layout::Alignment::Center)
+            .style(Style::default().bg(Theme::BACKGROUND));
+
+        frame.render_widget(loading_widget, loading_area);
+
+        // Update spinner animation
+        self.loading_step = self.loading_step.wrapping_add(1);
+    }
+
+    #[allow(dead_code)]
+    fn get_current_field_name(&self) -> &str {
+        self.current_field.name()
+    }
+
+    fn get_editing_field_name(&self) -> &str {
+        self.editing_field
+            .as_ref()
+            .map_or("Unknown", |field| field.name())
+    }
+
+    fn set_status_message(&mut self, message: String) {
+        self.status_message = message;
+        self.status_timer = 100; // Show for ~2 seconds at 50ms update rate
+    }
+
+    async fn handle_crossterm_events(&mut self) -> Result<()> {
+        tokio::select! {
+            event = self.event_stream.next().fuse() => {
+                if let Some(Ok(evt)) = event {
+                    match evt {
+                        Event::Key(key) if key.kind == KeyEventKind::Press => {
+                            self.on_key_event(key)?;
+                        }
+                        Event::Paste(text) => self.on_paste(&text),
+                        Event::Mouse(_) => {}
+                        Event::Resize(_, _) => {}
+                        _ => {}
+                    }
+                }
+            }
+            _ = tokio::time::sleep(tokio::time::Duration::from_millis(50)) => {}
+        }
+        Ok(())
+    }
+
+    /// Pasted text goes to whichever input is open, or the search bar.
+    fn on_paste(&mut self, text: &str) {
+        if let Some(editor) = &mut self.list_editor {
+            editor.paste(text);
+        } else if self.editing {
+            self.edit_input.insert(text);
+            self.edit_error = None;
+        } else if self.focus == Focus::Search {
+            self.filter_text.push_str(&text.replace(['\r', '\n'], " "));
+            self.list_state
+                .select(if self.get_filtered_agents().is_empty() {
+                    None
                 } else {
-                    (
-                        Style::default()
-                            .fg(Theme::ACCENT_MUTED)
-                            .add_modifier(Modifier::BOLD),
-                        Style::default().fg(Theme::FOREGROUND),
-                    )
+                    Some(0)
+                });
+        }
+    }
+
+    fn on_key_event(&mut self, key: KeyEvent) -> Result<()> {
+        if self.showing_exit_confirmation {
+            self.handle_exit_confirmation_keys(key)?;
+        } else if self.pending_transfer.is_some() {
+            self.handle_transfer_confirmation_keys(key)?;
+        } else if self.elevation_prompt.is_some() {
+            self.handle_elevation_prompt_keys(key)?;
+        } else if self.unsaved_prompt.is_some() {
+            self.handle_unsaved_prompt_keys(key)?;
+        } else if self.save_preview.is_some() {
+            self.handle_save_preview_keys(key)?;
+        } else if self.conflict.is_some() {
+            self.handle_conflict_keys(key)?;
+        } else if self.comparison.is_some() {
+            self.handle_comparison_keys(key);
+        } else if self.list_editor.is_some() {
+            self.handle_list_editor_keys(key);
+        } else if self.picker.is_some() {
+            self.handle_picker_keys(key);
+        } else if self.fix_menu.is_some() {
+            self.handle_fix_menu_keys(key);
+        } else if self.editing {
+            self.handle_edit_keys(key)?;
+        } else {
+            match (key.modifiers, key.code) {
+                (_, KeyCode::Esc | KeyCode::Char('q')) => {
+                    self.request_quit();
+                }
+                (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => {
+                    self.request_quit();
+                }
+                (_, KeyCode::Tab) => {
+                    self.focus = match self.focus {
+                        Focus::Search => Focus::Sidebar,
+                        Focus::Sidebar => Focus::Form,
+                        Focus::Form => Focus::Logs,
+                        Focus::Logs => Focus::Search,
+                        Focus::Backups => Focus::Form,
+                        Focus::Audit => Focus::Sidebar,
+                    };
+                    if self.focus == Focus::Logs {
+                        self.refresh_log_content();
+                    }
+                }
+                (KeyModifiers::CONTROL, KeyCode::Char('s') | KeyCode::Char('S')) => {
+                    self.preview_save();
+                }
+                (_, KeyCode::Char('l')) => {
+                    self.focus = Focus::Logs;
+                    self.refresh_log_content();
+                }
+                (_, KeyCode::Char('/')) => {
+                    self.focus = Focus::Search;
+                }
+                // A capital A typed into the search bar is part of the filter
+                (_, KeyCode::Char('A')) if !matches!(self.focus, Focus::Audit | Focus::Search) => {
+                    self.open_audit();
+                }
+                (_, KeyCode::Char('1')) => {
+                    self.navigate(PendingNavigation::SwitchTab(TabLocation::User))?;
+                }
+                (_, KeyCode::Char('2')) => {
+                    self.navigate(PendingNavigation::SwitchTab(TabLocation::Global))?;
+                }
+                (_, KeyCode::Char('3')) => {
+                    self.navigate(PendingNavigation::SwitchTab(TabLocation::Apple))?;
+                }
+                _ => match self.focus {
+                    Focus::Search => self.handle_search_keys(key)?,
+                    Focus::Sidebar => self.handle_sidebar_keys(key)?,
+                    Focus::Form => self.handle_form_keys(key)?,
+                    Focus::Logs => self.handle_log_keys(key)?,
+                    Focus::Backups => self.handle_backup_keys(key)?,
+                    Focus::Audit => self.handle_audit_keys(key)?,
+                },
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_exit_confirmation_keys(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Esc => {
+                self.quit();
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') => {
+                self.showing_exit_confirmation = false;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_unsaved_prompt_keys(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Char('s') | KeyCode::Char('S') => {
+                if let Some(navigation) = self.unsaved_prompt.take() {
+                    // The save still goes through its preview
+                    self.navigation_after_save = Some(navigation);
+                    self.preview_save();
+                    self.continue_navigation()?;
+                }
+            }
+            KeyCode::Char('d') | KeyCode::Char('D') => {
+                if let Some(navigation) = self.unsaved_prompt.take() {
+                    self.selected_plist = self.pristine_plist.clone();
+                    self.perform_navigation(navigation)?;
+                }
+            }
+            KeyCode::Char('c') | KeyCode::Char('C') | KeyCode::Esc => {
+                self.unsaved_prompt = None;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_conflict_keys(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(conflict) = &mut self.conflict else {
+            return Ok(());
+        };
+
+        if let Some(rows) = &mut conflict.merge {
+            match key.code {
+                KeyCode::Char('j') | KeyCode::Down if !rows.is_empty() => {
+                    conflict.merge_selected = (conflict.merge_selected + 1) % rows.len();
+                }
+                KeyCode::Char('k') | KeyCode::Up if !rows.is_empty() => {
+                    conflict.merge_selected =
+                        (conflict.merge_selected + rows.len() - 1) % rows.len();
+                }
+                KeyCode::Char(' ') | KeyCode::Tab => {
+                    if let Some(row) = rows.get_mut(conflict.merge_selected) {
+                        row.take_theirs = !row.take_theirs;
+                    }
+                }
+                KeyCode::Enter => {
+                    if let Some(conflict) = self.conflict.take() {
+                        self.apply_conflict_merge(conflict);
+                    }
+                }
+                KeyCode::Esc => {
+                    conflict.merge = None;
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        match key.code {
+            KeyCode::Char('r') | KeyCode::Char('R') => {
+                self.conflict = None;
+                if let Some(path) = self.selected_path.clone() {
+                    match self.load_plist_from(path) {
+                        Ok(()) => self.set_status_message(
+                            "↻ Reloaded from disk, your edits were discarded".to_string(),
+                        ),
+                        Err(e) => self.set_status_message(format!("✗ Failed to reload: {}", e)),
+                    }
+                }
+            }
+            KeyCode::Char('o') | KeyCode::Char('O') => {
+                let while_saving = conflict.while_saving;
+                self.conflict = None;
+                // Accept what's on disk now as the version being replaced
+                self.loaded_fingerprint = self
+                    .selected_path
+                    .as_deref()
+                    .and_then(|path| FileFingerprint::read(path).ok());
+                if while_saving {
+                    self.preview_save();
+                } else {
+                    self.set_status_message(
+                        "✓ Keeping your edits, saving will overwrite the file on disk".to_string(),
+                    );
+                }
+            }
+            KeyCode::Char('m') | KeyCode::Char('M') => {
+                if let (Some(base), Some(ours), Some(theirs)) =
+                    (&self.pristine_plist, &self.selected_plist, &conflict.theirs)
+                {
+                    conflict.merge = Some(merge_rows(base, ours, theirs));
+                    conflict.merge_selected = 0;
+                } else {
+                    self.set_status_message(
+                        "✗ The file on disk can't be parsed, reload or overwrite instead"
+                            .to_string(),
+                    );
+                }
+            }
+            KeyCode::Char('c') | KeyCode::Char('C') | KeyCode::Esc => {
+                self.conflict = None;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn apply_conflict_merge(&mut self, conflict: Conflict) {
+        let (Some(ours), Some(theirs), Some(rows)) =
+            (self.selected_plist.clone(), conflict.theirs, conflict.merge)
+        else {
+            return;
+        };
+        self.selected_plist = Some(apply_merge(&ours, &theirs, &rows));
+        self.record_change(None, ours);
+        // The merge result now sits on top of the on-disk version
+        self.pristine_plist = Some(theirs);
+        self.loaded_fingerprint = self
+            .selected_path
+            .as_deref()
+            .and_then(|path| FileFingerprint::read(path).ok());
+        let from_disk = rows.iter().filter(|row| row.take_theirs).count();
+        self.set_status_message(format!(
+            "✓ Merged {} field(s) from disk, Ctrl-S to save the result",
+            from_disk
+        ));
+    }
+
+    fn handle_save_preview_keys(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                if let Some(preview) = self.save_preview.take() {
+                    self.write_plist(preview.path, preview.contents);
+                    self.continue_navigation()?;
+                }
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                self.save_preview = None;
+                self.navigation_after_save = None;
+                self.set_status_message("✗ Save cancelled".to_string());
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                if let Some(preview) = &mut self.save_preview {
+                    preview.scroll = preview.scroll.saturating_add(1);
+                }
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                if let Some(preview) = &mut self.save_preview {
+                    preview.scroll = preview.scroll.saturating_sub(1);
+                }
+            }
+            KeyCode::PageDown => {
+                if let Some(preview) = &mut self.save_preview {
+                    preview.scroll = preview.scroll.saturating_add(10);
+                }
+            }
+            KeyCode::PageUp => {
+                if let Some(preview) = &mut self.save_preview {
+                    preview.scroll = preview.scroll.saturating_sub(10);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_transfer_confirmation_keys(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                if let Some(transfer) = self.pending_transfer.take() {
+                    self.start_transfer(transfer);
+                }
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                self.pending_transfer = None;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_elevation_prompt_keys(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                if let Some(action) = self.elevation_prompt.take() {
+                    self.request_privileged_action(action);
+                    self.continue_navigation()?;
+                }
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                self.elevation_prompt = None;
+                self.navigation_after_save = None;
+                self.set_status_message("✗ Save cancelled".to_string());
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_search_keys(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Char(c) => {
+                self.filter_text.push(c);
+                // Reset selection when filter changes
+                self.list_state
+                    .select(if self.get_filtered_agents().is_empty() {
+                        None
+                    } else {
+                        Some(0)
+                    });
+            }
+            KeyCode::Backspace => {
+                self.filter_text.pop();
+                // Reset selection when filter changes
+                self.list_state
+                    .select(if self.get_filtered_agents().is_empty() {
+                        None
+                    } else {
+                        Some(0)
+                    });
+            }
+            KeyCode::Enter => {
+                self.focus = Focus::Sidebar;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_sidebar_keys(&mut self, key: KeyEvent) -> Result<()> {
+        let filtered_count = self.get_filtered_agents().len();
+        if filtered_count == 0 {
+            return Ok(());
+        }
+
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                let i = match self.list_state.selected() {
+                    Some(i) => {
+                        if i >= filtered_count - 1 {
+                            0
+                        } else {
+                            i + 1
+                        }
+                    }
+                    _ => 0,
+                };
+                self.list_state.select(Some(i));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                let i = match self.list_state.selected() {
+                    Some(i) => {
+                        if i == 0 {
+                            filtered_count - 1
+                        } else {
+                            i - 1
+                        }
+                    }
+                    _ => 0,
+                };
+                self.list_state.select(Some(i));
+            }
+            KeyCode::Char('g') => {
+                self.list_state.select(Some(0));
+            }
+            KeyCode::Char('G') => {
+                self.list_state.select(Some(filtered_count - 1));
+            }
+            KeyCode::Enter => {
+                self.navigate(PendingNavigation::LoadAgent)?;
+            }
+            KeyCode::Char('m') => {
+                let selected = self
+                    .get_selected_filename()
+                    .map(|filename| self.get_tab_directory(self.current_tab).join(filename));
+                // Moving the open agent away takes its unsaved edits with it
+                if selected.is_some() && selected == self.selected_path {
+                    self.navigate(PendingNavigation::Transfer(TransferMode::Move))?;
+                } else {
+                    self.request_transfer(TransferMode::Move);
+                }
+            }
+            KeyCode::Char('c') => {
+                self.request_transfer(TransferMode::Copy);
+            }
+            KeyCode::Char('b') => {
+                self.navigate(PendingNavigation::BrowseBackups)?;
+            }
+            KeyCode::Char('x') => {
+                self.toggle_compare_mark();
+            }
+            KeyCode::Char('d') => {
+                self.compare_with_mark();
+            }
+            KeyCode::Char('a') => {
+                self.acknowledge_changes();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_form_keys(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.current_field = self.current_field.next();
+                self.auto_scroll_to_current_field();
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.current_field = self.current_field.previous();
+                self.auto_scroll_to_current_field();
+            }
+            KeyCode::Enter => {
+                self.start_editing()?;
+            }
+            KeyCode::Char(' ') if self.current_field.is_boolean() => {
+                self.start_editing()?;
+            }
+            KeyCode::Char('o') if self.selected_access == Access::ReadOnly => {
+                self.request_override();
+            }
+            KeyCode::Char('b') => {
+                self.open_backup_browser();
+            }
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.redo_edit();
+            }
+            KeyCode::Char('r') => {
+                self.refresh_from_disk()?;
+            }
+            KeyCode::Char('u') => {
+                self.undo_edit();
+            }
+            KeyCode::Char('f') => {
+                self.open_fix_menu();
+            }
+            KeyCode::Char('=') => {
+                self.preview_format();
+            }
+            KeyCode::PageUp => {
+                self.form_scroll_offset = self.form_scroll_offset.saturating_sub(5);
+            }
+            KeyCode::PageDown => {
+                self.form_scroll_offset = self.form_scroll_offset.saturating_add(5);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn auto_scroll_to_current_field(&mut self) {
+        // Calculate approximate line position of current field in the form
+        let field_position = match self.current_field {
+            FormField::Label => 0,
+            FormField::Program => 3,
+            FormField::ProgramArguments => 6,
+            FormField::StartInterval => 9,
+            FormField::ThrottleInterval => 12,
+            FormField::RunAtLoad => 15,
+            FormField::KeepAlive => 18,
+            FormField::AbandonProcessGroup => 21,
+            FormField::StandardOutPath => 24,
+            FormField::StandardErrorPath => 27,
+            FormField::WorkingDirectory => 30,
+            FormField::POSIXSpawnType => 33,
+            FormField::ProcessType => 36,
+            FormField::EnablePressuredExit => 39,
+            FormField::EnableTransactions => 42,
+            FormField::EventMonitor => 45,
+            FormField::LimitLoadToSessionType => 48,
+            FormField::AssociatedBundleIdentifiers => 53,
+            FormField::EnvironmentVariables => 58,
+        };
+
+        // Ensure the field is visible with some padding
+        const VIEWPORT_HEIGHT: u16 = 20; // Approximate form panel height
+        const PADDING: u16 = 3;
+
+        if field_position < self.form_scroll_offset + PADDING {
+            // Field is above visible area, scroll up
+            self.form_scroll_offset = field_position.saturating_sub(PADDING);
+        } else if field_position > self.form_scroll_offset + VIEWPORT_HEIGHT - PADDING {
+            // Field is below visible area, scroll down
+            self.form_scroll_offset = field_position.saturating_sub(VIEWPORT_HEIGHT - PADDING);
+        }
+    }
+
+    fn start_editing(&mut self) -> Result<()> {
+        if self.selected_plist.is_some() && self.selected_access == Access::ReadOnly {
+            self.set_status_message(
+                "✗ This agent is read-only, press o to copy it to User as an override".to_string(),
+            );
+            return Ok(());
+        }
+        if let Some(plist) = &self.selected_plist {
+            if self.current_field.is_boolean() {
+                self.toggle_boolean();
+                return Ok(());
+            }
+            if self.current_field.has_choices() {
+                self.picker = Some(Picker::new(self.current_field.clone(), plist));
+                return Ok(());
+            }
+            if self.current_field.is_list() {
+                self.list_editor = Some(ListEditor::new(self.current_field.clone(), plist));
+                return Ok(());
+            }
+            self.editing = true;
+            self.editing_field = Some(self.current_field.clone());
+            self.edit_input = TextInput::new(plist.field_text(&self.current_field));
+        }
+        Ok(())
+    }
+
+    fn handle_edit_keys(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Esc => {
+                self.editing = false;
+                self.editing_field = None;
+                self.edit_input = TextInput::default();
+                self.edit_error = None;
+                self.set_status_message("✗ Edit cancelled".to_string());
+            }
+            KeyCode::Enter => {
+                if self.save_field_edit()? {
+                    self.editing = false;
+                    self.editing_field = None;
+                }
+            }
+            // Everything else, including Tab and navigation keys, stays in the input
+            _ => {
+                if self.edit_input.handle_key(key) {
+                    self.edit_error = None;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn toggle_boolean(&mut self) {
+        let field = self.current_field.clone();
+        let Some(plist) = &mut self.selected_plist else {
+            return;
+        };
+        let before = plist.clone();
+        if let Some(value) = plist.bool_field_mut(&field) {
+            *value = cycle_bool(*value);
+            let shown = bool_label(*value);
+            self.set_status_message(format!("✓ {} is {}", field.name(), shown));
+            self.record_change(Some(field), before);
+        }
+    }
+
+    fn handle_picker_keys(&mut self, key: KeyEvent) {
+        let Some(picker) = &mut self.picker else {
+            return;
+        };
+        if matches!(key.code, KeyCode::Esc | KeyCode::Char('q')) {
+            self.picker = None;
+            return;
+        }
+        if !picker.handle_key(key) {
+            return;
+        }
+
+        if let (Some(picker), Some(plist)) = (self.picker.take(), &mut self.selected_plist) {
+            let before = plist.clone();
+            picker.apply(plist);
+            if *plist != before {
+                self.set_status_message(format!("✓ Updated {}", picker.field.name()));
+            }
+            self.record_change(Some(picker.field), before);
+        }
+    }
+
+    fn handle_list_editor_keys(&mut self, key: KeyEvent) {
+        let Some(editor) = &mut self.list_editor else {
+            return;
+        };
+        let closing =
+            editor.input.is_none() && matches!(key.code, KeyCode::Esc | KeyCode::Char('q'));
+        if !closing {
+            editor.handle_key(key);
+            return;
+        }
+
+        if let (Some(editor), Some(plist)) = (self.list_editor.take(), &mut self.selected_plist) {
+            let before = plist.clone();
+            editor.apply(plist);
+            if *plist != before {
+                self.set_status_message(format!("✓ Updated {}", editor.field.name()));
+            }
+            self.record_change(Some(editor.field), before);
+        }
+    }
+
+    /// Commits the edit if it's valid, returning false and keeping the editor
+    /// open with an inline error otherwise.
+    fn save_field_edit(&mut self) -> Result<bool> {
+        let (Some(plist), Some(field)) = (&mut self.selected_plist, self.editing_field.clone())
+        else {
+            return Ok(true);
+        };
+        let before = plist.clone();
+        if let Err(error) = plist.set_field_text(&field, self.edit_input.text()) {
+            self.edit_error = Some(error);
+            return Ok(false);
+        }
+
+        self.set_status_message(format!("✓ Updated {}", field.name()));
+        self.record_change(Some(field), before);
+        self.edit_input = TextInput::default();
+        self.edit_error = None;
+        Ok(true)
+    }
+
+    /// Adds the step from `before` to the current form to the agent's undo
+    /// history, unless nothing changed.
+    fn record_change(&mut self, field: Option<FormField>, before: PlistData) {
+        let name = field.as_ref().map_or("merge", FormField::name);
+        self.record_named_change(field, name, before);
+    }
+
+    fn record_named_change(
+        &mut self,
+        field: Option<FormField>,
+        name: &'static str,
+        before: PlistData,
+    ) {
+        if let (Some(path), Some(after)) = (&self.selected_path, &self.selected_plist)
+            && *after != before
+        {
+            self.edit_history
+                .entry(path.clone())
+                .or_default()
+                .record(FormChange {
+                    field,
+                    name,
+                    before,
+                    after: after.clone(),
+                });
+        }
+    }
+
+    fn undo_edit(&mut self) {
+        let change = self
+            .selected_path
+            .as_ref()
+            .and_then(|path| self.edit_history.get_mut(path))
+            .and_then(|history| history.undo())
+            .cloned();
+        match change {
+            Some(change) => {
+                self.selected_plist = Some(change.before.clone());
+                if let Some(field) = &change.field {
+                    self.current_field = field.clone();
+                }
+                self.set_status_message(format!("↶ Undid {}", change.name));
+            }
+            None => self.set_status_message("✗ Nothing to undo".to_string()),
+        }
+    }
+
+    fn redo_edit(&mut self) {
+        let change = self
+            .selected_path
+            .as_ref()
+            .and_then(|path| self.edit_history.get_mut(path))
+            .and_then(|history| history.redo())
+            .cloned();
+        match change {
+            Some(change) => {
+                self.selected_plist = Some(change.after.clone());
+                if let Some(field) = &change.field {
+                    self.current_field = field.clone();
+                }
+                self.set_status_message(format!("↷ Redid {}", change.name));
+            }
+            None => self.set_status_message("✗ Nothing to redo".to_string()),
+        }
+    }
+
+    /// Opens the save preview with a diff of the file on disk against what
+    /// would be written.
+    fn preview_save(&mut self) {
+        if let Some((path, contents)) = self.prepare_save() {
+            let current = fs::read_to_string(&path).unwrap_or_default();
+            self.save_preview = Some(SavePreview {
+                title: " 💾 Save Changes? ",
+                diff: unified_diff(&current, &contents),
+                path,
+                contents,
+                scroll: 0,
+            });
+        }
+    }
+
+    /// Previews saving the loaded plist laid out in the configured format
+    /// style.
+    fn preview_format(&mut self) {
+        let style = match Config::load() {
+            Ok(config) => config.format,
+            Err(e) => {
+                self.set_status_message(format!("✗ {}", e));
+                return;
+            }
+        };
+        let Some(plist) = &self.selected_plist else {
+            return;
+        };
+        let contents = format_plist(&plist.to_value(), &style);
+        if let Some((path, _)) = self.prepare_save() {
+            let current = fs::read_to_string(&path).unwrap_or_default();
+            if current == contents {
+                self.set_status_message("✓ Already formatted".to_string());
+                return;
+            }
+            self.save_preview = Some(SavePreview {
+                title: " 🧹 Save Formatted? ",
+                diff: unified_diff(&current, &contents),
+                path,
+                contents,
+                scroll: 0,
+            });
+        }
+    }
+
+    /// Serializes the loaded plist, or explains in the status bar why it
+    /// can't be saved.
+    fn prepare_save(&mut self) -> Option<(PathBuf, String)> {
+        let Some(plist) = &self.selected_plist else {
+            self.set_status_message("✗ No plist data to save".to_string());
+            return None;
+        };
+        let Some(file_path) = self.selected_path.clone() else {
+            self.set_status_message("✗ No agent selected".to_string());
+            return None;
+        };
+        if self.selected_access == Access::ReadOnly {
+            self.set_status_message(
+                "✗ This agent is read-only, press o to copy it to User as an override".to_string(),
+            );
+            return None;
+        }
+        if self.changed_on_disk() {
+            self.open_conflict(true);
+            return None;
+        }
+
+        // Write in the file's own layout so only the edited lines change
+        let layout = fs::read_to_string(&file_path)
+            .ok()
+            .and_then(|content| PlistLayout::detect(&content));
+        let xml = match layout {
+            Some(layout) => Ok(layout.write(&plist.to_value())),
+            None => Self::plist_to_xml(plist),
+        };
+        match xml {
+            Ok(xml_content) => Some((file_path, xml_content)),
+            Err(e) => {
+                self.set_status_message(format!("✗ Failed to serialize: {}", e));
+                None
+            }
+        }
+    }
+
+    fn write_plist(&mut self, file_path: PathBuf, contents: String) {
+        let filename = file_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if self.selected_access == Access::NeedsElevation {
+            self.elevation_prompt = Some(PrivilegedAction::Save {
+                filename,
+                path: file_path,
+                contents,
+            });
+            return;
+        }
+
+        if let Err(e) = backup_plist(&file_path) {
+            self.set_status_message(format!("✗ Backup failed, not saving: {}", e));
+            return;
+        }
+        if let Err(e) = write_atomically(&file_path, contents.as_bytes()) {
+            self.set_status_message(format!("✗ Failed to save: {}", e));
+            return;
+        }
+
+        self.pristine_plist = self.selected_plist.clone();
+        self.loaded_fingerprint = FileFingerprint::read(&file_path).ok();
+        self.reload_after_save(&filename, file_path);
+    }
+
+    fn reload_after_save(&mut self, filename: &str, file_path: PathBuf) {
+        self.acknowledge_own_write(&file_path);
+        // Reload the agent with launchctl
+        match self.reload_agent(file_path) {
+            Ok(()) => {
+                self.set_status_message(format!("✓ Saved and reloaded {}", filename));
+                // Refresh the agent status after successful reload
+                self.refresh_agent_status();
+            }
+            Err(e) => {
+                self.set_status_message(format!("✓ Saved {} but reload failed: {}", filename, e));
+            }
+        }
+    }
+
+    fn reload_agent(&self, file_path: PathBuf) -> Result<()> {
+        Self::unload_agent(&file_path)?;
+        Self::load_agent(&file_path)
+    }
+
+    fn unload_agent(file_path: &Path) -> Result<()> {
+        // Ignore errors if the agent wasn't loaded
+        let unload_result = std::process::Command::new("launchctl")
+            .args(["unload", &file_path.to_string_lossy()])
+            .output();
+
+        match unload_result {
+            Ok(output) => {
+                if !output.status.success() {
+                    // Unload failed, but that's okay if the agent wasn't loaded
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    if !stderr.contains("Could not find specified service") {
+                        return Err(color_eyre::eyre::eyre!("Unload failed: {}", stderr));
+                    }
+                }
+            }
+            Err(e) => {
+                return Err(color_eyre::eyre::eyre!(
+                    "Failed to run launchctl unload: {}",
+                    e
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn load_agent(file_path: &Path) -> Result<()> {
+        let load_result = std::process::Command::new("launchctl")
+            .args(["load", &file_path.to_string_lossy()])
+            .output();
+
+        match load_result {
+            Ok(output) => {
+                if !output.status.success() {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    return Err(color_eyre::eyre::eyre!("Load failed: {}", stderr));
+                }
+            }
+            Err(e) => {
+                return Err(color_eyre::eyre::eyre!(
+                    "Failed to run launchctl load: {}",
+                    e
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn refresh_agent_status(&mut self) {
+        // Refresh the status of agents in the current tab
+        let current_agents = self.get_current_agents_mut();
+        for agent in current_agents {
+            if let Some(label) = &agent.label {
+                agent.status = Self::check_agent_status(label);
+                agent.enabled = Self::check_agent_enabled(label);
+            }
+        }
+    }
+
+    fn request_transfer(&mut self, mode: TransferMode) {
+        let Some(to) = self.current_tab.transfer_target(mode) else {
+            self.set_status_message(
+                "✗ Apple agents can't be moved, press c to copy one to User instead".to_string(),
+            );
+            return;
+        };
+        match self.get_selected_filename() {
+            Some(filename) => {
+                self.pending_transfer = Some(AgentTransfer {
+                    mode,
+                    filename,
+                    from: self.current_tab,
+                    to,
+                });
+            }
+            None => self.set_status_message("✗ No agent selected".to_string()),
+        }
+    }
+
+    /// Asks to copy the agent open in the form into User, where it takes
+    /// precedence over the read-only original.
+    fn request_override(&mut self) {
+        let Some(path) = self.selected_path.clone() else {
+            self.set_status_message("✗ No agent loaded".to_string());
+            return;
+        };
+        let Some(filename) = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+        else {
+            return;
+        };
+        let from = [TabLocation::Global, TabLocation::Apple]
+            .into_iter()
+            .find(|tab| path.parent() == Some(self.get_tab_directory(*tab).as_path()));
+        match from {
+            Some(from) => {
+                self.pending_transfer = Some(AgentTransfer {
+                    mode: TransferMode::Copy,
+                    filename,
+                    from,
+                    to: TabLocation::User,
+                });
+            }
+            None => self.set_status_message(
+                "✗ This agent is already in User, there's nothing to override".to_string(),
+            ),
+        }
+    }
+
+    fn start_transfer(&mut self, transfer: AgentTransfer) {
+        let destination = self.get_tab_directory(transfer.to).join(&transfer.filename);
+        if destination.exists() {
+            self.set_status_message(format!(
+                "✗ {} already exists in {}",
+                transfer.filename,
+                destination.parent().unwrap_or(&destination).display()
+            ));
+            return;
+        }
+
+        if transfer.needs_root() {
+            self.request_privileged_action(PrivilegedAction::Transfer(transfer));
+        } else {
+            self.finish_transfer(transfer);
+        }
+    }
+
+    fn finish_transfer(&mut self, transfer: AgentTransfer) {
+        let source = self
+            .get_tab_directory(transfer.from)
+            .join(&transfer.filename);
+        match self.transfer_agent(&transfer) {
+            Ok(()) => {
+                if transfer.mode == TransferMode::Move
+                    && self.selected_path.as_ref() == Some(&source)
+                {
+                    self.clear_selected_plist();
+                }
+                let verb = match transfer.mode {
+                    TransferMode::Move => "Moved",
+                    TransferMode::Copy => "Copied",
                 };
+                self.set_status_message(format!(
+                    "✓ {} {} to {}",
+                    verb,
+                    transfer.filename,
+                    transfer.to.get_display_name()
+                ));
+            }
+            Err(e) => {
+                self.set_status_message(format!("✗ Transfer failed: {}", e));
+            }
+        }
 
-                let display_value = if is_editing {
-                    format!("{}│", &self.edit_buffer)
-                } else {
-                    value.to_string()
+        // Refresh both sides, even after a partial failure
+        self.reload_tab_agents(transfer.from);
+        self.reload_tab_agents(transfer.to);
+    }
+
+    fn transfer_agent(&self, transfer: &AgentTransfer) -> Result<()> {
+        let source = self
+            .get_tab_directory(transfer.from)
+            .join(&transfer.filename);
+        let destination = self.get_tab_directory(transfer.to).join(&transfer.filename);
+
+        match transfer.to {
+            TabLocation::Global => Self::run_helper_as_root(
+                &["write", &destination.to_string_lossy()],
+                Some(&fs::read(&source)?),
+            )?,
+            TabLocation::User | TabLocation::Apple => {
+                fs::copy(&source, &destination)?;
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    fs::set_permissions(&destination, fs::Permissions::from_mode(0o644))?;
+                }
+            }
+        }
+
+        // Both files share a label, so only the new location may stay loaded.
+        // SIP may refuse to unload Apple agents, which shouldn't block an override.
+        if let Err(e) = Self::unload_agent(&source)
+            && transfer.from != TabLocation::Apple
+        {
+            return Err(e);
+        }
+
+        if transfer.mode == TransferMode::Move {
+            match transfer.from {
+                TabLocation::Global => {
+                    Self::run_helper_as_root(&["remove", &source.to_string_lossy()], None)?
+                }
+                TabLocation::User | TabLocation::Apple => fs::remove_file(&source)?,
+            }
+        }
+
+        Self::load_agent(&destination)
+    }
+
+    /// Re-invokes this binary as `lam helper <args>` with root privileges,
+    /// going through `sudo -n` unless lam already is root. Callers make sure
+    /// sudo has cached credentials beforehand.
+    fn run_helper_as_root(args: &[&str], stdin: Option<&[u8]>) -> Result<()> {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        let exe = std::env::current_exe()?;
+        let mut command = if Self::is_root() {
+            Command::new(&exe)
+        } else {
+            let mut sudo = Command::new("sudo");
+            sudo.arg("-n").arg(&exe);
+            sudo
+        };
+        let mut child = command
+            .arg("helper")
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| color_eyre::eyre::eyre!("Failed to run helper: {}", e))?;
+
+        if let Some(mut child_stdin) = child.stdin.take() {
+            child_stdin.write_all(stdin.unwrap_or_default())?;
+        }
+        let output = child.wait_with_output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(color_eyre::eyre::eyre!("{}", stderr.trim()));
+        }
+        Ok(())
+    }
+
+    fn is_root() -> bool {
+        std::process::Command::new("id")
+            .arg("-u")
+            .output()
+            .is_ok_and(|o| String::from_utf8_lossy(&o.stdout).trim() == "0")
+    }
+
+    fn sudo_credentials_cached() -> bool {
+        std::process::Command::new("sudo")
+            .args(["-n", "true"])
+            .output()
+            .is_ok_and(|o| o.status.success())
+    }
+
+    fn switch_to_tab(&mut self, new_tab: TabLocation) {
+        if self.current_tab != new_tab {
+            self.current_tab = new_tab;
+            self.clear_selected_plist();
+            self.filter_text.clear();
+            self.form_scroll_offset = 0;
+            self.log_lines.clear();
+            self.log_scroll_offset = 0;
+
+            // Reset list selection to first item if available
+            let current_agents = self.get_current_agents();
+            self.list_state.select(if current_agents.is_empty() {
+                None
+            } else {
+                Some(0)
+            });
+        }
+    }
+
+    fn xml_escape(s: &str) -> String {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    }
+
+    /// Escapes `s` for a double-quoted attribute value.
+    fn xml_escape_attribute(s: &str) -> String {
+        Self::xml_escape(s).replace('"', "&quot;")
+    }
+
+    pub fn plist_to_xml(plist: &PlistData) -> Result<String> {
+        Ok(PlistLayout::legacy().write(&plist.to_value()))
+    }
+
+    fn refresh_log_content(&mut self) {
+        let path: Option<String> = match self.log_view {
+            LogView::Stdout => self
+                .selected_plist
+                .as_ref()
+                .and_then(|p| p.standard_out_path.as_deref().map(|s| s.to_string())),
+            LogView::Stderr => self
+                .selected_plist
+                .as_ref()
+                .and_then(|p| p.standard_error_path.as_deref().map(|s| s.to_string())),
+        };
+
+        self.log_lines = match path {
+            Some(ref p) if std::path::Path::new(p).exists() => {
+                let content = fs::read_to_string(p).unwrap_or_default();
+                const MAX_LINES: usize = 10_000;
+                let all: Vec<String> = content.lines().map(|l| l.to_string()).collect();
+                let start = all.len().saturating_sub(MAX_LINES);
+                all[start..].to_vec()
+            }
+            _ => Vec::new(),
+        };
+
+        self.log_scroll_offset = 0;
+    }
+
+    fn handle_log_keys(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.log_scroll_offset = self.log_scroll_offset.saturating_add(1);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.log_scroll_offset = self.log_scroll_offset.saturating_sub(1);
+            }
+            KeyCode::Char('g') => {
+                self.log_scroll_offset = 0;
+            }
+            KeyCode::Char('G') => {
+                self.log_scroll_offset = self.log_lines.len().saturating_sub(20) as u16;
+            }
+            KeyCode::PageDown => {
+                self.log_scroll_offset = self.log_scroll_offset.saturating_add(10);
+            }
+            KeyCode::PageUp => {
+                self.log_scroll_offset = self.log_scroll_offset.saturating_sub(10);
+            }
+            KeyCode::Char('t') => {
+                self.log_view = match self.log_view {
+                    LogView::Stdout => LogView::Stderr,
+                    LogView::Stderr => LogView::Stdout,
                 };
+                self.refresh_log_content();
+            }
+            KeyCode::Char('r') => {
+                self.refresh_log_content();
+                self.set_status_message("✓ Log refreshed".to_string());
+            }
+            _ => {}
+        }
+        Ok(())
+    }
 
-                // Add spacing between fields
-                if i > 0 {
-                    text.push(Line::from(""));
+    fn draw_log_panel(&mut self, frame: &mut Frame, area: Rect) {
+        let (border_style, title_style) = (
+            Style::default().fg(Theme::BORDER_FOCUSED),
+            Style::default()
+                .fg(Theme::ACCENT_PRIMARY)
+                .add_modifier(Modifier::BOLD),
+        );
+
+        let log_path: Option<String> = match self.log_view {
+            LogView::Stdout => self
+                .selected_plist
+                .as_ref()
+                .and_then(|p| p.standard_out_path.as_deref().map(|s| s.to_string())),
+            LogView::Stderr => self
+                .selected_plist
+                .as_ref()
+                .and_then(|p| p.standard_error_path.as_deref().map(|s| s.to_string())),
+        };
+
+        let stdout_style = if self.log_view == LogView::Stdout {
+            Style::default()
+                .fg(Theme::ACCENT_SECONDARY)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Theme::TEXT_DIM)
+        };
+        let stderr_style = if self.log_view == LogView::Stderr {
+            Style::default()
+                .fg(Theme::ACCENT_ERROR)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Theme::TEXT_DIM)
+        };
+
+        let line_count = self.log_lines.len();
+        let count_suffix = if line_count > 0 {
+            format!("  ({} lines)", line_count)
+        } else {
+            String::new()
+        };
+
+        let title_line = Line::from(vec![
+            Span::styled("📜 Logs  ", title_style),
+            Span::styled("stdout", stdout_style),
+            Span::styled(" | ", Style::default().fg(Theme::SUBTLE)),
+            Span::styled("stderr", stderr_style),
+            Span::styled(count_suffix, Style::default().fg(Theme::TEXT_DIM)),
+        ]);
+
+        let text: Vec<Line> = if self.selected_plist.is_none() {
+            vec![
+                Line::from(""),
+                Line::from(vec![Span::styled(
+                    "Select an agent from the sidebar to view logs",
+                    Style::default()
+                        .fg(Theme::TEXT_DIM)
+                        .add_modifier(Modifier::ITALIC),
+                )]),
+            ]
+        } else {
+            match log_path {
+                None => {
+                    let label = match self.log_view {
+                        LogView::Stdout => "StandardOutPath",
+                        LogView::Stderr => "StandardErrorPath",
+                    };
+                    vec![
+                        Line::from(""),
+                        Line::from(vec![Span::styled(
+                            format!("No {} configured for this agent", label),
+                            Style::default()
+                                .fg(Theme::TEXT_DIM)
+                                .add_modifier(Modifier::ITALIC),
+                        )]),
+                        Line::from(""),
+                        Line::from(vec![Span::styled(
+                            "Set the path in the agent editor (Tab to switch panels)",
+                            Style::default().fg(Theme::SUBTLE),
+                        )]),
+                    ]
+                }
+                Some(ref path) => {
+                    if self.log_lines.is_empty() {
+                        if std::path::Path::new(path).exists() {
+                            vec![
+                                Line::from(vec![Span::styled(
+                                    path.as_str(),
+                                    Style::default().fg(Theme::SUBTLE),
+                                )]),
+                                Line::from(""),
+                                Line::from(vec![Span::styled(
+                                    "Log file is empty",
+                                    Style::default()
+                                        .fg(Theme::TEXT_DIM)
+                                        .add_modifier(Modifier::ITALIC),
+                                )]),
+                            ]
+                        } else {
+                            vec![
+                                Line::from(vec![Span::styled(
+                                    "Log file not found:",
+                                    Style::default().fg(Theme::ACCENT_ERROR),
+                                )]),
+                                Line::from(""),
+                                Line::from(vec![Span::styled(
+                                    path.as_str(),
+                                    Style::default().fg(Theme::SUBTLE),
+                                )]),
+                            ]
+                        }
+                    } else {
+                        let mut lines: Vec<Line> = vec![
+                            Line::from(vec![Span::styled(
+                                path.as_str(),
+                                Style::default().fg(Theme::SUBTLE),
+                            )]),
+                            Line::from(""),
+                        ];
+                        lines.extend(self.log_lines.iter().enumerate().map(|(i, line)| {
+                            Line::from(vec![
+                                Span::styled(
+                                    format!("{:5} │ ", i + 1),
+                                    Style::default().fg(Theme::SUBTLE),
+                                ),
+                                Span::styled(line.clone(), Style::default().fg(Theme::FOREGROUND)),
+                            ])
+                        }));
+                        lines
+                    }
                 }
+            }
+        };
 
-                // Label on its own line
-                text.push(Line::from(vec![Span::styled(*label, label_style)]));
+        let paragraph = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .title(title_line)
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(border_style)
+                    .style(Style::default().bg(Theme::BACKGROUND))
+                    .padding(ratatui::widgets::Padding::uniform(1)),
+            )
+            .scroll((self.log_scroll_offset, 0));
 
-                // Value on next line with indentation
-                text.push(Line::from(vec![
-                    Span::raw("  "),
-                    Span::styled(display_value, value_style),
-                ]));
-            }
+        frame.render_widget(paragraph, area);
+    }
 
-            text.push(Line::from(""));
-            text.push(Line::from(""));
+    fn open_backup_browser(&mut self) {
+        if self.selected_path.is_none() {
+            self.set_status_message("✗ Load an agent to browse its backups".to_string());
+            return;
+        }
+        self.focus = Focus::Backups;
+        self.refresh_backups();
+        if self.backup_entries.is_empty() {
+            self.set_status_message("ℹ No backups yet, one is taken before every save".to_string());
+        }
+    }
 
-            if let Some(args) = &plist.program_arguments {
-                let is_current =
-                    self.focus == Focus::Form && self.current_field == FormField::ProgramArguments;
-                let is_editing = self.editing
-                    && self.editing_field.as_ref() == Some(&FormField::ProgramArguments);
+    fn refresh_backups(&mut self) {
+        self.backup_entries = self
+            .selected_path
+            .as_deref()
+            .and_then(|path| list_backups(path).ok())
+            .unwrap_or_default();
+        let selection = self
+            .backup_list_state
+            .selected()
+            .unwrap_or(0)
+            .min(self.backup_entries.len().saturating_sub(1));
+        self.backup_list_state
+            .select((!self.backup_entries.is_empty()).then_some(selection));
+        self.refresh_backup_diff();
+    }
 
-                let label_style = if is_current {
-                    Style::default()
-                        .fg(Theme::ACCENT_PRIMARY)
-                        .add_modifier(Modifier::BOLD)
-                } else {
-                    Style::default()
-                        .fg(Theme::ACCENT_MUTED)
-                        .add_modifier(Modifier::BOLD)
-                };
+    /// Marks the agent selected in the sidebar for comparison, or clears the
+    /// mark if it's already on it.
+    fn toggle_compare_mark(&mut self) {
+        let Some(filename) = self.get_selected_filename() else {
+            return;
+        };
+        let path = self.get_current_directory().join(&filename);
+        if self.compare_mark.as_ref() == Some(&path) {
+            self.compare_mark = None;
+            self.set_status_message(format!("✓ Unmarked {}", filename));
+        } else {
+            self.compare_mark = Some(path);
+            self.set_status_message(format!(
+                "✓ Marked {}, select another agent and press d to compare",
+                filename
+            ));
+        }
+    }
 
-                text.push(Line::from(vec![Span::styled(
-                    "⚙️  Program Arguments:",
-                    label_style,
-                )]));
-                text.push(Line::from(""));
+    /// Compares the marked agent with the one selected in the sidebar.
+    fn compare_with_mark(&mut self) {
+        let Some(mark) = self.compare_mark.clone() else {
+            self.set_status_message(
+                "✗ Press x on an agent to mark it for comparison first".to_string(),
+            );
+            return;
+        };
+        let Some(filename) = self.get_selected_filename() else {
+            return;
+        };
+        let path = self.get_current_directory().join(filename);
+        if path == mark {
+            self.set_status_message("✗ Select a different agent to compare with".to_string());
+            return;
+        }
+        let name = |path: &Path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default()
+        };
+        self.open_comparison(&mark, &path, name(&mark), name(&path));
+    }
 
-                for (i, arg) in args.iter().enumerate() {
-                    let arg_style = if is_editing {
-                        Style::default()
-                            .fg(Theme::BACKGROUND)
-                            .bg(Theme::ACCENT_WARNING)
-                    } else if is_current {
-                        Style::default()
-                            .fg(Theme::ACCENT_PRIMARY)
-                            .bg(Theme::HIGHLIGHT)
-                    } else {
-                        Style::default().fg(Theme::FOREGROUND)
-                    };
-                    text.push(Line::from(vec![
-                        Span::raw("    "),
-                        Span::styled(format!("[{}] ", i), Style::default().fg(Theme::TEXT_DIM)),
-                        Span::styled(arg, arg_style),
-                    ]));
-                }
+    /// Compares the loaded agent's file with the selected backup.
+    fn compare_selected_backup(&mut self) {
+        let (Some(current), Some(backup)) = (
+            self.selected_path.clone(),
+            self.backup_list_state
+                .selected()
+                .and_then(|i| self.backup_entries.get(i))
+                .cloned(),
+        ) else {
+            return;
+        };
+        self.open_comparison(
+            &current,
+            &backup,
+            "current".to_string(),
+            backup_display_name(&backup),
+        );
+    }
+
+    fn open_comparison(&mut self, old: &Path, new: &Path, old_name: String, new_name: String) {
+        let values = read_plist_value(old).and_then(|old| Ok((old, read_plist_value(new)?)));
+        match values {
+            Ok((old, new)) => {
+                let mut changes = Vec::new();
+                diff_plist_values("", &old, &new, &mut changes);
+                self.comparison = Some(Comparison {
+                    old_name,
+                    new_name,
+                    changes,
+                    scroll: 0,
+                });
             }
+            Err(e) => self.set_status_message(format!("✗ Failed to compare: {}", e)),
+        }
+    }
 
-            // Display Associated Bundle Identifiers
-            if let Some(ids) = &plist.associated_bundle_identifiers {
-                let is_current = self.focus == Focus::Form
-                    && self.current_field == FormField::AssociatedBundleIdentifiers;
-                let is_editing = self.editing
-                    && self.editing_field.as_ref() == Some(&FormField::AssociatedBundleIdentifiers);
+    fn handle_comparison_keys(&mut self, key: KeyEvent) {
+        let Some(comparison) = &mut self.comparison else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter => self.comparison = None,
+            KeyCode::Char('j') | KeyCode::Down => {
+                comparison.scroll = comparison.scroll.saturating_add(1)
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                comparison.scroll = comparison.scroll.saturating_sub(1)
+            }
+            KeyCode::PageDown => comparison.scroll = comparison.scroll.saturating_add(10),
+            KeyCode::PageUp => comparison.scroll = comparison.scroll.saturating_sub(10),
+            _ => {}
+        }
+    }
 
-                let label_style = if is_current {
-                    Style::default()
-                        .fg(Theme::ACCENT_PRIMARY)
-                        .add_modifier(Modifier::BOLD)
-                } else {
-                    Style::default()
-                        .fg(Theme::ACCENT_MUTED)
-                        .add_modifier(Modifier::BOLD)
-                };
+    fn draw_comparison(&mut self, frame: &mut Frame, comparison: Comparison) {
+        let area = frame.area();
+        let popup_area = centered_rect(area, 80, area.height.saturating_sub(4));
+        frame.render_widget(Clear, popup_area);
 
-                text.push(Line::from(""));
-                text.push(Line::from(vec![Span::styled(
-                    "📦 Associated Bundle Identifiers:",
-                    label_style,
-                )]));
-                text.push(Line::from(""));
+        let mut text = vec![
+            Line::from(vec![
+                Span::styled(
+                    comparison.old_name,
+                    Style::default().fg(Theme::ACCENT_ERROR),
+                ),
+                Span::styled(" → ", Style::default().fg(Theme::TEXT_DIM)),
+                Span::styled(
+                    comparison.new_name,
+                    Style::default().fg(Theme::ACCENT_SECONDARY),
+                ),
+            ]),
+            Line::from(""),
+        ];
+        if comparison.changes.is_empty() {
+            text.push(Line::from(vec![Span::styled(
+                "No differences, the plists have the same keys and values",
+                Style::default()
+                    .fg(Theme::TEXT_DIM)
+                    .add_modifier(Modifier::ITALIC),
+            )]));
+        }
+        for change in &comparison.changes {
+            let color = match change.kind {
+                ChangeKind::Added(_) => Theme::ACCENT_SECONDARY,
+                ChangeKind::Removed(_) => Theme::ACCENT_ERROR,
+                ChangeKind::Changed(..) => Theme::ACCENT_WARNING,
+            };
+            text.push(Line::from(vec![Span::styled(
+                format!("{} {}", change.kind.sign(), change.describe()),
+                Style::default().fg(color),
+            )]));
+        }
 
-                for (i, id) in ids.iter().enumerate() {
-                    let id_style = if is_editing {
-                        Style::default()
-                            .fg(Theme::BACKGROUND)
-                            .bg(Theme::ACCENT_WARNING)
-                    } else if is_current {
+        let dialog = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .title(Line::from(vec![Span::styled(
+                        " 🔀 Compare ",
                         Style::default()
                             .fg(Theme::ACCENT_PRIMARY)
-                            .bg(Theme::HIGHLIGHT)
-                    } else {
-                        Style::default().fg(Theme::FOREGROUND)
-                    };
-                    text.push(Line::from(vec![
-                        Span::raw("    "),
-                        Span::styled(format!("[{}] ", i), Style::default().fg(Theme::TEXT_DIM)),
-                        Span::styled(id, id_style),
-                    ]));
-                }
-            }
+                            .add_modifier(Modifier::BOLD),
+                    )]))
+                    .title_bottom(Line::from(vec![Span::styled(
+                        " Esc=Close, j/k PgUp/PgDn=Scroll ",
+                        Style::default().fg(Theme::TEXT_DIM),
+                    )]))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Thick)
+                    .border_style(Style::default().fg(Theme::ACCENT_PRIMARY))
+                    .style(Style::default().bg(Theme::BACKGROUND)),
+            )
+            .style(Style::default().bg(Theme::BACKGROUND))
+            .wrap(Wrap { trim: false })
+            .scroll((comparison.scroll, 0));
 
-            // Display Limit Load To Session Type
-            if let Some(session_type) = &plist.limit_load_to_session_type {
-                let is_current = self.focus == Focus::Form
-                    && self.current_field == FormField::LimitLoadToSessionType;
-                let is_editing = self.editing
-                    && self.editing_field.as_ref() == Some(&FormField::LimitLoadToSessionType);
+        frame.render_widget(dialog, popup_area);
+    }
 
-                let label_style = if is_current {
-                    Style::default()
-                        .fg(Theme::ACCENT_PRIMARY)
-                        .add_modifier(Modifier::BOLD)
-                } else {
-                    Style::default()
-                        .fg(Theme::ACCENT_MUTED)
-                        .add_modifier(Modifier::BOLD)
-                };
+    /// Audits the agents in every tab and lists the flagged ones.
+    fn open_audit(&mut self) {
+        let audited = Config::load()
+            .and_then(|config| audit_agent_directories(&config.audit.trusted_vendors));
+        match audited {
+            Ok(agents) => {
+                self.audit_agents = agents;
+                let flagged = flagged_agents(&self.audit_agents).len();
+                self.audit_list_state.select((flagged > 0).then_some(0));
+                self.focus = Focus::Audit;
+                self.set_status_message(format!(
+                    "✓ Audited {} agents, {} flagged",
+                    self.audit_agents.len(),
+                    flagged
+                ));
+            }
+            Err(e) => self.set_status_message(format!("✗ Audit failed: {}", e)),
+        }
+    }
 
-                text.push(Line::from(""));
-                text.push(Line::from(vec![Span::styled(
-                    "🔒 Limit Load To Session Type:",
-                    label_style,
-                )]));
-                text.push(Line::from(""));
+    fn selected_audit_agent(&self) -> Option<AuditedAgent> {
+        let i = self.audit_list_state.selected()?;
+        flagged_agents(&self.audit_agents)
+            .get(i)
+            .map(|agent| (*agent).clone())
+    }
 
-                match session_type {
-                    LimitLoadToSessionType::Single(s) => {
-                        let session_style = if is_editing {
-                            Style::default()
-                                .fg(Theme::BACKGROUND)
-                                .bg(Theme::ACCENT_WARNING)
-                        } else if is_current {
-                            Style::default()
-                                .fg(Theme::ACCENT_PRIMARY)
-                                .bg(Theme::HIGHLIGHT)
-                        } else {
-                            Style::default().fg(Theme::FOREGROUND)
-                        };
-                        text.push(Line::from(vec![
-                            Span::raw("    "),
-                            Span::styled(s, session_style),
-                        ]));
-                    }
-                    LimitLoadToSessionType::Multiple(sessions) => {
-                        for (i, session) in sessions.iter().enumerate() {
-                            let session_style = if is_editing {
-                                Style::default()
-                                    .fg(Theme::BACKGROUND)
-                                    .bg(Theme::ACCENT_WARNING)
-                            } else if is_current {
-                                Style::default()
-                                    .fg(Theme::ACCENT_PRIMARY)
-                                    .bg(Theme::HIGHLIGHT)
-                            } else {
-                                Style::default().fg(Theme::FOREGROUND)
-                            };
-                            text.push(Line::from(vec![
-                                Span::raw("    "),
-                                Span::styled(
-                                    format!("[{}] ", i),
-                                    Style::default().fg(Theme::TEXT_DIM),
-                                ),
-                                Span::styled(session, session_style),
-                            ]));
-                        }
-                    }
-                }
+    /// Writes the audit as JSON to `<state dir>/audits/<timestamp>.json`.
+    fn export_audit(&mut self) {
+        let exported = state_dir().and_then(|dir| {
+            let dir = dir.join("audits");
+            fs::create_dir_all(&dir)?;
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or_default();
+            let path = dir.join(format!("{}.json", format_timestamp(now)));
+            write_atomically(&path, format_audit_json(&self.audit_agents).as_bytes())?;
+            Ok(path)
+        });
+        match exported {
+            Ok(path) => {
+                self.set_status_message(format!("✓ Exported the audit to {}", path.display()))
             }
+            Err(e) => self.set_status_message(format!("✗ Failed to export the audit: {}", e)),
+        }
+    }
 
-            // Display Environment Variables
-            if let Some(env_vars) = &plist.environment_variables {
-                let is_current = self.focus == Focus::Form
-                    && self.current_field == FormField::EnvironmentVariables;
-                let is_editing = self.editing
-                    && self.editing_field.as_ref() == Some(&FormField::EnvironmentVariables);
-
-                let label_style = if is_current {
-                    Style::default()
-                        .fg(Theme::ACCENT_PRIMARY)
-                        .add_modifier(Modifier::BOLD)
-                } else {
-                    Style::default()
-                        .fg(Theme::ACCENT_MUTED)
-                        .add_modifier(Modifier::BOLD)
-                };
+    fn handle_audit_keys(&mut self, key: KeyEvent) -> Result<()> {
+        let count = flagged_agents(&self.audit_agents).len();
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down if count > 0 => {
+                let i = self
+                    .audit_list_state
+                    .selected()
+                    .map_or(0, |i| (i + 1) % count);
+                self.audit_list_state.select(Some(i));
+            }
+            KeyCode::Char('k') | KeyCode::Up if count > 0 => {
+                let i = self
+                    .audit_list_state
+                    .selected()
+                    .map_or(0, |i| (i + count - 1) % count);
+                self.audit_list_state.select(Some(i));
+            }
+            KeyCode::Enter => {
+                let target = self.selected_audit_agent().and_then(|agent| {
+                    let filename = agent.path.file_name()?.to_string_lossy().to_string();
+                    Some((agent.location?, filename))
+                });
+                if let Some((location, filename)) = target {
+                    self.navigate(PendingNavigation::OpenAgent(location, filename))?;
+                }
+            }
+            KeyCode::Char('e') => {
+                self.export_audit();
+            }
+            KeyCode::Char('r') => {
+                self.open_audit();
+            }
+            KeyCode::Char('A') => {
+                self.focus = Focus::Sidebar;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
 
-                text.push(Line::from(""));
-                text.push(Line::from(vec![Span::styled(
-                    "🌍 Environment Variables:",
-                    label_style,
-                )]));
-                text.push(Line::from(""));
+    fn draw_audit_panel(&mut self, frame: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+            .split(area);
 
-                for (key, value) in env_vars.iter() {
-                    let env_style = if is_editing {
-                        Style::default()
-                            .fg(Theme::BACKGROUND)
-                            .bg(Theme::ACCENT_WARNING)
-                    } else if is_current {
+        let title_style = Style::default()
+            .fg(Theme::ACCENT_PRIMARY)
+            .add_modifier(Modifier::BOLD);
+
+        let flagged = flagged_agents(&self.audit_agents);
+        let items: Vec<ListItem> = flagged
+            .iter()
+            .map(|agent| {
+                let risk = agent.risk().unwrap_or(Risk::Low);
+                let name = agent
+                    .label
+                    .clone()
+                    .or_else(|| Some(agent.path.file_name()?.to_string_lossy().to_string()))
+                    .unwrap_or_default();
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{} ", risk.icon()),
                         Style::default()
-                            .fg(Theme::ACCENT_PRIMARY)
-                            .bg(Theme::HIGHLIGHT)
-                    } else {
-                        Style::default().fg(Theme::FOREGROUND)
-                    };
+                            .fg(risk.color())
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(
+                        format!(
+                            "{} ",
+                            agent
+                                .location
+                                .as_ref()
+                                .map(TabLocation::get_display_name)
+                                .unwrap_or_default()
+                        ),
+                        Style::default().fg(Theme::TEXT_DIM),
+                    ),
+                    Span::styled(name, Style::default().fg(Theme::FOREGROUND)),
+                    Span::styled(
+                        format!("  {}", agent.findings.len()),
+                        Style::default().fg(risk.color()),
+                    ),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .title(Line::from(vec![Span::styled(
+                        format!(
+                            "🛡️  Audit ({} of {} flagged)",
+                            flagged.len(),
+                            self.audit_agents.len()
+                        ),
+                        title_style,
+                    )]))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(Theme::BORDER_FOCUSED))
+                    .style(Style::default().bg(Theme::BACKGROUND)),
+            )
+            .highlight_style(
+                Style::default()
+                    .bg(Theme::HIGHLIGHT)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("▶ ");
+        frame.render_stateful_widget(list, chunks[0], &mut self.audit_list_state);
+
+        let text = match self.selected_audit_agent() {
+            None => vec![Line::from(vec![Span::styled(
+                "Nothing flagged",
+                Style::default()
+                    .fg(Theme::TEXT_DIM)
+                    .add_modifier(Modifier::ITALIC),
+            )])],
+            Some(agent) => {
+                let mut text = vec![
+                    Line::from(vec![Span::styled(
+                        agent.path.display().to_string(),
+                        Style::default().fg(Theme::ACCENT_MUTED),
+                    )]),
+                    Line::from(vec![
+                        Span::styled("Program: ", Style::default().fg(Theme::TEXT_DIM)),
+                        Span::styled(
+                            agent
+                                .program
+                                .clone()
+                                .unwrap_or_else(|| "(none)".to_string()),
+                            Style::default().fg(Theme::FOREGROUND),
+                        ),
+                    ]),
+                    Line::from(""),
+                ];
+                for finding in &agent.findings {
                     text.push(Line::from(vec![
-                        Span::raw("    "),
                         Span::styled(
-                            format!("{}=", key),
-                            Style::default().fg(Theme::ACCENT_MUTED),
+                            format!("{} {:<6} ", finding.risk.icon(), finding.risk.name()),
+                            Style::default().fg(finding.risk.color()),
+                        ),
+                        Span::styled(
+                            format!("[{}] ", finding.rule),
+                            Style::default().fg(Theme::TEXT_DIM),
+                        ),
+                        Span::styled(
+                            finding.message.clone(),
+                            Style::default().fg(Theme::FOREGROUND),
                         ),
-                        Span::styled(value, env_style),
                     ]));
                 }
+                text
             }
+        };
 
-            // Create title with scroll indicators
-            let total_content_height = text.len() as u16;
-            let viewport_height = 20; // Approximate visible lines
-            let can_scroll_up = self.form_scroll_offset > 0;
-            let can_scroll_down = total_content_height > viewport_height + self.form_scroll_offset;
+        let details = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .title(Line::from(vec![Span::styled("🔎 Findings", title_style)]))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(Theme::BORDER_UNFOCUSED))
+                    .style(Style::default().bg(Theme::BACKGROUND)),
+            )
+            .wrap(Wrap { trim: false });
+        frame.render_widget(details, chunks[1]);
+    }
 
-            let mut title_spans = vec![Span::styled("⚙️  Agent Editor", title_style)];
+    fn agent_directories(&self) -> [&Path; 3] {
+        [
+            &self.user_agents_dir,
+            &self.global_agents_dir,
+            &self.apple_agents_dir,
+        ]
+    }
 
-            if can_scroll_up || can_scroll_down {
-                title_spans.push(Span::raw(" "));
-                if can_scroll_up {
-                    title_spans.push(Span::styled(
-                        "↑",
-                        Style::default().fg(Theme::ACCENT_SECONDARY),
-                    ));
+    /// Compares the agent directories with the acknowledged snapshot.
+    fn refresh_agent_changes(&mut self) {
+        self.agent_changes = self
+            .current_snapshot
+            .changes_since(&self.acknowledged_snapshot);
+    }
+
+    /// Makes the current agents the acknowledged snapshot, clearing the new
+    /// and changed badges.
+    fn acknowledge_changes(&mut self) {
+        let mut current = Snapshot::take(&self.agent_directories());
+        let count = current.changes_since(&self.acknowledged_snapshot).len();
+        if count == 0 {
+            self.set_status_message("✓ No agent changes to acknowledge".to_string());
+            return;
+        }
+        if let Err(e) = current.save() {
+            self.set_status_message(format!("✗ Failed to save the snapshot: {}", e));
+            return;
+        }
+        self.current_snapshot = current.clone();
+        self.acknowledged_snapshot = current;
+        self.snapshot_error = false;
+        self.agent_changes.clear();
+        self.set_status_message(format!("✓ Acknowledged {} agent changes", count));
+    }
+
+    /// Acknowledges a write lam made itself, so it isn't reported as a change.
+    fn acknowledge_own_write(&mut self, path: &Path) {
+        self.current_snapshot.update(path);
+        self.acknowledged_snapshot.update(path);
+        // A snapshot file that couldn't be read is only replaced when asked to
+        if !self.snapshot_error {
+            let _ = self.acknowledged_snapshot.save();
+        }
+        self.refresh_agent_changes();
+    }
+
+    /// Diffs the current file against the selected backup, showing what a
+    /// restore would change.
+    fn refresh_backup_diff(&mut self) {
+        self.backup_diff_scroll = 0;
+        self.backup_diff = match (
+            self.selected_path.as_ref(),
+            self.backup_list_state
+                .selected()
+                .and_then(|i| self.backup_entries.get(i)),
+        ) {
+            (Some(current), Some(backup)) => {
+                let current = fs::read_to_string(current).unwrap_or_default();
+                let backup = fs::read_to_string(backup).unwrap_or_default();
+                unified_diff(&current, &backup)
+            }
+            _ => Vec::new(),
+        };
+    }
+
+    fn restore_selected_backup(&mut self) {
+        let Some(path) = self.selected_path.clone() else {
+            return;
+        };
+        let Some(backup) = self
+            .backup_list_state
+            .selected()
+            .and_then(|i| self.backup_entries.get(i))
+            .cloned()
+        else {
+            return;
+        };
+        let contents = match fs::read_to_string(&backup) {
+            Ok(contents) => contents,
+            Err(e) => {
+                self.set_status_message(format!("✗ Failed to read backup: {}", e));
+                return;
+            }
+        };
+        let filename = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        // Like a save, a restore mustn't silently replace another program's edits
+        if self.changed_on_disk() {
+            self.set_status_message(format!(
+                "✗ {} changed on disk since it was loaded, press r to reload it before restoring",
+                filename
+            ));
+            return;
+        }
+
+        match self.selected_access {
+            Access::ReadOnly => {
+                self.set_status_message("✗ This agent is read-only".to_string());
+            }
+            Access::NeedsElevation => {
+                self.elevation_prompt = Some(PrivilegedAction::Save {
+                    filename,
+                    path,
+                    contents,
+                });
+            }
+            Access::Writable => {
+                // Back up the current version too, so a restore can be undone
+                let written = backup_plist(&path)
+                    .and_then(|_| Ok(write_atomically(&path, contents.as_bytes())?));
+                if let Err(e) = written {
+                    self.set_status_message(format!("✗ Failed to restore: {}", e));
+                    return;
                 }
-                if can_scroll_down {
-                    title_spans.push(Span::styled(
-                        "↓",
-                        Style::default().fg(Theme::ACCENT_SECONDARY),
-                    ));
+                self.acknowledge_own_write(&path);
+
+                match self.reload_agent(path.clone()) {
+                    Ok(()) => {
+                        self.set_status_message(format!(
+                            "✓ Restored {} from {} and reloaded",
+                            filename,
+                            backup_display_name(&backup)
+                        ));
+                        self.refresh_agent_status();
+                    }
+                    Err(e) => {
+                        self.set_status_message(format!(
+                            "✓ Restored {} but reload failed: {}",
+                            filename, e
+                        ));
+                    }
                 }
-                title_spans.push(Span::styled(
-                    " [PgUp/PgDn]",
-                    Style::default().fg(Theme::TEXT_DIM),
-                ));
+                let _ = self.load_plist_from(path);
+                self.refresh_backups();
             }
+        }
+    }
 
-            let paragraph = Paragraph::new(text)
-                .block(
-                    Block::default()
-                        .title(Line::from(title_spans))
-                        .borders(Borders::ALL)
-                        .border_type(BorderType::Rounded)
-                        .border_style(border_style)
-                        .style(Style::default().bg(Theme::BACKGROUND))
-                        .padding(ratatui::widgets::Padding::uniform(1)),
-                )
-                .wrap(Wrap { trim: true })
-                .scroll((self.form_scroll_offset, 0));
+    fn handle_backup_keys(&mut self, key: KeyEvent) -> Result<()> {
+        let count = self.backup_entries.len();
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down if count > 0 => {
+                let i = self
+                    .backup_list_state
+                    .selected()
+                    .map_or(0, |i| (i + 1) % count);
+                self.backup_list_state.select(Some(i));
+                self.refresh_backup_diff();
+            }
+            KeyCode::Char('k') | KeyCode::Up if count > 0 => {
+                let i = self
+                    .backup_list_state
+                    .selected()
+                    .map_or(0, |i| (i + count - 1) % count);
+                self.backup_list_state.select(Some(i));
+                self.refresh_backup_diff();
+            }
+            KeyCode::PageDown => {
+                self.backup_diff_scroll = self.backup_diff_scroll.saturating_add(10);
+            }
+            KeyCode::PageUp => {
+                self.backup_diff_scroll = self.backup_diff_scroll.saturating_sub(10);
+            }
+            KeyCode::Enter => {
+                self.navigate(PendingNavigation::RestoreBackup)?;
+            }
+            KeyCode::Char('d') => {
+                self.compare_selected_backup();
+            }
+            KeyCode::Char('b') => {
+                self.focus = Focus::Form;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
 
-            frame.render_widget(paragraph, area);
-        } else {
-            let paragraph = Paragraph::new(Line::from(vec![
-                Span::styled("📝 ", Style::default().fg(Theme::TEXT_DIM)),
-                Span::styled(
-                    "Select an agent from the sidebar to view and edit its configuration",
-                    Style::default()
-                        .fg(Theme::TEXT_DIM)
-                        .add_modifier(Modifier::ITALIC),
-                ),
-            ]))
+    fn draw_backup_panel(&mut self, frame: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+            .split(area);
+
+        let title_style = Style::default()
+            .fg(Theme::ACCENT_PRIMARY)
+            .add_modifier(Modifier::BOLD);
+
+        let items: Vec<ListItem> = self
+            .backup_entries
+            .iter()
+            .map(|backup| {
+                ListItem::new(Line::from(vec![
+                    Span::styled("🕘 ", Style::default().fg(Theme::TEXT_DIM)),
+                    Span::styled(
+                        backup_display_name(backup),
+                        Style::default().fg(Theme::FOREGROUND),
+                    ),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
             .block(
                 Block::default()
                     .title(Line::from(vec![Span::styled(
-                        "⚙️  Agent Editor",
+                        format!("🗄️  Backups ({})", self.backup_entries.len()),
                         title_style,
                     )]))
                     .borders(Borders::ALL)
                     .border_type(BorderType::Rounded)
-                    .border_style(border_style)
+                    .border_style(Style::default().fg(Theme::BORDER_FOCUSED))
                     .style(Style::default().bg(Theme::BACKGROUND)),
             )
-            .style(Style::default().fg(Theme::TEXT_DIM))
-            .alignment(ratatui::layout::Alignment::Center)
-            .wrap(Wrap { trim: true });
+            .highlight_style(
+                Style::default()
+                    .bg(Theme::HIGHLIGHT)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("▶ ");
+        frame.render_stateful_widget(list, chunks[0], &mut self.backup_list_state);
+
+        let text = if self.backup_entries.is_empty() {
+            vec![Line::from(vec![Span::styled(
+                "No backups for this agent yet",
+                Style::default()
+                    .fg(Theme::TEXT_DIM)
+                    .add_modifier(Modifier::ITALIC),
+            )])]
+        } else if self.backup_diff.is_empty() {
+            vec![Line::from(vec![Span::styled(
+                "Identical to the current file",
+                Style::default()
+                    .fg(Theme::TEXT_DIM)
+                    .add_modifier(Modifier::ITALIC),
+            )])]
+        } else {
+            diff_to_lines(&self.backup_diff)
+        };
+
+        let diff = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .title(Line::from(vec![
+                        Span::styled("🔀 Diff  ", title_style),
+                        Span::styled("current → backup", Style::default().fg(Theme::TEXT_DIM)),
+                    ]))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(Theme::BORDER_UNFOCUSED))
+                    .style(Style::default().bg(Theme::BACKGROUND)),
+            )
+            .scroll((self.backup_diff_scroll, 0));
+        frame.render_widget(diff, chunks[1]);
+    }
+
+    fn request_quit(&mut self) {
+        if self.is_dirty() {
+            self.unsaved_prompt = Some(PendingNavigation::Quit);
+        } else {
+            self.showing_exit_confirmation = true;
+        }
+    }
+
+    fn quit(&mut self) {
+        self.running = false;
+    }
+}
+
+/// Whether the current user may write to `path`, as judged by access(2).
+#[cfg(unix)]
+fn is_writable(path: &Path) -> bool {
+    nix::unistd::access(path, nix::unistd::AccessFlags::W_OK).is_ok()
+}
+
+#[cfg(not(unix))]
+fn is_writable(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|metadata| !metadata.permissions().readonly())
+}
+
+/// Indentation `lam fmt` writes.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "IndentSetting")]
+enum Indent {
+    Tab,
+    Spaces(usize),
+}
+
+/// `indent` in the config file, either `"tab"` or a number of spaces.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum IndentSetting {
+    Width(usize),
+    Name(String),
+}
+
+impl TryFrom<IndentSetting> for Indent {
+    type Error = String;
+
+    fn try_from(setting: IndentSetting) -> Result<Self, Self::Error> {
+        match setting {
+            IndentSetting::Width(width) => width.to_string().parse(),
+            IndentSetting::Name(name) => name.parse(),
+        }
+    }
+}
+
+impl std::str::FromStr for Indent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tab" | "tabs" => Ok(Indent::Tab),
+            _ => match s.parse() {
+                Ok(width @ 1..=8) => Ok(Indent::Spaces(width)),
+                _ => Err(format!(
+                    "Indent must be \"tab\" or 1 to 8 spaces, got {}",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+impl Indent {
+    fn unit(&self) -> String {
+        match self {
+            Indent::Tab => "\t".to_string(),
+            Indent::Spaces(width) => " ".repeat(*width),
+        }
+    }
+}
+
+/// Order of the top-level keys. Nested dictionaries, such as
+/// EnvironmentVariables, always keep their order.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum KeyOrder {
+    /// The order of launchd.plist(5), unknown keys last.
+    Launchd,
+    Alphabetical,
+    /// The order the keys are in now.
+    Preserve,
+}
+
+impl std::str::FromStr for KeyOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "launchd" => Ok(KeyOrder::Launchd),
+            "alphabetical" => Ok(KeyOrder::Alphabetical),
+            "preserve" => Ok(KeyOrder::Preserve),
+            _ => Err(format!(
+                "Key order must be launchd, alphabetical or preserve, got {}",
+                s
+            )),
+        }
+    }
+}
 
-            frame.render_widget(paragraph, area);
+/// The layout `lam fmt` and the form's format action write. The default
+/// matches plists written by Xcode and `plutil`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FormatStyle {
+    indent: Indent,
+    key_order: KeyOrder,
+    /// An empty line between top-level keys.
+    blank_lines: bool,
+}
+
+impl Default for FormatStyle {
+    fn default() -> Self {
+        Self {
+            indent: Indent::Tab,
+            key_order: KeyOrder::Launchd,
+            blank_lines: false,
         }
     }
+}
 
-    fn draw_status_bar(&mut self, frame: &mut Frame, area: Rect) {
-        // Update status timer
-        if self.status_timer > 0 {
-            self.status_timer -= 1;
-            if self.status_timer == 0 {
-                self.status_message.clear();
-            }
+/// Settings from `config.toml` in lam's config directory.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Config {
+    format: FormatStyle,
+    audit: AuditConfig,
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/lam/config.toml` or `~/.config/lam/config.toml`.
+    fn path() -> Result<PathBuf> {
+        if let Some(dir) = std::env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+            return Ok(PathBuf::from(dir).join("lam").join("config.toml"));
         }
+        let home_dir = dirs::home_dir()
+            .ok_or_else(|| color_eyre::eyre::eyre!("Could not find home directory"))?;
+        Ok(home_dir.join(".config").join("lam").join("config.toml"))
+    }
 
-        let (status_text, status_style, icon) = if !self.status_message.is_empty() {
-            let icon = if self.status_message.contains('✓') {
-                "✅"
-            } else if self.status_message.contains('✗') {
-                "❌"
-            } else {
-                "ℹ️"
-            };
-            (
-                self.status_message.clone(),
-                Style::default()
-                    .fg(Theme::ACCENT_SECONDARY)
-                    .add_modifier(Modifier::BOLD),
-                icon,
-            )
-        } else if self.editing {
-            (
-                format!(
-                    "EDITING: {} | Enter=Save, Esc=Cancel",
-                    self.get_editing_field_name()
-                ),
-                Style::default()
-                    .fg(Theme::ACCENT_WARNING)
-                    .add_modifier(Modifier::BOLD),
-                "✏️",
-            )
-        } else {
-            let (text, icon) = match self.focus {
-                Focus::Search => (
-                    "Type to filter agents | Enter=Focus Sidebar, Tab=Next Panel, 1/2/3=Switch Tabs".to_string(),
-                    "🔍",
-                ),
-                Focus::Sidebar => (
-                    "j/k=Navigate, Enter=Load, /=Search, 1/2/3=Switch Tabs".to_string(),
-                    "📋",
-                ),
-                Focus::Form => (
-                    "j/k=Navigate Fields, Enter=Edit, PgUp/PgDn=Scroll, Ctrl+S=Save, l=Logs | Tab=Switch Panel, 1/2/3=Switch Tabs".to_string(),
-                    "⚙️",
-                ),
-                Focus::Logs => (
-                    "j/k=Scroll, g/G=Top/Bottom, PgUp/PgDn=Page, t=Toggle stdout/stderr, r=Refresh | Tab=Switch Panel".to_string(),
-                    "📜",
-                ),
+    /// Reads the config file, using the defaults when there isn't one.
+    fn load() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)?;
+        toml::from_str(&content)
+            .map_err(|e| color_eyre::eyre::eyre!("Invalid {}: {}", path.display(), e))
+    }
+}
+
+/// lam's state directory, `$XDG_STATE_HOME/lam` or `~/.local/state/lam`.
+fn state_dir() -> Result<PathBuf> {
+    if let Some(dir) = std::env::var_os("XDG_STATE_HOME").filter(|dir| !dir.is_empty()) {
+        return Ok(PathBuf::from(dir).join("lam"));
+    }
+    let home_dir =
+        dirs::home_dir().ok_or_else(|| color_eyre::eyre::eyre!("Could not find home directory"))?;
+    Ok(home_dir.join(".local").join("state").join("lam"))
+}
+
+/// `name` as a single directory name, or `None` if it can't be one.
+fn backup_dir_name(name: &str) -> Option<String> {
+    let name = name.replace(['/', '\\'], "_");
+    (!matches!(name.as_str(), "" | "." | "..")).then_some(name)
+}
+
+/// Backups of a plist are grouped under `backups` by its label, falling back
+/// to the filename when the label can't name a directory.
+fn backup_dir_for(backups: &Path, path: &Path) -> PathBuf {
+    let name = App::extract_label_from_file(path)
+        .and_then(|label| backup_dir_name(&label))
+        .or_else(|| backup_dir_name(&path.file_stem()?.to_string_lossy()))
+        .unwrap_or_else(|| "_".to_string());
+    backups.join(name)
+}
+
+fn backups_root() -> Result<PathBuf> {
+    Ok(state_dir()?.join("backups"))
+}
+
+/// Copies the current version of `path` to
+/// `<state dir>/backups/<label>/<timestamp>.plist` before it gets overwritten.
+fn backup_plist(path: &Path) -> Result<Option<PathBuf>> {
+    backup_plist_in(&backups_root()?, path)
+}
+
+fn backup_plist_in(backups: &Path, path: &Path) -> Result<Option<PathBuf>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let dir = backup_dir_for(backups, path);
+    fs::create_dir_all(&dir)?;
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    let timestamp = format_timestamp(now);
+    let mut backup = dir.join(format!("{}.plist", timestamp));
+    let mut attempt = 1;
+    while backup.exists() {
+        backup = dir.join(format!("{}.{}.plist", timestamp, attempt));
+        attempt += 1;
+    }
+
+    fs::copy(path, &backup)?;
+    Ok(Some(backup))
+}
+
+/// Backups of `path`, newest first.
+fn list_backups(path: &Path) -> Result<Vec<PathBuf>> {
+    list_backups_in(&backups_root()?, path)
+}
+
+fn list_backups_in(backups: &Path, path: &Path) -> Result<Vec<PathBuf>> {
+    let dir = backup_dir_for(backups, path);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    // Ordered by the timestamp in the name, since copies can keep the
+    // original's modification time. Backups made within the same second
+    // are numbered `<timestamp>.1.plist` and up.
+    let mut backups: Vec<((String, u32), PathBuf)> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "plist"))
+        .map(|path| {
+            let stem = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let order = match stem.split_once('.') {
+                Some((timestamp, attempt)) => {
+                    (timestamp.to_string(), attempt.parse().unwrap_or_default())
+                }
+                None => (stem, 0),
             };
-            (text, Style::default().fg(Theme::ACCENT_MUTED), icon)
-        };
+            (order, path)
+        })
+        .collect();
+    backups.sort_by(|a, b| b.cmp(a));
+    Ok(backups.into_iter().map(|(_, path)| path).collect())
+}
 
-        let mut status_spans = vec![Span::styled(
-            format!("{} ", icon),
-            Style::default().fg(Theme::ACCENT_PRIMARY),
-        )];
+/// `2025-08-14T09-30-00Z.plist` is shown as `2025-08-14 09:30:00 UTC`.
+fn backup_display_name(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    match stem.split_once('T') {
+        Some((date, time)) if time.len() >= 8 => {
+            format!("{} {} UTC", date, time[..8].replace('-', ":"))
+        }
+        _ => stem,
+    }
+}
 
-        // Add colored legend for sidebar
-        if self.focus == Focus::Sidebar {
-            // Add status legend with proper colors
-            status_spans.extend(vec![
-                Span::styled("●", Style::default().fg(Theme::ACCENT_SECONDARY)), // Running (Green)
-                Span::styled("=Running ", Style::default().fg(Theme::FOREGROUND)),
-                Span::styled("●", Style::default().fg(Theme::ACCENT_ERROR)), // Stopped (Red)
-                Span::styled("=Stopped ", Style::default().fg(Theme::FOREGROUND)),
-                Span::styled("◉", Style::default().fg(Theme::ACCENT_MUTED)), // Enabled (Cyan)
-                Span::styled("=Enabled | ", Style::default().fg(Theme::FOREGROUND)),
-            ]);
+/// How an agent differs from the acknowledged snapshot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AgentChange {
+    New,
+    Changed,
+    Removed,
+}
+
+impl AgentChange {
+    fn name(&self) -> &'static str {
+        match self {
+            AgentChange::New => "new",
+            AgentChange::Changed => "changed",
+            AgentChange::Removed => "removed",
         }
+    }
 
-        status_spans.push(Span::styled(status_text, status_style));
+    fn sign(&self) -> char {
+        match self {
+            AgentChange::New => '+',
+            AgentChange::Changed => '~',
+            AgentChange::Removed => '-',
+        }
+    }
 
-        let status_line = Line::from(status_spans);
+    fn color(&self) -> Color {
+        match self {
+            AgentChange::New => Theme::ACCENT_SECONDARY,
+            AgentChange::Changed => Theme::ACCENT_WARNING,
+            AgentChange::Removed => Theme::ACCENT_ERROR,
+        }
+    }
+}
 
-        let status_paragraph = Paragraph::new(vec![status_line])
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_type(BorderType::Rounded)
-                    .border_style(Style::default().fg(Theme::BORDER_UNFOCUSED))
-                    .style(Style::default().bg(Theme::BACKGROUND)),
-            )
-            .style(Style::default().bg(Theme::BACKGROUND));
+/// What an agent's plist held when it was snapshotted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct AgentSnapshot {
+    hash: String,
+    /// Only looked up when the snapshot is saved, so removed agents can
+    /// still be named.
+    label: Option<String>,
+}
 
-        frame.render_widget(status_paragraph, area);
+impl AgentSnapshot {
+    fn read(path: &Path) -> Option<Self> {
+        let contents = fs::read(path).ok()?;
+        Some(Self {
+            hash: content_hash(&contents),
+            label: None,
+        })
     }
 
-    fn draw_exit_confirmation(&mut self, frame: &mut Frame) {
-        let area = frame.area();
+    fn read_label(path: &Path) -> Option<String> {
+        let content = fs::read_to_string(path).ok()?;
+        parse_plist_xml(&content).ok()?.label
+    }
+}
 
-        // Create a centered popup area
-        let popup_area = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Percentage(35),
-                Constraint::Length(9),
-                Constraint::Percentage(35),
-            ])
-            .split(area)[1];
+/// A 64-bit FNV-1a hash of `contents` in hex. Unlike `DefaultHasher` it's
+/// the same in every build, so stored snapshots stay comparable when lam is
+/// upgraded.
+fn content_hash(contents: &[u8]) -> String {
+    let hash = contents
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    format!("{:016x}", hash)
+}
 
-        let popup_area = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Percentage(20),
-                Constraint::Percentage(60),
-                Constraint::Percentage(20),
-            ])
-            .split(popup_area)[1];
+/// The plists in the agent directories, keyed by path. The last acknowledged
+/// one is kept in `<state dir>/snapshot.json`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct Snapshot {
+    agents: std::collections::BTreeMap<PathBuf, AgentSnapshot>,
+}
 
-        // Clear the background
-        frame.render_widget(Clear, popup_area);
+impl Snapshot {
+    /// Snapshots the `.plist` files directly in `dirs`.
+    fn take(dirs: &[&Path]) -> Self {
+        let mut snapshot = Self::default();
+        for dir in dirs {
+            let Ok(entries) = fs::read_dir(dir) else {
+                continue;
+            };
+            for path in entries.flatten().map(|entry| entry.path()) {
+                if path.extension().is_some_and(|ext| ext == "plist")
+                    && path.is_file()
+                    && let Some(agent) = AgentSnapshot::read(&path)
+                {
+                    snapshot.agents.insert(path, agent);
+                }
+            }
+        }
+        snapshot
+    }
 
-        // Create the confirmation dialog
-        let confirmation_text = vec![
-            Line::from(""),
-            Line::from(vec![Span::styled(
-                "  🚪 Quit LaunchAgent Manager?",
-                Style::default()
-                    .fg(Theme::ACCENT_WARNING)
-                    .add_modifier(Modifier::BOLD),
-            )]),
-            Line::from(""),
-            Line::from(vec![
-                Span::styled("  ", Style::default()),
-                Span::styled(
-                    "[Y]",
-                    Style::default()
-                        .fg(Theme::ACCENT_SECONDARY)
-                        .add_modifier(Modifier::BOLD),
-                ),
-                Span::styled("es  ", Style::default().fg(Theme::FOREGROUND)),
-                Span::styled(
-                    "[N]",
-                    Style::default()
-                        .fg(Theme::ACCENT_ERROR)
-                        .add_modifier(Modifier::BOLD),
-                ),
-                Span::styled("o  ", Style::default().fg(Theme::FOREGROUND)),
-                Span::styled(
-                    "[Esc]",
-                    Style::default()
-                        .fg(Theme::ACCENT_MUTED)
-                        .add_modifier(Modifier::BOLD),
-                ),
-            ]),
-            Line::from(""),
-            Line::from(vec![Span::styled(
-                "  Press any key to choose",
-                Style::default()
-                    .fg(Theme::TEXT_DIM)
-                    .add_modifier(Modifier::ITALIC),
-            )]),
-            Line::from(""),
-        ];
+    fn path() -> Result<PathBuf> {
+        Ok(state_dir()?.join("snapshot.json"))
+    }
 
-        let confirmation_dialog = Paragraph::new(confirmation_text)
-            .block(
-                Block::default()
-                    .title(Line::from(vec![Span::styled(
-                        " ⚠️  Confirm Exit ",
-                        Style::default()
-                            .fg(Theme::ACCENT_WARNING)
-                            .add_modifier(Modifier::BOLD),
-                    )]))
-                    .borders(Borders::ALL)
-                    .border_type(BorderType::Thick)
-                    .border_style(Style::default().fg(Theme::ACCENT_WARNING))
-                    .style(Style::default().bg(Theme::BACKGROUND)),
+    /// The acknowledged snapshot. The first time, the current state of `dirs`
+    /// is recorded and acknowledged, so only later changes are reported.
+    fn acknowledged(dirs: &[&Path]) -> Result<Self> {
+        let path = Self::path()?;
+        if path.exists() {
+            let content = fs::read_to_string(&path)?;
+            return serde_json::from_str(&content)
+                .map_err(|e| color_eyre::eyre::eyre!("Invalid {}: {}", path.display(), e));
+        }
+        let mut snapshot = Self::take(dirs);
+        snapshot.save()?;
+        Ok(snapshot)
+    }
+
+    /// Looks up the labels of agents that don't have one yet.
+    fn read_labels(&mut self) {
+        for (path, agent) in &mut self.agents {
+            if agent.label.is_none() {
+                agent.label = AgentSnapshot::read_label(path);
+            }
+        }
+    }
+
+    fn save(&mut self) -> Result<()> {
+        self.read_labels();
+        let path = Self::path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        write_atomically(&path, format!("{:#}\n", serde_json::json!(self)).as_bytes())?;
+        Ok(())
+    }
+
+    /// Records the current state of the plist at `path`, or that it's gone.
+    fn update(&mut self, path: &Path) {
+        match AgentSnapshot::read(path) {
+            Some(agent) => self.agents.insert(path.to_path_buf(), agent),
+            None => self.agents.remove(path),
+        };
+    }
+
+    /// The agents that were added, changed or removed since `acknowledged`.
+    fn changes_since(&self, acknowledged: &Snapshot) -> Vec<(PathBuf, AgentChange)> {
+        let mut changes: Vec<(PathBuf, AgentChange)> = self
+            .agents
+            .iter()
+            .filter_map(|(path, agent)| match acknowledged.agents.get(path) {
+                None => Some((path.clone(), AgentChange::New)),
+                Some(previous) if previous.hash != agent.hash => {
+                    Some((path.clone(), AgentChange::Changed))
+                }
+                Some(_) => None,
+            })
+            .chain(
+                acknowledged
+                    .agents
+                    .keys()
+                    .filter(|path| !self.agents.contains_key(*path))
+                    .map(|path| (path.clone(), AgentChange::Removed)),
             )
-            .style(Style::default().bg(Theme::BACKGROUND))
-            .alignment(ratatui::layout::Alignment::Left);
+            .collect();
+        changes.sort_by(|a, b| a.0.cmp(&b.0));
+        changes
+    }
+}
+
+/// Watches the agent directories, queueing filesystem events for the run
+/// loop to apply.
+#[derive(Debug)]
+struct AgentWatcher {
+    // Watching stops when this is dropped
+    _watcher: notify::RecommendedWatcher,
+    events: std::sync::mpsc::Receiver<notify::Result<notify::Event>>,
+    /// Each watched directory's canonical path, which events are reported
+    /// under, and the path it was given as.
+    dirs: Vec<(PathBuf, PathBuf)>,
+}
+
+impl AgentWatcher {
+    /// Watches those of `dirs` that exist.
+    fn new(dirs: &[&Path]) -> notify::Result<Self> {
+        use notify::Watcher;
+
+        let (sender, events) = std::sync::mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+        let mut watched = Vec::new();
+        for dir in dirs.iter().filter(|dir| dir.is_dir()) {
+            watcher.watch(dir, notify::RecursiveMode::NonRecursive)?;
+            let canonical = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+            watched.push((canonical, dir.to_path_buf()));
+        }
+        Ok(Self {
+            _watcher: watcher,
+            events,
+            dirs: watched,
+        })
+    }
+
+    /// The plists created, modified or removed since the last call, each
+    /// listed once under the directory path it was watched by. FSEvents
+    /// reports resolved paths, such as `/private/var` for `/var`.
+    fn changed_plists(&self) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        for event in self.events.try_iter().flatten() {
+            if matches!(event.kind, notify::EventKind::Access(_)) {
+                continue;
+            }
+            for path in event.paths {
+                if path.extension().is_none_or(|ext| ext != "plist") {
+                    continue;
+                }
+                let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+                    continue;
+                };
+                let Some((_, dir)) = self
+                    .dirs
+                    .iter()
+                    .find(|(canonical, dir)| parent == canonical || parent == dir)
+                else {
+                    continue;
+                };
+                let path = dir.join(name);
+                if !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+        paths
+    }
+}
 
-        frame.render_widget(confirmation_dialog, popup_area);
+/// Formats seconds since the Unix epoch as a sortable, filename-safe UTC
+/// timestamp such as `2025-08-14T09-30-00Z`.
+fn format_timestamp(secs: u64) -> String {
+    // Civil-from-days conversion from Howard Hinnant's date algorithms
+    let days = (secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    let seconds_of_day = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}-{:02}-{:02}Z",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        seconds_of_day % 3600 / 60,
+        seconds_of_day % 60
+    )
+}
+
+/// A line-based unified diff with three lines of context around each change.
+fn unified_diff(old: &str, new: &str) -> Vec<(DiffKind, String)> {
+    let diff = similar::TextDiff::from_lines(old, new);
+    let mut lines = Vec::new();
+    for hunk in diff.unified_diff().context_radius(3).iter_hunks() {
+        lines.push((DiffKind::Hunk, hunk.header().to_string()));
+        for change in hunk.iter_changes() {
+            let (kind, sign) = match change.tag() {
+                similar::ChangeTag::Equal => (DiffKind::Context, ' '),
+                similar::ChangeTag::Insert => (DiffKind::Added, '+'),
+                similar::ChangeTag::Delete => (DiffKind::Removed, '-'),
+            };
+            let text = change.value().trim_end_matches(['\r', '\n']);
+            lines.push((kind, format!("{}{}", sign, text)));
+        }
     }
+    lines
+}
 
-    fn draw_loading_screen(&mut self, frame: &mut Frame) {
-        // Clear background with theme color
-        let background = Block::default().style(Style::default().bg(Theme::BACKGROUND));
-        frame.render_widget(background, frame.area());
+fn diff_to_lines(diff: &[(DiffKind, String)]) -> Vec<Line<'_>> {
+    diff.iter()
+        .map(|(kind, text)| {
+            let style = match kind {
+                DiffKind::Hunk => Style::default().fg(Theme::ACCENT_MUTED),
+                DiffKind::Context => Style::default().fg(Theme::TEXT_DIM),
+                DiffKind::Added => Style::default().fg(Theme::ACCENT_SECONDARY),
+                DiffKind::Removed => Style::default().fg(Theme::ACCENT_ERROR),
+            };
+            Line::from(vec![Span::styled(text.as_str(), style)])
+        })
+        .collect()
+}
 
-        // Create centered loading area
-        let area = frame.area();
-        let loading_area = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Percentage(25),
-                Constraint::Length(12),
-                Constraint::Percentage(25),
-            ])
-            .split(area)[1];
+/// Entry point for `lam helper`, the privileged half of editing root-owned
+/// plists. The TUI re-invokes lam through sudo with the serialized plist on
+/// stdin rather than running the whole interface as root.
+fn run_helper(args: &[String]) -> Result<()> {
+    match args {
+        [command, path] if command == "write" => {
+            use std::io::Read;
 
-        let loading_area = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Percentage(15),
-                Constraint::Percentage(70),
-                Constraint::Percentage(15),
-            ])
-            .split(loading_area)[1];
+            let path = PathBuf::from(path);
+            validate_privileged_path(&path)?;
 
-        // Animated spinner characters
-        let spinner_chars = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
-        let spinner_index = (self.loading_step as usize) % spinner_chars.len();
-        let spinner = spinner_chars[spinner_index];
+            let mut contents = String::new();
+            std::io::stdin().read_to_string(&mut contents)?;
+            if parse_plist_xml(&contents)?.label.is_none() {
+                return Err(color_eyre::eyre::eyre!(
+                    "Refusing to write a plist without a Label"
+                ));
+            }
 
-        // Create progress bar
-        let progress_width = loading_area.width.saturating_sub(6) as f32;
-        let filled_width = (progress_width * self.loading_progress) as u16;
-        let progress_bar = "█".repeat(filled_width as usize)
-            + &"░".repeat((progress_width as u16).saturating_sub(filled_width) as usize);
+            write_root_owned(&path, contents.as_bytes())?;
 
-        let loading_content = vec![
-            Line::from(""),
-            Line::from(vec![Span::styled(
-                "🚀 Launch Agent Manager",
-                Style::default()
-                    .fg(Theme::ACCENT_PRIMARY)
-                    .add_modifier(Modifier::BOLD),
-            )]),
-            Line::from(""),
-            Line::from(vec![
-                Span::styled(
-                    format!("{} ", spinner),
-                    Style::default()
-                        .fg(Theme::ACCENT_SECONDARY)
-                        .add_modifier(Modifier::BOLD),
-                ),
-                Span::styled(
-                    &self.loading_message,
-                    Style::default().fg(Theme::FOREGROUND),
-                ),
-            ]),
-            Line::from(""),
-            Line::from(vec![Span::styled(
-                format!(
-                    "[{}] {}%",
-                    progress_bar,
-                    (self.loading_progress * 100.0) as u8
-                ),
-                Style::default().fg(Theme::ACCENT_MUTED),
-            )]),
-            Line::from(""),
-            Line::from(vec![Span::styled(
-                "Loading launch agents and checking status...",
-                Style::default()
-                    .fg(Theme::TEXT_DIM)
-                    .add_modifier(Modifier::ITALIC),
-            )]),
-        ];
+            // Daemons live in the system domain, so root reloads them here.
+            // Agents run per user, so the TUI reloads those in its own session.
+            if path.starts_with("/Library/LaunchDaemons") {
+                App::unload_agent(&path)?;
+                App::load_agent(&path)?;
+            }
+            Ok(())
+        }
+        [command, path] if command == "remove" => {
+            let path = PathBuf::from(path);
+            validate_privileged_path(&path)?;
+            fs::remove_file(&path)?;
+            Ok(())
+        }
+        _ => Err(color_eyre::eyre::eyre!(
+            "Usage: lam helper write <path> | lam helper remove <path>"
+        )),
+    }
+}
 
-        let loading_widget = Paragraph::new(loading_content)
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_type(BorderType::Rounded)
-                    .border_style(Style::default().fg(Theme::BORDER_FOCUSED))
-                    .style(Style::default().bg(Theme::BACKGROUND))
-                    .padding(ratatui::widgets::Padding::uniform(1)),
-            )
-            .alignment(ratatui::layout::Alignment::Center)
-            .style(Style::default().bg(Theme::BACKGROUND));
+/// Walks a subcommand's arguments, splitting `--flag=value` and taking a
+/// flag's value from the next argument when it isn't given inline.
+struct FlagParser<'a> {
+    args: std::slice::Iter<'a, String>,
+    usage: &'static str,
+    flag: &'a str,
+    inline_value: Option<&'a str>,
+}
 
-        frame.render_widget(loading_widget, loading_area);
+impl<'a> FlagParser<'a> {
+    fn new(args: &'a [String], usage: &'static str) -> Self {
+        Self {
+            args: args.iter(),
+            usage,
+            flag: "",
+            inline_value: None,
+        }
+    }
 
-        // Update spinner animation
-        self.loading_step = self.loading_step.wrapping_add(1);
+    /// The next flag without its inline value, or the next positional
+    /// argument.
+    fn next_arg(&mut self) -> Option<&'a str> {
+        let arg = self.args.next()?;
+        (self.flag, self.inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value)),
+            _ => (arg.as_str(), None),
+        };
+        Some(self.flag)
     }
 
-    #[allow(dead_code)]
-    fn get_current_field_name(&self) -> &str {
-        match self.current_field {
-            FormField::Label => "Label",
-            FormField::ProgramArguments => "Program Arguments",
-            FormField::Program => "Program",
-            FormField::StartInterval => "Start Interval",
-            FormField::RunAtLoad => "Run At Load",
-            FormField::KeepAlive => "Keep Alive",
-            FormField::StandardOutPath => "Standard Out Path",
-            FormField::StandardErrorPath => "Standard Error Path",
-            FormField::WorkingDirectory => "Working Directory",
-            FormField::EnvironmentVariables => "Environment Variables",
-            FormField::LimitLoadToSessionType => "Limit Load To Session Type",
-            FormField::AbandonProcessGroup => "Abandon Process Group",
-            FormField::AssociatedBundleIdentifiers => "Associated Bundle Identifiers",
-            FormField::ThrottleInterval => "Throttle Interval",
-            FormField::POSIXSpawnType => "POSIX Spawn Type",
-            FormField::EnablePressuredExit => "Enable Pressured Exit",
-            FormField::EnableTransactions => "Enable Transactions",
-            FormField::EventMonitor => "Event Monitor",
+    /// The value of the current flag.
+    fn value(&mut self) -> Result<&'a str> {
+        match self.inline_value.take() {
+            Some(value) => Ok(value),
+            None => self.args.next().map(String::as_str).ok_or_else(|| {
+                color_eyre::eyre::eyre!("{} needs a value\n{}", self.flag, self.usage)
+            }),
         }
     }
 
-    fn get_editing_field_name(&self) -> &str {
-        if let Some(editing_field) = &self.editing_field {
-            match editing_field {
-                FormField::Label => "Label",
-                FormField::ProgramArguments => "Program Arguments",
-                FormField::Program => "Program",
-                FormField::StartInterval => "Start Interval",
-                FormField::RunAtLoad => "Run At Load",
-                FormField::KeepAlive => "Keep Alive",
-                FormField::StandardOutPath => "Standard Out Path",
-                FormField::StandardErrorPath => "Standard Error Path",
-                FormField::WorkingDirectory => "Working Directory",
-                FormField::EnvironmentVariables => "Environment Variables",
-                FormField::LimitLoadToSessionType => "Limit Load To Session Type",
-                FormField::AbandonProcessGroup => "Abandon Process Group",
-                FormField::AssociatedBundleIdentifiers => "Associated Bundle Identifiers",
-                FormField::ThrottleInterval => "Throttle Interval",
-                FormField::POSIXSpawnType => "POSIX Spawn Type",
-                FormField::EnablePressuredExit => "Enable Pressured Exit",
-                FormField::EnableTransactions => "Enable Transactions",
-                FormField::EventMonitor => "Event Monitor",
-            }
+    /// The error for the current argument when the command doesn't know it.
+    fn unknown(&self) -> color_eyre::Report {
+        if matches!(self.flag, "-h" | "--help") {
+            color_eyre::eyre::eyre!("{}", self.usage)
         } else {
-            "Unknown"
+            color_eyre::eyre::eyre!("Unknown option: {}\n{}", self.flag, self.usage)
         }
     }
+}
 
-    fn set_status_message(&mut self, message: String) {
-        self.status_message = message;
-        self.status_timer = 100; // Show for ~2 seconds at 50ms update rate
+const LINT_USAGE: &str = "Usage: lam lint [--format human|json|sarif|checkstyle] [--severity warning|error] [--check-paths] <path>...";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LintFormat {
+    Human,
+    Json,
+    Sarif,
+    Checkstyle,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct LintOptions {
+    format: LintFormat,
+    /// The lowest severity that makes `lam lint` exit non-zero.
+    fail_on: Severity,
+    /// Also run the filesystem checks, which only make sense on the machine
+    /// the agents run on.
+    check_paths: bool,
+    paths: Vec<PathBuf>,
+}
+
+fn parse_lint_args(args: &[String]) -> Result<LintOptions> {
+    use color_eyre::eyre::eyre;
+
+    let mut options = LintOptions {
+        format: LintFormat::Human,
+        fail_on: Severity::Warning,
+        check_paths: false,
+        paths: Vec::new(),
+    };
+    let mut parser = FlagParser::new(args, LINT_USAGE);
+    while let Some(arg) = parser.next_arg() {
+        match arg {
+            "--format" => {
+                options.format = match parser.value()? {
+                    "human" => LintFormat::Human,
+                    "json" => LintFormat::Json,
+                    "sarif" => LintFormat::Sarif,
+                    "checkstyle" => LintFormat::Checkstyle,
+                    other => return Err(eyre!("Unknown format: {}\n{}", other, LINT_USAGE)),
+                }
+            }
+            "--severity" => {
+                options.fail_on = match parser.value()? {
+                    "warning" => Severity::Warning,
+                    "error" => Severity::Error,
+                    other => return Err(eyre!("Unknown severity: {}\n{}", other, LINT_USAGE)),
+                }
+            }
+            "--check-paths" => options.check_paths = true,
+            _ if arg.starts_with('-') => return Err(parser.unknown()),
+            _ => options.paths.push(PathBuf::from(arg)),
+        }
+    }
+    if options.paths.is_empty() {
+        return Err(eyre!("{}", LINT_USAGE));
     }
+    Ok(options)
+}
 
-    async fn handle_crossterm_events(&mut self) -> Result<()> {
-        tokio::select! {
-            event = self.event_stream.next().fuse() => {
-                if let Some(Ok(evt)) = event {
-                    match evt {
-                        Event::Key(key) if key.kind == KeyEventKind::Press => {
-                            self.on_key_event(key)?;
-                        }
-                        Event::Mouse(_) => {}
-                        Event::Resize(_, _) => {}
-                        _ => {}
+/// Adds `path` if it's a file, or the `.plist` files under it if it's a
+/// directory.
+fn collect_plist_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    if !path.is_dir() {
+        if !path.exists() {
+            return Err(color_eyre::eyre::eyre!(
+                "{}: No such file or directory",
+                path.display()
+            ));
+        }
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+    let mut entries: Vec<PathBuf> = fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<_>>()?;
+    entries.sort();
+    for entry in entries {
+        if entry.is_dir() {
+            collect_plist_files(&entry, files)?;
+        } else if entry.extension().is_some_and(|ext| ext == "plist") {
+            files.push(entry);
+        }
+    }
+    Ok(())
+}
+
+/// One file's diagnostics, each with the line of the key it's about.
+#[derive(Debug)]
+struct LintedFile {
+    path: String,
+    diagnostics: Vec<(Diagnostic, Option<usize>)>,
+}
+
+/// The 1-based line of the first `<key>` element for `key`.
+fn key_line(content: &str, key: &str) -> Option<usize> {
+    let element = format!("<key>{}</key>", App::xml_escape(key));
+    content
+        .lines()
+        .position(|line| line.contains(&element))
+        .map(|i| i + 1)
+}
+
+fn lint_file(path: &Path, check_paths: bool) -> LintedFile {
+    let filename = path.file_name().and_then(|name| name.to_str());
+    let content = fs::read_to_string(path);
+    let parsed = match &content {
+        Ok(content) => parse_plist_xml(content),
+        Err(e) => Err(color_eyre::eyre::eyre!("Failed to read: {}", e)),
+    };
+    let diagnostics = match parsed {
+        Ok(plist) => {
+            let mut diagnostics = lint_plist(&plist, filename);
+            if check_paths {
+                diagnostics.extend(check_plist_paths(&plist));
+            }
+            diagnostics
+        }
+        Err(e) => vec![Diagnostic::new("invalid-plist", None, e.to_string())],
+    };
+    LintedFile {
+        path: path.display().to_string(),
+        diagnostics: diagnostics
+            .into_iter()
+            .map(|diagnostic| {
+                let line = match (&content, &diagnostic.key) {
+                    (Ok(content), Some(key)) => key_line(content, key),
+                    _ => None,
+                };
+                (diagnostic, line)
+            })
+            .collect(),
+    }
+}
+
+fn format_lint_report(files: &[LintedFile], format: LintFormat) -> String {
+    use serde_json::json;
+
+    let all = || {
+        files.iter().flat_map(|file| {
+            file.diagnostics
+                .iter()
+                .map(move |(diagnostic, line)| (file.path.as_str(), diagnostic, *line))
+        })
+    };
+    match format {
+        LintFormat::Human => {
+            let mut out = String::new();
+            for (path, diagnostic, line) in all() {
+                let location = match line {
+                    Some(line) => format!("{}:{}", path, line),
+                    None => path.to_string(),
+                };
+                out.push_str(&format!(
+                    "{}: {} [{}] {}\n",
+                    location,
+                    diagnostic.severity.name(),
+                    diagnostic.rule,
+                    diagnostic.message
+                ));
+            }
+            let errors = all()
+                .filter(|(_, d, _)| d.severity == Severity::Error)
+                .count();
+            let warnings = all().count() - errors;
+            let plural = |count: usize, noun: &str| {
+                format!("{} {}{}", count, noun, if count == 1 { "" } else { "s" })
+            };
+            if errors + warnings == 0 {
+                out.push_str(&format!(
+                    "✓ {} checked, no problems\n",
+                    plural(files.len(), "file")
+                ));
+            } else {
+                out.push_str(&format!(
+                    "✗ {} and {} in {}\n",
+                    plural(errors, "error"),
+                    plural(warnings, "warning"),
+                    plural(files.len(), "file")
+                ));
+            }
+            out
+        }
+        LintFormat::Json => {
+            let results: Vec<serde_json::Value> = all()
+                .map(|(path, diagnostic, line)| {
+                    json!({
+                        "file": path,
+                        "line": line,
+                        "rule": diagnostic.rule,
+                        "severity": diagnostic.severity.name(),
+                        "key": diagnostic.key,
+                        "message": diagnostic.message,
+                        "fix": diagnostic.fix.as_ref().map(QuickFix::description),
+                    })
+                })
+                .collect();
+            format!("{:#}\n", serde_json::Value::from(results))
+        }
+        LintFormat::Sarif => {
+            let rules: Vec<serde_json::Value> = LINT_RULES
+                .iter()
+                .map(|rule| {
+                    json!({
+                        "id": rule.id,
+                        "defaultConfiguration": { "level": rule.severity.name() },
+                    })
+                })
+                .collect();
+            let results: Vec<serde_json::Value> = all()
+                .map(|(path, diagnostic, line)| {
+                    let mut location = json!({ "artifactLocation": { "uri": path } });
+                    if let Some(line) = line {
+                        location["region"] = json!({ "startLine": line });
                     }
+                    json!({
+                        "ruleId": diagnostic.rule,
+                        "level": diagnostic.severity.name(),
+                        "message": { "text": diagnostic.message },
+                        "locations": [{ "physicalLocation": location }],
+                    })
+                })
+                .collect();
+            let sarif = json!({
+                "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+                "version": "2.1.0",
+                "runs": [{
+                    "tool": {
+                        "driver": {
+                            "name": "lam",
+                            "version": env!("CARGO_PKG_VERSION"),
+                            "rules": rules,
+                        }
+                    },
+                    "results": results,
+                }],
+            });
+            format!("{:#}\n", sarif)
+        }
+        LintFormat::Checkstyle => {
+            let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+            out.push_str("<checkstyle version=\"4.3\">\n");
+            for file in files {
+                out.push_str(&format!(
+                    "  <file name=\"{}\">\n",
+                    App::xml_escape_attribute(&file.path)
+                ));
+                for (diagnostic, line) in &file.diagnostics {
+                    out.push_str(&format!(
+                        "    <error line=\"{}\" severity=\"{}\" message=\"{}\" source=\"lam.{}\"/>\n",
+                        line.unwrap_or(1),
+                        diagnostic.severity.name(),
+                        App::xml_escape_attribute(&diagnostic.message),
+                        diagnostic.rule
+                    ));
                 }
+                out.push_str("  </file>\n");
             }
-            _ = tokio::time::sleep(tokio::time::Duration::from_millis(50)) => {}
+            out.push_str("</checkstyle>\n");
+            out
         }
-        Ok(())
     }
+}
 
-    fn on_key_event(&mut self, key: KeyEvent) -> Result<()> {
-        if self.showing_exit_confirmation {
-            self.handle_exit_confirmation_keys(key)?;
-        } else if self.editing {
-            self.handle_edit_keys(key)?;
-        } else {
-            match (key.modifiers, key.code) {
-                (_, KeyCode::Esc | KeyCode::Char('q')) => {
-                    self.showing_exit_confirmation = true;
+/// Entry point for `lam lint`. Returns whether any diagnostic reached the
+/// `--severity` threshold.
+fn run_lint(args: &[String]) -> Result<bool> {
+    let options = parse_lint_args(args)?;
+    let mut paths = Vec::new();
+    for path in &options.paths {
+        collect_plist_files(path, &mut paths)?;
+    }
+    let files: Vec<LintedFile> = paths
+        .iter()
+        .map(|path| lint_file(path, options.check_paths))
+        .collect();
+    print!("{}", format_lint_report(&files, options.format));
+    Ok(files
+        .iter()
+        .flat_map(|file| &file.diagnostics)
+        .any(|(diagnostic, _)| diagnostic.severity >= options.fail_on))
+}
+
+const FMT_USAGE: &str = "Usage: lam fmt [--check] [--indent tab|<spaces>] [--key-order launchd|alphabetical|preserve] [--blank-lines|--no-blank-lines] <path>...";
+
+#[derive(Debug, Clone, PartialEq)]
+struct FmtOptions {
+    /// Report unformatted files instead of rewriting them.
+    check: bool,
+    style: FormatStyle,
+    paths: Vec<PathBuf>,
+}
+
+/// Parses `lam fmt` arguments. Flags override `style`, which comes from the
+/// config file.
+fn parse_fmt_args(args: &[String], style: FormatStyle) -> Result<FmtOptions> {
+    use color_eyre::eyre::eyre;
+
+    let mut options = FmtOptions {
+        check: false,
+        style,
+        paths: Vec::new(),
+    };
+    let mut parser = FlagParser::new(args, FMT_USAGE);
+    while let Some(arg) = parser.next_arg() {
+        match arg {
+            "--check" => options.check = true,
+            "--indent" => {
+                options.style.indent = parser.value()?.parse().map_err(|e| eyre!("{}", e))?
+            }
+            "--key-order" => {
+                options.style.key_order = parser.value()?.parse().map_err(|e| eyre!("{}", e))?
+            }
+            "--blank-lines" => options.style.blank_lines = true,
+            "--no-blank-lines" => options.style.blank_lines = false,
+            _ if arg.starts_with('-') => return Err(parser.unknown()),
+            _ => options.paths.push(PathBuf::from(arg)),
+        }
+    }
+    if options.paths.is_empty() {
+        return Err(eyre!("{}", FMT_USAGE));
+    }
+    Ok(options)
+}
+
+/// Entry point for `lam fmt`. Returns whether it failed: with `--check`, that
+/// some file isn't formatted, and otherwise that some file couldn't be read.
+fn run_fmt(args: &[String]) -> Result<bool> {
+    let options = parse_fmt_args(args, Config::load()?.format)?;
+    let mut paths = Vec::new();
+    for path in &options.paths {
+        collect_plist_files(path, &mut paths)?;
+    }
+
+    let mut failed = false;
+    let mut changed = 0;
+    for path in &paths {
+        let formatted = fs::read_to_string(path)
+            .map_err(color_eyre::Report::from)
+            .and_then(|content| {
+                let value = parse_plist_value(&content)?;
+                Ok((format_plist(&value, &options.style), content))
+            });
+        match formatted {
+            Ok((formatted, content)) if formatted != content => {
+                changed += 1;
+                if options.check {
+                    println!("{}", path.display());
+                    failed = true;
+                } else {
+                    write_atomically(path, formatted.as_bytes())?;
+                    println!("Formatted {}", path.display());
                 }
-                (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => {
-                    self.showing_exit_confirmation = true;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("{}: {}", path.display(), e);
+                failed = true;
+            }
+        }
+    }
+    if options.check && changed > 0 {
+        eprintln!("✗ {} of {} files need formatting", changed, paths.len());
+    }
+    Ok(failed)
+}
+
+/// One difference between two plists, at a path such as
+/// `EnvironmentVariables.PATH` or `ProgramArguments[1]`.
+#[derive(Debug, Clone, PartialEq)]
+struct PlistChange {
+    path: String,
+    kind: ChangeKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ChangeKind {
+    Added(PlistValue),
+    Removed(PlistValue),
+    Changed(PlistValue, PlistValue),
+}
+
+impl ChangeKind {
+    fn name(&self) -> &'static str {
+        match self {
+            ChangeKind::Added(_) => "added",
+            ChangeKind::Removed(_) => "removed",
+            ChangeKind::Changed(..) => "changed",
+        }
+    }
+
+    fn sign(&self) -> char {
+        match self {
+            ChangeKind::Added(_) => '+',
+            ChangeKind::Removed(_) => '-',
+            ChangeKind::Changed(..) => '~',
+        }
+    }
+}
+
+impl PlistChange {
+    /// `path: value`, or `path: old → new` for a changed value.
+    fn describe(&self) -> String {
+        let path = if self.path.is_empty() {
+            "<root>"
+        } else {
+            &self.path
+        };
+        match &self.kind {
+            ChangeKind::Added(value) | ChangeKind::Removed(value) => {
+                format!("{}: {}", path, plist_value_json(value))
+            }
+            ChangeKind::Changed(old, new) => format!(
+                "{}: {} → {}",
+                path,
+                plist_value_json(old),
+                plist_value_json(new)
+            ),
+        }
+    }
+}
+
+/// Compares two plist values, ignoring key order and layout. Dictionaries
+/// are matched by key and array items by content, so an inserted argument
+/// shows up as a single addition.
+fn diff_plist_values(
+    path: &str,
+    old: &PlistValue,
+    new: &PlistValue,
+    changes: &mut Vec<PlistChange>,
+) {
+    match (old, new) {
+        (PlistValue::Dict(old_entries), PlistValue::Dict(new_entries)) => {
+            let child = |key: &str| {
+                if path.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{}.{}", path, key)
                 }
-                (_, KeyCode::Tab) => {
-                    self.focus = match self.focus {
-                        Focus::Search => Focus::Sidebar,
-                        Focus::Sidebar => Focus::Form,
-                        Focus::Form => Focus::Logs,
-                        Focus::Logs => Focus::Search,
-                    };
-                    if self.focus == Focus::Logs {
-                        self.refresh_log_content();
+            };
+            for (key, old_value) in old_entries {
+                match new_entries.iter().find(|(other, _)| other == key) {
+                    Some((_, new_value)) => {
+                        diff_plist_values(&child(key), old_value, new_value, changes)
                     }
+                    None => changes.push(PlistChange {
+                        path: child(key),
+                        kind: ChangeKind::Removed(old_value.clone()),
+                    }),
                 }
-                (KeyModifiers::CONTROL, KeyCode::Char('s') | KeyCode::Char('S')) => {
-                    self.save_plist()?;
-                }
-                (_, KeyCode::Char('l')) => {
-                    self.focus = Focus::Logs;
-                    self.refresh_log_content();
+            }
+            for (key, new_value) in new_entries {
+                if !old_entries.iter().any(|(other, _)| other == key) {
+                    changes.push(PlistChange {
+                        path: child(key),
+                        kind: ChangeKind::Added(new_value.clone()),
+                    });
                 }
-                (_, KeyCode::Char('/')) => {
-                    self.focus = Focus::Search;
+            }
+        }
+        (PlistValue::Array(old_items), PlistValue::Array(new_items)) => {
+            let item = |index: usize| format!("{}[{}]", path, index);
+            let render = |items: &[PlistValue]| -> Vec<String> {
+                items
+                    .iter()
+                    .map(|item| plist_value_json(item).to_string())
+                    .collect()
+            };
+            let (old_keys, new_keys) = (render(old_items), render(new_items));
+            for op in similar::capture_diff_slices(similar::Algorithm::Myers, &old_keys, &new_keys)
+            {
+                let (tag, old_range, new_range) = op.as_tag_tuple();
+                if tag == similar::DiffTag::Equal {
+                    continue;
                 }
-                (_, KeyCode::Char('1')) => {
-                    self.switch_to_tab(TabLocation::User);
+                // Replaced items are compared in pairs, the rest were added
+                // or removed
+                let paired = old_range.len().min(new_range.len());
+                for i in 0..paired {
+                    diff_plist_values(
+                        &item(new_range.start + i),
+                        &old_items[old_range.start + i],
+                        &new_items[new_range.start + i],
+                        changes,
+                    );
                 }
-                (_, KeyCode::Char('2')) => {
-                    self.switch_to_tab(TabLocation::Global);
+                for i in old_range.skip(paired) {
+                    changes.push(PlistChange {
+                        path: item(i),
+                        kind: ChangeKind::Removed(old_items[i].clone()),
+                    });
                 }
-                (_, KeyCode::Char('3')) => {
-                    self.switch_to_tab(TabLocation::Apple);
+                for i in new_range.skip(paired) {
+                    changes.push(PlistChange {
+                        path: item(i),
+                        kind: ChangeKind::Added(new_items[i].clone()),
+                    });
                 }
-                _ => match self.focus {
-                    Focus::Search => self.handle_search_keys(key)?,
-                    Focus::Sidebar => self.handle_sidebar_keys(key)?,
-                    Focus::Form => self.handle_form_keys(key)?,
-                    Focus::Logs => self.handle_log_keys(key)?,
-                },
             }
         }
-        Ok(())
+        _ if old != new => changes.push(PlistChange {
+            path: path.to_string(),
+            kind: ChangeKind::Changed(old.clone(), new.clone()),
+        }),
+        _ => {}
     }
+}
 
-    fn handle_exit_confirmation_keys(&mut self, key: KeyEvent) -> Result<()> {
-        match key.code {
-            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Esc => {
-                self.quit();
-            }
-            KeyCode::Char('n') | KeyCode::Char('N') => {
-                self.showing_exit_confirmation = false;
-            }
-            _ => {}
+/// `value` as JSON. Dates and data become their text.
+fn plist_value_json(value: &PlistValue) -> serde_json::Value {
+    use serde_json::Value;
+
+    match value {
+        PlistValue::String(s) | PlistValue::Date(s) | PlistValue::Data(s) => {
+            Value::from(s.as_str())
         }
-        Ok(())
+        PlistValue::Integer(n) => Value::from(*n),
+        PlistValue::Real(n) => Value::from(*n),
+        PlistValue::Boolean(b) => Value::from(*b),
+        PlistValue::Array(items) => Value::Array(items.iter().map(plist_value_json).collect()),
+        PlistValue::Dict(entries) => Value::Object(
+            entries
+                .iter()
+                .map(|(key, value)| (key.clone(), plist_value_json(value)))
+                .collect(),
+        ),
     }
+}
 
-    fn handle_search_keys(&mut self, key: KeyEvent) -> Result<()> {
-        match key.code {
-            KeyCode::Char(c) => {
-                self.filter_text.push(c);
-                // Reset selection when filter changes
-                self.list_state
-                    .select(if self.get_filtered_agents().is_empty() {
-                        None
-                    } else {
-                        Some(0)
-                    });
-            }
-            KeyCode::Backspace => {
-                self.filter_text.pop();
-                // Reset selection when filter changes
-                self.list_state
-                    .select(if self.get_filtered_agents().is_empty() {
-                        None
-                    } else {
-                        Some(0)
-                    });
-            }
-            KeyCode::Enter => {
-                self.focus = Focus::Sidebar;
+/// Reads and parses the plist at `path` for comparing.
+fn read_plist_value(path: &Path) -> Result<PlistValue> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| color_eyre::eyre::eyre!("{}: {}", path.display(), e))?;
+    parse_plist_value(&content).map_err(|e| color_eyre::eyre::eyre!("{}: {}", path.display(), e))
+}
+
+const DIFF_USAGE: &str = "Usage: lam diff [--format human|json] <old> <new>";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DiffFormat {
+    Human,
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct DiffOptions {
+    format: DiffFormat,
+    old: PathBuf,
+    new: PathBuf,
+}
+
+fn parse_diff_args(args: &[String]) -> Result<DiffOptions> {
+    use color_eyre::eyre::eyre;
+
+    let mut format = DiffFormat::Human;
+    let mut paths = Vec::new();
+    let mut parser = FlagParser::new(args, DIFF_USAGE);
+    while let Some(arg) = parser.next_arg() {
+        match arg {
+            "--format" => {
+                format = match parser.value()? {
+                    "human" => DiffFormat::Human,
+                    "json" => DiffFormat::Json,
+                    other => return Err(eyre!("Unknown format: {}\n{}", other, DIFF_USAGE)),
+                }
             }
-            _ => {}
+            _ if arg.starts_with('-') => return Err(parser.unknown()),
+            _ => paths.push(PathBuf::from(arg)),
         }
-        Ok(())
     }
+    match <[PathBuf; 2]>::try_from(paths) {
+        Ok([old, new]) => Ok(DiffOptions { format, old, new }),
+        Err(_) => Err(eyre!("{}", DIFF_USAGE)),
+    }
+}
 
-    fn handle_sidebar_keys(&mut self, key: KeyEvent) -> Result<()> {
-        let filtered_count = self.get_filtered_agents().len();
-        if filtered_count == 0 {
-            return Ok(());
+fn format_plist_changes(
+    old_name: &str,
+    new_name: &str,
+    changes: &[PlistChange],
+    format: DiffFormat,
+) -> String {
+    use serde_json::json;
+
+    match format {
+        DiffFormat::Human if changes.is_empty() => {
+            format!("✓ No differences between {} and {}\n", old_name, new_name)
         }
-
-        match key.code {
-            KeyCode::Char('j') | KeyCode::Down => {
-                let i = match self.list_state.selected() {
-                    Some(i) => {
-                        if i >= filtered_count - 1 {
-                            0
-                        } else {
-                            i + 1
-                        }
-                    }
-                    _ => 0,
-                };
-                self.list_state.select(Some(i));
+        DiffFormat::Human => {
+            let mut out = format!("--- {}\n+++ {}\n", old_name, new_name);
+            for change in changes {
+                out.push_str(&format!("{} {}\n", change.kind.sign(), change.describe()));
             }
-            KeyCode::Char('k') | KeyCode::Up => {
-                let i = match self.list_state.selected() {
-                    Some(i) => {
-                        if i == 0 {
-                            filtered_count - 1
-                        } else {
-                            i - 1
+            let count = |name: &str| {
+                changes
+                    .iter()
+                    .filter(|change| change.kind.name() == name)
+                    .count()
+            };
+            out.push_str(&format!(
+                "{} added, {} removed, {} changed\n",
+                count("added"),
+                count("removed"),
+                count("changed")
+            ));
+            out
+        }
+        DiffFormat::Json => {
+            let changes: Vec<serde_json::Value> = changes
+                .iter()
+                .map(|change| {
+                    let mut object = json!({
+                        "path": change.path,
+                        "change": change.kind.name(),
+                    });
+                    match &change.kind {
+                        ChangeKind::Added(value) => object["new"] = plist_value_json(value),
+                        ChangeKind::Removed(value) => object["old"] = plist_value_json(value),
+                        ChangeKind::Changed(old, new) => {
+                            object["old"] = plist_value_json(old);
+                            object["new"] = plist_value_json(new);
                         }
                     }
-                    _ => 0,
-                };
-                self.list_state.select(Some(i));
-            }
-            KeyCode::Char('g') => {
-                self.list_state.select(Some(0));
-            }
-            KeyCode::Char('G') => {
-                self.list_state.select(Some(filtered_count - 1));
+                    object
+                })
+                .collect();
+            format!(
+                "{:#}\n",
+                json!({ "old": old_name, "new": new_name, "changes": changes })
+            )
+        }
+    }
+}
+
+/// Entry point for `lam diff`. Returns whether the plists differ.
+fn run_diff(args: &[String]) -> Result<bool> {
+    let DiffOptions { format, old, new } = parse_diff_args(args)?;
+    let mut changes = Vec::new();
+    diff_plist_values(
+        "",
+        &read_plist_value(&old)?,
+        &read_plist_value(&new)?,
+        &mut changes,
+    );
+    print!(
+        "{}",
+        format_plist_changes(
+            &old.display().to_string(),
+            &new.display().to_string(),
+            &changes,
+            format
+        )
+    );
+    Ok(!changes.is_empty())
+}
+
+const AUDIT_USAGE: &str = "Usage: lam audit [--json] [path...]";
+
+/// Entry point for `lam audit`. Audits the User, Global and Apple agent
+/// directories unless paths are given, and returns whether anything was
+/// flagged.
+fn run_audit(args: &[String]) -> Result<bool> {
+    use color_eyre::eyre::eyre;
+
+    let mut json = false;
+    let mut paths = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json = true,
+            "-h" | "--help" => return Err(eyre!("{}", AUDIT_USAGE)),
+            _ if arg.starts_with('-') => {
+                return Err(eyre!("Unknown option: {}\n{}", arg, AUDIT_USAGE));
             }
-            KeyCode::Enter => {
-                self.load_selected_plist()?;
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    let trusted_vendors = Config::load()?.audit.trusted_vendors;
+    let mut agents = Vec::new();
+    if paths.is_empty() {
+        agents = audit_agent_directories(&trusted_vendors)?;
+    } else {
+        let mut files = Vec::new();
+        for path in &paths {
+            collect_plist_files(path, &mut files)?;
+        }
+        for file in &files {
+            agents.push(audit_file(file, None, &trusted_vendors));
+        }
+    }
+
+    if json {
+        print!("{}", format_audit_json(&agents));
+    } else {
+        let flagged = flagged_agents(&agents);
+        for agent in &flagged {
+            let risk = agent.risk().unwrap_or(Risk::Low);
+            println!(
+                "{} {:<6} {}{}",
+                risk.icon(),
+                risk.name(),
+                agent.path.display(),
+                agent
+                    .label
+                    .as_ref()
+                    .map(|label| format!(" ({})", label))
+                    .unwrap_or_default()
+            );
+            for finding in &agent.findings {
+                println!("    [{}] {}", finding.rule, finding.message);
             }
-            _ => {}
         }
+        let count = |risk: Risk| {
+            flagged
+                .iter()
+                .flat_map(|agent| &agent.findings)
+                .filter(|finding| finding.risk == risk)
+                .count()
+        };
+        println!(
+            "Audited {} agents: {} flagged ({} high, {} medium, {} low)",
+            agents.len(),
+            flagged.len(),
+            count(Risk::High),
+            count(Risk::Medium),
+            count(Risk::Low)
+        );
+    }
+    Ok(agents.iter().any(|agent| !agent.findings.is_empty()))
+}
+
+const CHANGES_USAGE: &str = "Usage: lam changes [--json] [--ack]";
+
+/// Entry point for `lam changes`. Lists the agents added, changed or removed
+/// since the last acknowledged snapshot, and returns whether there were any
+/// that weren't acknowledged with `--ack`.
+fn run_changes(args: &[String]) -> Result<bool> {
+    use color_eyre::eyre::eyre;
+    use serde_json::json;
+
+    let mut json = false;
+    let mut acknowledge = false;
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--ack" => acknowledge = true,
+            "-h" | "--help" => return Err(eyre!("{}", CHANGES_USAGE)),
+            _ => return Err(eyre!("Unknown argument: {}\n{}", arg, CHANGES_USAGE)),
+        }
+    }
+
+    let dirs = [TabLocation::User, TabLocation::Global, TabLocation::Apple]
+        .iter()
+        .map(TabLocation::get_directory)
+        .collect::<Result<Vec<PathBuf>>>()?;
+    let dirs: Vec<&Path> = dirs.iter().map(PathBuf::as_path).collect();
+    let acknowledged = Snapshot::acknowledged(&dirs)?;
+    let mut current = Snapshot::take(&dirs);
+    let changes = current.changes_since(&acknowledged);
+    // Removed agents keep the label they were acknowledged with
+    let label = |path: &PathBuf| {
+        AgentSnapshot::read_label(path).or_else(|| acknowledged.agents.get(path)?.label.clone())
+    };
+
+    if json {
+        let changes: Vec<serde_json::Value> = changes
+            .iter()
+            .map(|(path, change)| {
+                json!({
+                    "path": path.display().to_string(),
+                    "change": change.name(),
+                    "label": label(path),
+                })
+            })
+            .collect();
+        println!("{:#}", json!({ "changes": changes }));
+    } else if changes.is_empty() {
+        println!("✓ No changes since the last acknowledged snapshot");
+    } else {
+        for (path, change) in &changes {
+            println!(
+                "{} {}{}",
+                change.sign(),
+                path.display(),
+                label(path)
+                    .map(|label| format!(" ({})", label))
+                    .unwrap_or_default()
+            );
+        }
+        println!(
+            "{} changes since the last acknowledged snapshot",
+            changes.len()
+        );
+    }
+
+    if acknowledge {
+        current.save()?;
+        if !json {
+            println!("✓ Acknowledged, the current agents are the new snapshot");
+        }
+        return Ok(false);
+    }
+    Ok(!changes.is_empty())
+}
+
+/// Audits the plists in every tab's directory.
+fn audit_agent_directories(trusted_vendors: &[String]) -> Result<Vec<AuditedAgent>> {
+    let mut agents = Vec::new();
+    for location in [TabLocation::User, TabLocation::Global, TabLocation::Apple] {
+        let dir = location.get_directory()?;
+        if !dir.is_dir() {
+            continue;
+        }
+        let mut files = Vec::new();
+        collect_plist_files(&dir, &mut files)?;
+        for file in &files {
+            agents.push(audit_file(file, Some(location), trusted_vendors));
+        }
+    }
+    Ok(agents)
+}
+
+/// Only plain `.plist` files directly inside one of the
+/// [`PRIVILEGED_DIRECTORIES`] may be touched by the helper.
+fn validate_privileged_path(path: &Path) -> Result<()> {
+    let in_allowed_directory = path.parent().is_some_and(|parent| {
+        PRIVILEGED_DIRECTORIES
+            .iter()
+            .any(|allowed| parent == Path::new(allowed))
+    });
+    if !in_allowed_directory || path.extension().is_none_or(|ext| ext != "plist") {
+        return Err(color_eyre::eyre::eyre!(
+            "{} is not a plist in {}",
+            path.display(),
+            PRIVILEGED_DIRECTORIES.join(" or ")
+        ));
+    }
+    if path.is_symlink() {
+        return Err(color_eyre::eyre::eyre!(
+            "Refusing to write through symlink {}",
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Writes `contents` atomically with owner root:wheel and mode 644, which
+/// launchd requires for anything outside a user's home.
+#[cfg(unix)]
+fn write_root_owned(path: &Path, contents: &[u8]) -> Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::PermissionsExt;
+
+    write_atomically_with(path, |file| {
+        file.write_all(contents)?;
+        file.set_permissions(fs::Permissions::from_mode(0o644))?;
+        std::os::unix::fs::fchown(&*file, Some(0), Some(0))
+    })?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn write_root_owned(_path: &Path, _contents: &[u8]) -> Result<()> {
+    Err(color_eyre::eyre::eyre!(
+        "Privileged writes are only supported on macOS"
+    ))
+}
+
+/// Replaces `path` with `contents` without ever leaving a truncated file behind.
+fn write_atomically(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    write_atomically_with(path, |file| file.write_all(contents))
+}
+
+/// Writes a temp file in the same directory as `path`, fsyncs it and renames it
+/// over the target, so a crash or full disk mid-write leaves the original
+/// intact. The original's mode, owner and extended attributes are copied onto
+/// the temp file before `write` fills it in. Symlinks are followed, so a plist
+/// linked in from a dotfiles repo stays a link.
+fn write_atomically_with(
+    path: &Path,
+    write: impl FnOnce(&mut fs::File) -> std::io::Result<()>,
+) -> std::io::Result<()> {
+    let target = if path.is_symlink() {
+        fs::canonicalize(path)?
+    } else {
+        path.to_path_buf()
+    };
+    let filename = target
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp_path = target.with_file_name(format!(".{}.lam-tmp-{}", filename, std::process::id()));
+
+    let result = (|| {
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)?;
+        if target.exists() {
+            copy_file_metadata(&target, &file)?;
+        }
+        write(&mut file)?;
+        file.sync_all()?;
+        fs::rename(&temp_path, &target)?;
+        sync_parent_directory(&target);
         Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
     }
+    result
+}
 
-    fn handle_form_keys(&mut self, key: KeyEvent) -> Result<()> {
-        match key.code {
-            KeyCode::Char('j') | KeyCode::Down => {
-                self.current_field = match self.current_field {
-                    FormField::Label => FormField::Program,
-                    FormField::Program => FormField::ProgramArguments,
-                    FormField::ProgramArguments => FormField::StartInterval,
-                    FormField::StartInterval => FormField::ThrottleInterval,
-                    FormField::ThrottleInterval => FormField::RunAtLoad,
-                    FormField::RunAtLoad => FormField::KeepAlive,
-                    FormField::KeepAlive => FormField::AbandonProcessGroup,
-                    FormField::AbandonProcessGroup => FormField::StandardOutPath,
-                    FormField::StandardOutPath => FormField::StandardErrorPath,
-                    FormField::StandardErrorPath => FormField::WorkingDirectory,
-                    FormField::WorkingDirectory => FormField::POSIXSpawnType,
-                    FormField::POSIXSpawnType => FormField::EnablePressuredExit,
-                    FormField::EnablePressuredExit => FormField::EnableTransactions,
-                    FormField::EnableTransactions => FormField::EventMonitor,
-                    FormField::EventMonitor => FormField::LimitLoadToSessionType,
-                    FormField::LimitLoadToSessionType => FormField::AssociatedBundleIdentifiers,
-                    FormField::AssociatedBundleIdentifiers => FormField::EnvironmentVariables,
-                    FormField::EnvironmentVariables => FormField::Label,
-                };
-                self.auto_scroll_to_current_field();
-            }
-            KeyCode::Char('k') | KeyCode::Up => {
-                self.current_field = match self.current_field {
-                    FormField::Label => FormField::EnvironmentVariables,
-                    FormField::Program => FormField::Label,
-                    FormField::ProgramArguments => FormField::Program,
-                    FormField::StartInterval => FormField::ProgramArguments,
-                    FormField::ThrottleInterval => FormField::StartInterval,
-                    FormField::RunAtLoad => FormField::ThrottleInterval,
-                    FormField::KeepAlive => FormField::RunAtLoad,
-                    FormField::AbandonProcessGroup => FormField::KeepAlive,
-                    FormField::StandardOutPath => FormField::AbandonProcessGroup,
-                    FormField::StandardErrorPath => FormField::StandardOutPath,
-                    FormField::WorkingDirectory => FormField::StandardErrorPath,
-                    FormField::POSIXSpawnType => FormField::WorkingDirectory,
-                    FormField::EnablePressuredExit => FormField::POSIXSpawnType,
-                    FormField::EnableTransactions => FormField::EnablePressuredExit,
-                    FormField::EventMonitor => FormField::EnableTransactions,
-                    FormField::LimitLoadToSessionType => FormField::EventMonitor,
-                    FormField::AssociatedBundleIdentifiers => FormField::LimitLoadToSessionType,
-                    FormField::EnvironmentVariables => FormField::AssociatedBundleIdentifiers,
-                };
-                self.auto_scroll_to_current_field();
-            }
-            KeyCode::Enter => {
-                self.start_editing()?;
-            }
-            KeyCode::PageUp => {
-                self.form_scroll_offset = self.form_scroll_offset.saturating_sub(5);
-            }
-            KeyCode::PageDown => {
-                self.form_scroll_offset = self.form_scroll_offset.saturating_add(5);
+#[cfg(unix)]
+fn copy_file_metadata(original: &Path, file: &fs::File) -> std::io::Result<()> {
+    use std::io::ErrorKind;
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+    use xattr::FileExt;
+
+    let metadata = fs::metadata(original)?;
+    file.set_permissions(fs::Permissions::from_mode(metadata.mode() & 0o7777))?;
+
+    // Only root can give a file away. For files we own this is a no-op, and
+    // anything else we can write but not chown keeps our ownership.
+    if let Err(e) = std::os::unix::fs::fchown(file, Some(metadata.uid()), Some(metadata.gid()))
+        && e.kind() != ErrorKind::PermissionDenied
+    {
+        return Err(e);
+    }
+
+    if xattr::SUPPORTED_PLATFORM {
+        for name in xattr::list(original)? {
+            let Some(value) = xattr::get(original, &name)? else {
+                continue;
+            };
+            // Some attributes (e.g. security.*) can only be set by the system
+            if let Err(e) = file.set_xattr(&name, &value)
+                && !matches!(
+                    e.kind(),
+                    ErrorKind::PermissionDenied | ErrorKind::Unsupported
+                )
+            {
+                return Err(e);
             }
-            _ => {}
         }
-        Ok(())
     }
+    Ok(())
+}
 
-    fn auto_scroll_to_current_field(&mut self) {
-        // Calculate approximate line position of current field in the form
-        let field_position = match self.current_field {
-            FormField::Label => 0,
-            FormField::Program => 3,
-            FormField::ProgramArguments => 6,
-            FormField::StartInterval => 9,
-            FormField::ThrottleInterval => 12,
-            FormField::RunAtLoad => 15,
-            FormField::KeepAlive => 18,
-            FormField::AbandonProcessGroup => 21,
-            FormField::StandardOutPath => 24,
-            FormField::StandardErrorPath => 27,
-            FormField::WorkingDirectory => 30,
-            FormField::POSIXSpawnType => 33,
-            FormField::EnablePressuredExit => 36,
-            FormField::EnableTransactions => 39,
-            FormField::EventMonitor => 42,
-            FormField::LimitLoadToSessionType => 45,
-            FormField::AssociatedBundleIdentifiers => 50,
-            FormField::EnvironmentVariables => 55,
-        };
+#[cfg(not(unix))]
+fn copy_file_metadata(original: &Path, file: &fs::File) -> std::io::Result<()> {
+    file.set_permissions(fs::metadata(original)?.permissions())
+}
 
-        // Ensure the field is visible with some padding
-        const VIEWPORT_HEIGHT: u16 = 20; // Approximate form panel height
-        const PADDING: u16 = 3;
+/// Makes the rename itself durable. Best effort, as not every filesystem lets
+/// directories be fsynced.
+#[cfg(unix)]
+fn sync_parent_directory(path: &Path) {
+    if let Some(parent) = path.parent()
+        && let Ok(directory) = fs::File::open(parent)
+    {
+        let _ = directory.sync_all();
+    }
+}
 
-        if field_position < self.form_scroll_offset + PADDING {
-            // Field is above visible area, scroll up
-            self.form_scroll_offset = field_position.saturating_sub(PADDING);
-        } else if field_position > self.form_scroll_offset + VIEWPORT_HEIGHT - PADDING {
-            // Field is below visible area, scroll down
-            self.form_scroll_offset = field_position.saturating_sub(VIEWPORT_HEIGHT - PADDING);
-        }
+#[cfg(not(unix))]
+fn sync_parent_directory(_path: &Path) {}
+
+/// A rectangle of fixed height and a percentage of the width, centered in `area`.
+fn centered_rect(area: Rect, percent_x: u16, height: u16) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Fill(1),
+            Constraint::Length(height),
+            Constraint::Fill(1),
+        ])
+        .split(area)[1];
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical)[1]
+}
+
+fn string_items(items: &[PlistValue]) -> Option<Vec<String>> {
+    items
+        .iter()
+        .map(|item| match item {
+            PlistValue::String(s) => Some(s.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn parse_plist_integer(text: &str) -> Option<i64> {
+    let text = text.trim();
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => i64::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
     }
+}
 
-    fn start_editing(&mut self) -> Result<()> {
-        if let Some(plist) = &self.selected_plist {
-            self.editing = true;
-            self.editing_field = Some(self.current_field.clone());
-            self.edit_buffer = match self.current_field {
-                FormField::Label => plist.label.clone().unwrap_or_default(),
-                FormField::Program => plist.program.clone().unwrap_or_default(),
-                FormField::StartInterval => plist
-                    .start_interval
-                    .map(|i| i.to_string())
-                    .unwrap_or_default(),
-                FormField::ThrottleInterval => plist
-                    .throttle_interval
-                    .map(|i| i.to_string())
-                    .unwrap_or_default(),
-                FormField::RunAtLoad => if plist.run_at_load.unwrap_or(false) {
-                    "true"
-                } else {
-                    "false"
-                }
-                .to_string(),
-                FormField::KeepAlive => if plist.keep_alive.unwrap_or(false) {
-                    "true"
-                } else {
-                    "false"
-                }
-                .to_string(),
-                FormField::AbandonProcessGroup => if plist.abandon_process_group.unwrap_or(false) {
-                    "true"
-                } else {
-                    "false"
+/// Parses an XML plist into a value tree. The `<plist>` wrapper is optional.
+fn parse_plist_value(content: &str) -> Result<PlistValue> {
+    use color_eyre::eyre::eyre;
+    use quick_xml::events::Event as XmlEvent;
+
+    enum Container {
+        Array(Vec<PlistValue>),
+        Dict(Vec<(String, PlistValue)>, Option<String>),
+    }
+
+    let mut reader = quick_xml::Reader::from_str(content);
+    let mut stack: Vec<Container> = Vec::new();
+    // Element name and text of the scalar being read
+    let mut scalar: Option<(String, String)> = None;
+    let mut root = None;
+
+    loop {
+        let event = reader.read_event().map_err(|e| {
+            let position = (reader.error_position() as usize).min(content.len());
+            let line = content.as_bytes()[..position]
+                .iter()
+                .filter(|byte| **byte == b'\n')
+                .count()
+                + 1;
+            eyre!("Invalid XML on line {}: {}", line, e)
+        })?;
+        let value = match event {
+            XmlEvent::Start(element) => {
+                let name = String::from_utf8_lossy(element.name().as_ref()).to_string();
+                match name.as_str() {
+                    "plist" => {}
+                    "dict" => stack.push(Container::Dict(Vec::new(), None)),
+                    "array" => stack.push(Container::Array(Vec::new())),
+                    "key" | "string" | "integer" | "real" | "date" | "data" => {
+                        scalar = Some((name, String::new()))
+                    }
+                    _ => return Err(eyre!("Unexpected <{}> element", name)),
                 }
-                .to_string(),
-                FormField::EnablePressuredExit => if plist.enable_pressured_exit.unwrap_or(false) {
-                    "true"
-                } else {
-                    "false"
+                continue;
+            }
+            XmlEvent::Empty(element) => match element.name().as_ref() {
+                b"true" => PlistValue::Boolean(true),
+                b"false" => PlistValue::Boolean(false),
+                b"string" => PlistValue::String(String::new()),
+                b"array" => PlistValue::Array(Vec::new()),
+                b"dict" => PlistValue::Dict(Vec::new()),
+                b"plist" => continue,
+                name => {
+                    return Err(eyre!(
+                        "Unexpected <{}/> element",
+                        String::from_utf8_lossy(name)
+                    ));
                 }
-                .to_string(),
-                FormField::EnableTransactions => if plist.enable_transactions.unwrap_or(false) {
-                    "true"
-                } else {
-                    "false"
+            },
+            XmlEvent::Text(text) => {
+                if let Some((_, buffer)) = &mut scalar {
+                    buffer.push_str(&text.decode()?);
                 }
-                .to_string(),
-                FormField::EventMonitor => if plist.event_monitor.unwrap_or(false) {
-                    "true"
-                } else {
-                    "false"
-                }
-                .to_string(),
-                FormField::StandardOutPath => plist.standard_out_path.clone().unwrap_or_default(),
-                FormField::StandardErrorPath => {
-                    plist.standard_error_path.clone().unwrap_or_default()
-                }
-                FormField::WorkingDirectory => plist.working_directory.clone().unwrap_or_default(),
-                FormField::POSIXSpawnType => plist.posix_spawn_type.clone().unwrap_or_default(),
-                FormField::ProgramArguments => {
-                    if let Some(args) = &plist.program_arguments {
-                        args.join("\n")
-                    } else {
-                        String::new()
-                    }
+                continue;
+            }
+            XmlEvent::CData(text) => {
+                if let Some((_, buffer)) = &mut scalar {
+                    buffer.push_str(&text.decode()?);
                 }
-                FormField::AssociatedBundleIdentifiers => {
-                    if let Some(ids) = &plist.associated_bundle_identifiers {
-                        ids.join("\n")
-                    } else {
-                        String::new()
+                continue;
+            }
+            XmlEvent::GeneralRef(reference) => {
+                if let Some((_, buffer)) = &mut scalar {
+                    match reference.resolve_char_ref()? {
+                        Some(ch) => buffer.push(ch),
+                        None => {
+                            let name = reference.decode()?;
+                            let resolved = quick_xml::escape::resolve_predefined_entity(&name)
+                                .ok_or_else(|| eyre!("Unknown entity &{};", name))?;
+                            buffer.push_str(resolved);
+                        }
                     }
                 }
-                FormField::LimitLoadToSessionType => match &plist.limit_load_to_session_type {
-                    Some(LimitLoadToSessionType::Single(s)) => s.clone(),
-                    Some(LimitLoadToSessionType::Multiple(v)) => v.join("\n"),
-                    None => String::new(),
+                continue;
+            }
+            XmlEvent::End(element) => match element.name().as_ref() {
+                b"plist" => continue,
+                b"dict" | b"array" => match stack.pop() {
+                    Some(Container::Dict(entries, _)) => PlistValue::Dict(entries),
+                    Some(Container::Array(items)) => PlistValue::Array(items),
+                    None => return Err(eyre!("Unbalanced </dict> or </array>")),
                 },
-                FormField::EnvironmentVariables => {
-                    if let Some(env_vars) = &plist.environment_variables {
-                        env_vars
-                            .iter()
-                            .map(|(k, v)| format!("{}={}", k, v))
-                            .collect::<Vec<_>>()
-                            .join("\n")
-                    } else {
-                        String::new()
+                _ => {
+                    let Some((name, text)) = scalar.take() else {
+                        continue;
+                    };
+                    match name.as_str() {
+                        "key" => {
+                            match stack.last_mut() {
+                                Some(Container::Dict(_, key)) => *key = Some(text),
+                                _ => return Err(eyre!("<key> outside of a <dict>")),
+                            }
+                            continue;
+                        }
+                        "integer" => PlistValue::Integer(
+                            parse_plist_integer(&text)
+                                .ok_or_else(|| eyre!("Invalid integer: {}", text.trim()))?,
+                        ),
+                        "real" => PlistValue::Real(
+                            text.trim()
+                                .parse()
+                                .map_err(|_| eyre!("Invalid real: {}", text.trim()))?,
+                        ),
+                        "date" => PlistValue::Date(text.trim().to_string()),
+                        "data" => PlistValue::Data(text.split_whitespace().collect()),
+                        _ => PlistValue::String(text),
                     }
                 }
-            };
+            },
+            XmlEvent::Eof => break,
+            _ => continue,
+        };
+
+        match stack.last_mut() {
+            Some(Container::Array(items)) => items.push(value),
+            Some(Container::Dict(entries, key)) => {
+                let key = key
+                    .take()
+                    .ok_or_else(|| eyre!("<{}> value without a <key>", value.kind()))?;
+                entries.push((key, value));
+            }
+            None if root.is_none() => root = Some(value),
+            None => return Err(eyre!("More than one top-level value")),
         }
-        Ok(())
     }
 
-    fn handle_edit_keys(&mut self, key: KeyEvent) -> Result<()> {
-        match key.code {
-            KeyCode::Esc => {
-                self.editing = false;
-                self.editing_field = None;
-                self.edit_buffer.clear();
-                self.set_status_message("✗ Edit cancelled".to_string());
-            }
-            KeyCode::Enter => {
-                self.save_field_edit()?;
-                self.editing = false;
-                self.editing_field = None;
-            }
-            KeyCode::Backspace => {
-                self.edit_buffer.pop();
-            }
-            KeyCode::Char(c) => {
-                self.edit_buffer.push(c);
-            }
-            // Ignore arrow keys and other navigation keys during editing
-            KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right => {
-                // Do nothing - prevent navigation during editing
-            }
-            KeyCode::Tab => {
-                // Tab should not change focus while editing
-            }
-            KeyCode::Home | KeyCode::End | KeyCode::PageUp | KeyCode::PageDown => {
-                // Ignore other navigation keys during editing
-            }
-            _ => {}
+    if !stack.is_empty() {
+        return Err(eyre!("Unclosed <dict> or <array>"));
+    }
+    root.ok_or_else(|| eyre!("No plist value found"))
+}
+
+fn parse_plist_xml(content: &str) -> Result<PlistData> {
+    PlistData::from_value(&parse_plist_value(content)?)
+}
+
+const PLIST_HEADER: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n<plist version=\"1.0\">\n";
+
+/// The exact layout of a plist document: everything around the root
+/// dictionary and the whitespace between its keys.
+#[derive(Debug, Clone, PartialEq)]
+struct PlistLayout {
+    /// Everything before the root `<dict>`: the XML declaration, DOCTYPE and
+    /// `<plist>` tag.
+    header: String,
+    /// Everything after the root `</dict>`.
+    footer: String,
+    indent: String,
+    /// The line separating top-level keys, if there is one.
+    blank_line: Option<String>,
+    /// Whether the separator also follows the last key.
+    trailing_blank_line: bool,
+    crlf: bool,
+    /// The root dictionary's entries as they appear in the source. Entries
+    /// whose value hasn't changed are written back verbatim.
+    entries: Vec<SourceEntry>,
+    /// Everything between the last entry and the root `</dict>`.
+    tail: Option<String>,
+}
+
+/// A top-level entry of a plist file and the text it was read from.
+#[derive(Debug, Clone, PartialEq)]
+struct SourceEntry {
+    key: String,
+    value: PlistValue,
+    /// Whitespace and comments between the previous entry and this one.
+    gap: String,
+    /// The `<key>` element through the end of the value.
+    text: String,
+}
+
+impl PlistLayout {
+    fn from_style(style: &FormatStyle) -> Self {
+        Self {
+            header: PLIST_HEADER.to_string(),
+            footer: "\n</plist>\n".to_string(),
+            indent: style.indent.unit(),
+            blank_line: style.blank_lines.then(String::new),
+            trailing_blank_line: false,
+            crlf: false,
+            entries: Vec::new(),
+            tail: None,
         }
-        Ok(())
     }
 
-    fn save_field_edit(&mut self) -> Result<()> {
-        if let (Some(plist), Some(editing_field)) = (&mut self.selected_plist, &self.editing_field)
-        {
-            match editing_field {
-                FormField::Label => {
-                    plist.label = (!self.edit_buffer.is_empty()).then(|| self.edit_buffer.clone());
-                }
-                FormField::Program => {
-                    plist.program =
-                        (!self.edit_buffer.is_empty()).then(|| self.edit_buffer.clone());
-                }
-                FormField::StartInterval => {
-                    plist.start_interval = self.edit_buffer.parse().ok();
-                }
-                FormField::ThrottleInterval => {
-                    plist.throttle_interval = self.edit_buffer.parse().ok();
-                }
-                FormField::RunAtLoad => {
-                    plist.run_at_load = Some(self.edit_buffer == "true");
-                }
-                FormField::KeepAlive => {
-                    plist.keep_alive = Some(self.edit_buffer == "true");
-                }
-                FormField::AbandonProcessGroup => {
-                    plist.abandon_process_group = Some(self.edit_buffer == "true");
-                }
-                FormField::EnablePressuredExit => {
-                    plist.enable_pressured_exit = Some(self.edit_buffer == "true");
-                }
-                FormField::EnableTransactions => {
-                    plist.enable_transactions = Some(self.edit_buffer == "true");
-                }
-                FormField::EventMonitor => {
-                    plist.event_monitor = Some(self.edit_buffer == "true");
-                }
-                FormField::StandardOutPath => {
-                    plist.standard_out_path =
-                        (!self.edit_buffer.is_empty()).then(|| self.edit_buffer.clone());
-                }
-                FormField::StandardErrorPath => {
-                    plist.standard_error_path =
-                        (!self.edit_buffer.is_empty()).then(|| self.edit_buffer.clone());
-                }
-                FormField::WorkingDirectory => {
-                    plist.working_directory =
-                        (!self.edit_buffer.is_empty()).then(|| self.edit_buffer.clone());
-                }
-                FormField::POSIXSpawnType => {
-                    plist.posix_spawn_type =
-                        (!self.edit_buffer.is_empty()).then(|| self.edit_buffer.clone());
-                }
-                FormField::ProgramArguments => {
-                    let args: Vec<String> = self
-                        .edit_buffer
-                        .lines()
-                        .map(|line| line.trim().to_string())
-                        .filter(|line| !line.is_empty())
-                        .collect();
-                    plist.program_arguments = (!args.is_empty()).then_some(args);
-                }
-                FormField::AssociatedBundleIdentifiers => {
-                    let ids: Vec<String> = self
-                        .edit_buffer
-                        .lines()
-                        .map(|line| line.trim().to_string())
-                        .filter(|line| !line.is_empty())
-                        .collect();
-                    plist.associated_bundle_identifiers = (!ids.is_empty()).then_some(ids);
-                }
-                FormField::LimitLoadToSessionType => {
-                    if self.edit_buffer.is_empty() {
-                        plist.limit_load_to_session_type = None;
-                    } else {
-                        let lines: Vec<String> = self
-                            .edit_buffer
-                            .lines()
-                            .map(|line| line.trim().to_string())
-                            .filter(|line| !line.is_empty())
-                            .collect();
-                        if lines.len() == 1 {
-                            plist.limit_load_to_session_type =
-                                Some(LimitLoadToSessionType::Single(lines[0].clone()));
-                        } else if lines.len() > 1 {
-                            plist.limit_load_to_session_type =
-                                Some(LimitLoadToSessionType::Multiple(lines));
+    /// The layout lam wrote new files in before it kept each file's own:
+    /// four-space indents and an indented blank line after every key.
+    fn legacy() -> Self {
+        Self {
+            header: PLIST_HEADER.to_string(),
+            footer: "\n</plist>\n".to_string(),
+            indent: "    ".to_string(),
+            blank_line: Some("    ".to_string()),
+            trailing_blank_line: true,
+            crlf: false,
+            entries: Vec::new(),
+            tail: None,
+        }
+    }
+
+    /// Detects the layout of `content`, a plist whose root is a dictionary.
+    /// Returns `None` when the root isn't one or the plist doesn't parse.
+    fn detect(content: &str) -> Option<Self> {
+        use quick_xml::events::Event as XmlEvent;
+
+        let crlf = content.contains("\r\n");
+        let content = content.replace("\r\n", "\n");
+        let PlistValue::Dict(parsed) = parse_plist_value(&content).ok()? else {
+            return None;
+        };
+
+        // Byte ranges of the root tags and of each top-level entry
+        let mut reader = quick_xml::Reader::from_str(&content);
+        let mut depth = 0;
+        let mut header_end = 0;
+        let mut start = None;
+        let mut end = None;
+        let mut key_start = None;
+        let mut spans = Vec::new();
+        while end.is_none() {
+            let before = reader.buffer_position() as usize;
+            let event = reader.read_event().ok()?;
+            let after = reader.buffer_position() as usize;
+            match event {
+                XmlEvent::Start(element) => match element.name().as_ref() {
+                    b"dict" | b"array" => {
+                        if depth == 0 {
+                            header_end = before;
+                            start = Some(after);
                         }
+                        depth += 1;
                     }
-                }
-                FormField::EnvironmentVariables => {
-                    let mut env_vars = std::collections::HashMap::new();
-                    for line in self.edit_buffer.lines() {
-                        let line = line.trim();
-                        if !line.is_empty()
-                            && line.contains('=')
-                            && let Some((key, value)) = line.split_once('=')
-                        {
-                            env_vars.insert(key.trim().to_string(), value.trim().to_string());
+                    b"key" if depth == 1 => key_start = Some(before),
+                    _ => {}
+                },
+                XmlEvent::End(element) => match element.name().as_ref() {
+                    b"dict" | b"array" => {
+                        depth -= 1;
+                        match depth {
+                            0 => end = Some(before),
+                            1 => spans.push(key_start.take()?..after),
+                            _ => {}
                         }
                     }
-                    plist.environment_variables = (!env_vars.is_empty()).then_some(env_vars);
+                    b"key" | b"plist" => {}
+                    _ if depth == 1 => spans.push(key_start.take()?..after),
+                    _ => {}
+                },
+                XmlEvent::Empty(element) if depth == 1 && element.name().as_ref() != b"plist" => {
+                    spans.push(key_start.take()?..after)
                 }
+                XmlEvent::Eof => return None,
+                _ => {}
             }
-            self.set_status_message(format!("✓ Updated {}", self.get_editing_field_name()));
         }
-        self.edit_buffer.clear();
-        Ok(())
+        let (start, end) = (start?, end?);
+        if spans.len() != parsed.len() {
+            return None;
+        }
+
+        let mut previous = start;
+        let mut entries = Vec::new();
+        for (span, (key, value)) in spans.into_iter().zip(parsed) {
+            entries.push(SourceEntry {
+                key,
+                value,
+                gap: content[previous..span.start].to_string(),
+                text: content[span.clone()].to_string(),
+            });
+            previous = span.end;
+        }
+
+        // The first and last pieces share a line with the root tags
+        let lines: Vec<&str> = content[start..end].split('\n').collect();
+        let lines = lines
+            .get(1..lines.len().saturating_sub(1))
+            .unwrap_or_default();
+        let is_blank = |line: &str| line.trim().is_empty();
+        let indent = lines
+            .iter()
+            .find(|line| line.trim_start().starts_with("<key>"))
+            .map(|line| &line[..line.len() - line.trim_start().len()])
+            .filter(|indent| !indent.is_empty())
+            .unwrap_or("\t");
+
+        Some(Self {
+            header: content[..header_end].to_string(),
+            footer: content[end + "</dict>".len()..].to_string(),
+            indent: indent.to_string(),
+            blank_line: lines
+                .iter()
+                .find(|line| is_blank(line))
+                .map(|line| line.to_string()),
+            trailing_blank_line: lines.last().is_some_and(|line| is_blank(line)),
+            crlf,
+            entries,
+            tail: Some(content[previous..end].to_string()),
+        })
     }
 
-    fn save_plist(&mut self) -> Result<()> {
-        if let Some(plist) = &self.selected_plist {
-            if let Some(selected) = self.list_state.selected() {
-                let filtered_agents = self.get_filtered_agents();
-                if let Some(agent) = filtered_agents.get(selected) {
-                    let file_path = self.get_current_directory().join(&agent.filename);
-                    let xml_content = match self.plist_to_xml(plist) {
-                        Ok(c) => c,
-                        Err(e) => {
-                            self.set_status_message(format!("✗ Failed to serialize: {}", e));
-                            return Ok(());
+    /// Writes a complete plist document for `value`, keeping its key order.
+    /// Entries read from the source that are still the same keep their
+    /// original text.
+    fn write(&self, value: &PlistValue) -> String {
+        let mut xml = self.header.clone();
+        match value {
+            PlistValue::Dict(entries) if !entries.is_empty() => {
+                xml.push_str("<dict>");
+                let mut unused: Vec<&SourceEntry> = self.entries.iter().collect();
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    let source = unused
+                        .iter()
+                        .position(|entry| entry.key == *key)
+                        .map(|position| unused.remove(position));
+                    match source {
+                        // Reordered dictionary keys count as a change
+                        Some(source) if source.value == *value => {
+                            xml.push_str(&source.gap);
+                            xml.push_str(&source.text);
+                            continue;
                         }
-                    };
-                    if let Err(e) = fs::write(&file_path, &xml_content) {
-                        self.set_status_message(format!("✗ Failed to save: {}", e));
-                        return Ok(());
-                    }
-
-                    // Reload the agent with launchctl
-                    match self.reload_agent(file_path.to_owned()) {
-                        Ok(()) => {
-                            self.set_status_message(format!(
-                                "✓ Saved and reloaded {}",
-                                agent.filename
-                            ));
-                            // Refresh the agent status after successful reload
-                            self.refresh_agent_status();
+                        // Keep the comments and blank lines before a changed entry
+                        Some(source) => match source.gap.rfind('\n') {
+                            Some(newline) => xml.push_str(&source.gap[..newline]),
+                            None => xml.push_str(&source.gap),
+                        },
+                        None => {
+                            if let Some(blank_line) = self.blank_line.as_ref().filter(|_| i > 0) {
+                                xml.push_str(&format!("\n{}", blank_line));
+                            }
                         }
-                        Err(e) => {
-                            self.set_status_message(format!(
-                                "✓ Saved {} but reload failed: {}",
-                                agent.filename, e
-                            ));
+                    }
+                    xml.push_str(&format!(
+                        "\n{}<key>{}</key>\n",
+                        self.indent,
+                        App::xml_escape(key)
+                    ));
+                    write_plist_value(&mut xml, value, 1, &self.indent);
+                    xml.pop();
+                }
+                match &self.tail {
+                    Some(tail) => xml.push_str(tail),
+                    None => {
+                        xml.push('\n');
+                        if let Some(blank_line) = self
+                            .blank_line
+                            .as_ref()
+                            .filter(|_| self.trailing_blank_line)
+                        {
+                            xml.push_str(&format!("{}\n", blank_line));
                         }
                     }
-                } else {
-                    self.set_status_message("✗ No agent selected".to_string());
                 }
-            } else {
-                self.set_status_message("✗ No agent selected".to_string());
+                xml.push_str("</dict>");
+            }
+            value => {
+                write_plist_value(&mut xml, value, 0, &self.indent);
+                xml.pop();
             }
-        } else {
-            self.set_status_message("✗ No plist data to save".to_string());
         }
-        Ok(())
+        xml.push_str(&self.footer);
+        if self.crlf {
+            xml = xml.replace('\n', "\r\n");
+        }
+        xml
     }
+}
 
-    fn reload_agent(&self, file_path: PathBuf) -> Result<()> {
-        // First unload the agent (ignore errors if it wasn't loaded)
-        let unload_result = std::process::Command::new("launchctl")
-            .args(["unload", &file_path.to_string_lossy()])
-            .output();
+/// Position of `key` in launchd.plist(5), unknown keys sorting last.
+fn launchd_key_rank(key: &str) -> usize {
+    LAUNCHD_KEYS
+        .iter()
+        .position(|known| known.name == key)
+        .unwrap_or(LAUNCHD_KEYS.len())
+}
 
-        match unload_result {
-            Ok(output) => {
-                if !output.status.success() {
-                    // Unload failed, but that's okay if the agent wasn't loaded
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    if !stderr.contains("Could not find specified service") {
-                        return Err(color_eyre::eyre::eyre!("Unload failed: {}", stderr));
-                    }
-                }
-            }
-            Err(e) => {
-                return Err(color_eyre::eyre::eyre!(
-                    "Failed to run launchctl unload: {}",
-                    e
-                ));
+/// Writes a complete plist document for `value` in `style`.
+fn format_plist(value: &PlistValue, style: &FormatStyle) -> String {
+    let value = match value {
+        PlistValue::Dict(entries) => {
+            let mut entries = entries.clone();
+            match style.key_order {
+                KeyOrder::Launchd => entries.sort_by_key(|(key, _)| launchd_key_rank(key)),
+                KeyOrder::Alphabetical => entries.sort_by(|a, b| a.0.cmp(&b.0)),
+                KeyOrder::Preserve => {}
             }
+            PlistValue::Dict(entries)
         }
+        value => value.clone(),
+    };
+    PlistLayout::from_style(style).write(&value)
+}
 
-        // Now load the agent
-        let load_result = std::process::Command::new("launchctl")
-            .args(["load", &file_path.to_string_lossy()])
-            .output();
-
-        match load_result {
-            Ok(output) => {
-                if !output.status.success() {
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    return Err(color_eyre::eyre::eyre!("Load failed: {}", stderr));
-                }
+/// Writes `value` as plist XML, `depth` levels of `indent` deep.
+fn write_plist_value(xml: &mut String, value: &PlistValue, depth: usize, indent: &str) {
+    let pad = indent.repeat(depth);
+    match value {
+        PlistValue::String(s) => {
+            xml.push_str(&format!("{}<string>{}</string>\n", pad, App::xml_escape(s)))
+        }
+        PlistValue::Integer(n) => xml.push_str(&format!("{}<integer>{}</integer>\n", pad, n)),
+        PlistValue::Real(n) => xml.push_str(&format!("{}<real>{}</real>\n", pad, n)),
+        PlistValue::Boolean(true) => xml.push_str(&format!("{}<true/>\n", pad)),
+        PlistValue::Boolean(false) => xml.push_str(&format!("{}<false/>\n", pad)),
+        PlistValue::Date(date) => xml.push_str(&format!("{}<date>{}</date>\n", pad, date)),
+        PlistValue::Data(data) => xml.push_str(&format!("{}<data>{}</data>\n", pad, data)),
+        PlistValue::Array(items) if items.is_empty() => xml.push_str(&format!("{}<array/>\n", pad)),
+        PlistValue::Array(items) => {
+            xml.push_str(&format!("{}<array>\n", pad));
+            for item in items {
+                write_plist_value(xml, item, depth + 1, indent);
             }
-            Err(e) => {
-                return Err(color_eyre::eyre::eyre!(
-                    "Failed to run launchctl load: {}",
-                    e
+            xml.push_str(&format!("{}</array>\n", pad));
+        }
+        PlistValue::Dict(entries) if entries.is_empty() => {
+            xml.push_str(&format!("{}<dict/>\n", pad))
+        }
+        PlistValue::Dict(entries) => {
+            xml.push_str(&format!("{}<dict>\n", pad));
+            for (key, value) in entries {
+                xml.push_str(&format!(
+                    "{}{}<key>{}</key>\n",
+                    pad,
+                    indent,
+                    App::xml_escape(key)
                 ));
+                write_plist_value(xml, value, depth + 1, indent);
             }
+            xml.push_str(&format!("{}</dict>\n", pad));
         }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_label_element() {
+        let xml = r#"<dict>
+    <key>Label</key>
+    <string>com.user.test</string>
+</dict>"#;
+
+        let parsed = parse_plist_xml(xml).unwrap();
+        assert_eq!(parsed.label, Some("com.user.test".to_string()));
+    }
+
+    #[test]
+    fn test_parse_start_interval_element() {
+        let xml = r#"<dict>
+    <key>StartInterval</key>
+    <integer>300</integer>
+</dict>"#;
+
+        let parsed = parse_plist_xml(xml).unwrap();
+        assert_eq!(parsed.start_interval, Some(300));
+    }
+
+    #[test]
+    fn test_parse_boolean_true_element() {
+        let xml = r#"<dict>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>"#;
+
+        let parsed = parse_plist_xml(xml).unwrap();
+        assert_eq!(parsed.run_at_load, Some(true));
+    }
+
+    #[test]
+    fn test_parse_boolean_false_element() {
+        let xml = r#"<dict>
+    <key>KeepAlive</key>
+    <false/>
+</dict>"#;
+
+        let parsed = parse_plist_xml(xml).unwrap();
+        assert_eq!(parsed.keep_alive, Some(false));
+    }
+
+    #[test]
+    fn test_parse_program_arguments_array() {
+        let xml = r#"<dict>
+    <key>ProgramArguments</key>
+    <array>
+        <string>/usr/bin/test</string>
+        <string>--flag</string>
+    </array>
+</dict>"#;
+
+        let parsed = parse_plist_xml(xml).unwrap();
+        assert_eq!(
+            parsed.program_arguments,
+            Some(vec!["/usr/bin/test".to_string(), "--flag".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_parse_string_paths() {
+        let xml = r#"<dict>
+    <key>StandardOutPath</key>
+    <string>/tmp/out.log</string>
+    <key>StandardErrorPath</key>
+    <string>/tmp/err.log</string>
+    <key>WorkingDirectory</key>
+    <string>/tmp</string>
+</dict>"#;
+
+        let parsed = parse_plist_xml(xml).unwrap();
+        assert_eq!(parsed.standard_out_path, Some("/tmp/out.log".to_string()));
+        assert_eq!(parsed.standard_error_path, Some("/tmp/err.log".to_string()));
+        assert_eq!(parsed.working_directory, Some("/tmp".to_string()));
+    }
+
+    #[test]
+    fn test_transfer_targets() {
+        assert_eq!(
+            TabLocation::User.transfer_target(TransferMode::Move),
+            Some(TabLocation::Global)
+        );
+        assert_eq!(
+            TabLocation::Global.transfer_target(TransferMode::Move),
+            Some(TabLocation::User)
+        );
+        assert_eq!(
+            TabLocation::Apple.transfer_target(TransferMode::Copy),
+            Some(TabLocation::User)
+        );
+        assert_eq!(TabLocation::Apple.transfer_target(TransferMode::Move), None);
+    }
+
+    #[test]
+    fn test_apple_location_is_read_only() {
+        let path = Path::new("/System/Library/LaunchAgents/com.apple.example.plist");
+        assert_eq!(TabLocation::Apple.access_for(path), Access::ReadOnly);
+        assert_eq!(TabLocation::User.access_for(path), Access::ReadOnly);
+    }
+
+    #[test]
+    fn test_user_location_is_writable() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("com.user.test.plist");
+        assert_eq!(TabLocation::User.access_for(&path), Access::Writable);
+
+        fs::write(&path, "<plist/>").unwrap();
+        assert_eq!(TabLocation::User.access_for(&path), Access::Writable);
+    }
+
+    #[test]
+    fn test_transfer_needs_root() {
+        let transfer = |mode, from, to| AgentTransfer {
+            mode,
+            filename: "com.user.test.plist".to_string(),
+            from,
+            to,
+        };
+
+        assert!(transfer(TransferMode::Copy, TabLocation::User, TabLocation::Global).needs_root());
+        assert!(transfer(TransferMode::Move, TabLocation::Global, TabLocation::User).needs_root());
+        assert!(!transfer(TransferMode::Copy, TabLocation::Global, TabLocation::User).needs_root());
+    }
+
+    #[test]
+    fn test_validate_privileged_path() {
+        assert!(
+            validate_privileged_path(Path::new("/Library/LaunchAgents/com.example.agent.plist"))
+                .is_ok()
+        );
+        assert!(
+            validate_privileged_path(Path::new("/Library/LaunchDaemons/com.example.daemon.plist"))
+                .is_ok()
+        );
+        assert!(validate_privileged_path(Path::new("/etc/sudoers")).is_err());
+        assert!(validate_privileged_path(Path::new("/Library/LaunchAgents/notes.txt")).is_err());
+        assert!(
+            validate_privileged_path(Path::new(
+                "/Library/LaunchAgents/../LaunchDaemons/x/com.example.plist"
+            ))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_write_atomically_replaces_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("com.user.test.plist");
+        fs::write(&path, "old").unwrap();
 
-        Ok(())
+        write_atomically(&path, b"new").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
     }
 
-    fn refresh_agent_status(&mut self) {
-        // Refresh the status of agents in the current tab
-        let current_agents = self.get_current_agents_mut();
-        for agent in current_agents {
-            if let Some(label) = &agent.label {
-                agent.status = Self::check_agent_status(label);
-                agent.enabled = Self::check_agent_enabled(label);
-            }
+    #[test]
+    fn test_write_atomically_keeps_original_on_failure() {
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("com.user.test.plist");
+        fs::write(&path, "original").unwrap();
+
+        let result = write_atomically_with(&path, |file| {
+            file.write_all(b"trunc")?;
+            Err(std::io::Error::new(
+                std::io::ErrorKind::StorageFull,
+                "disk full",
+            ))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "original");
+        // The temp file is cleaned up
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomically_preserves_mode_and_xattrs() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("com.user.test.plist");
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+        // Not every filesystem supports user xattrs (e.g. older tmpfs)
+        let has_xattr = xattr::set(&path, "user.lam.test", b"kept").is_ok();
+
+        write_atomically(&path, b"new").unwrap();
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        if has_xattr {
+            assert_eq!(
+                xattr::get(&path, "user.lam.test").unwrap(),
+                Some(b"kept".to_vec())
+            );
         }
     }
 
-    fn switch_to_tab(&mut self, new_tab: TabLocation) {
-        if self.current_tab != new_tab {
-            self.current_tab = new_tab;
-            self.selected_plist = None;
-            self.filter_text.clear();
-            self.form_scroll_offset = 0;
-            self.log_lines.clear();
-            self.log_scroll_offset = 0;
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomically_follows_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("dotfiles.plist");
+        let link = dir.path().join("com.user.test.plist");
+        fs::write(&target, "old").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
 
-            // Reset list selection to first item if available
-            let current_agents = self.get_current_agents();
-            self.list_state.select(if current_agents.is_empty() {
-                None
-            } else {
-                Some(0)
-            });
-        }
+        write_atomically(&link, b"new").unwrap();
+
+        assert!(link.is_symlink());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "new");
     }
 
-    fn xml_escape(s: &str) -> String {
-        s.replace('&', "&amp;")
-            .replace('<', "&lt;")
-            .replace('>', "&gt;")
-            .replace('"', "&quot;")
+    #[test]
+    fn test_merge_rows_three_way() {
+        let base = PlistData {
+            label: Some("com.user.test".to_string()),
+            program: Some("/bin/old".to_string()),
+            start_interval: Some(60),
+            ..Default::default()
+        };
+        let mut ours = base.clone();
+        ours.start_interval = Some(120);
+        ours.run_at_load = Some(true);
+        let mut theirs = base.clone();
+        theirs.program = Some("/bin/new".to_string());
+        theirs.run_at_load = Some(false);
+        let calendar = PlistValue::Dict(vec![("Hour".to_string(), PlistValue::Integer(9))]);
+        theirs.set_extra("StartCalendarInterval", Some(calendar.clone()));
+
+        let mut rows = merge_rows(&base, &ours, &theirs);
+        let keys: Vec<_> = rows.iter().map(|row| row.key.clone()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                MergeKey::Field(FormField::Program),
+                MergeKey::Field(FormField::StartInterval),
+                MergeKey::Field(FormField::RunAtLoad),
+                MergeKey::Extra("StartCalendarInterval".to_string()),
+            ]
+        );
+        assert!(rows[3].take_theirs && !rows[3].conflicting);
+        assert_eq!(rows[3].base, "");
+        assert_eq!(rows[3].theirs, "{\"Hour\":9}");
+        // Only one side changed Program and StartInterval, both changed RunAtLoad
+        assert!(rows[0].take_theirs && !rows[0].conflicting);
+        assert!(!rows[1].take_theirs && !rows[1].conflicting);
+        assert!(!rows[2].take_theirs && rows[2].conflicting);
+
+        let merged = apply_merge(&ours, &theirs, &rows);
+        assert_eq!(merged.program.as_deref(), Some("/bin/new"));
+        assert_eq!(merged.start_interval, Some(120));
+        assert_eq!(merged.run_at_load, Some(true));
+        assert_eq!(merged.extra_value("StartCalendarInterval"), Some(&calendar));
+
+        rows[2].take_theirs = true;
+        assert_eq!(apply_merge(&ours, &theirs, &rows).run_at_load, Some(false));
     }
 
-    pub fn plist_to_xml(&self, plist: &PlistData) -> Result<String> {
-        let mut xml = String::new();
-        xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
-        xml.push_str("<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n");
-        xml.push_str("<plist version=\"1.0\">\n");
-        xml.push_str("<dict>\n");
+    #[test]
+    fn test_fingerprint_detects_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("agent.plist");
+        fs::write(&path, "one").unwrap();
 
-        if let Some(label) = &plist.label {
-            xml.push_str("    <key>Label</key>\n");
-            xml.push_str(&format!("    <string>{}</string>\n", Self::xml_escape(label)));
-            xml.push_str("    \n");
-        }
+        let fingerprint = FileFingerprint::read(&path).unwrap();
+        assert!(!fingerprint.is_stale(&path));
 
-        if let Some(args) = &plist.program_arguments {
-            xml.push_str("    <key>ProgramArguments</key>\n");
-            xml.push_str("    <array>\n");
-            for arg in args {
-                xml.push_str(&format!("        <string>{}</string>\n", Self::xml_escape(arg)));
-            }
-            xml.push_str("    </array>\n");
-            xml.push_str("    \n");
-        }
+        fs::write(&path, "one, edited").unwrap();
+        assert!(fingerprint.is_stale(&path));
 
-        if let Some(interval) = plist.start_interval {
-            xml.push_str("    <key>StartInterval</key>\n");
-            xml.push_str(&format!("    <integer>{}</integer>\n", interval));
-            xml.push_str("    \n");
-        }
+        fs::remove_file(&path).unwrap();
+        assert!(fingerprint.is_stale(&path));
+    }
 
-        if let Some(run_at_load) = plist.run_at_load {
-            xml.push_str("    <key>RunAtLoad</key>\n");
-            xml.push_str(&format!(
-                "    <{}/>\n",
-                if run_at_load { "true" } else { "false" }
-            ));
-            xml.push_str("    \n");
+    #[test]
+    fn test_edit_history_undo_redo() {
+        let step = |from: Option<i32>, to: Option<i32>| FormChange {
+            field: Some(FormField::StartInterval),
+            name: FormField::StartInterval.name(),
+            before: PlistData {
+                start_interval: from,
+                ..Default::default()
+            },
+            after: PlistData {
+                start_interval: to,
+                ..Default::default()
+            },
+        };
+        let mut history = EditHistory::default();
+        history.record(step(None, Some(60)));
+        history.record(step(Some(60), Some(120)));
+
+        assert_eq!(history.undo().unwrap().before.start_interval, Some(60));
+        assert_eq!(history.undo().unwrap().before.start_interval, None);
+        assert!(history.undo().is_none());
+        assert_eq!(history.redo().unwrap().after.start_interval, Some(60));
+
+        // A new edit after undoing drops the redo branch
+        history.record(step(Some(60), Some(300)));
+        assert!(history.redo().is_none());
+        assert_eq!(history.undo().unwrap().name, "Start Interval");
+
+        for _ in 0..UNDO_LIMIT + 10 {
+            history.record(step(None, Some(1)));
         }
+        assert_eq!(history.undo.len(), UNDO_LIMIT);
+    }
 
-        if let Some(keep_alive) = plist.keep_alive {
-            xml.push_str("    <key>KeepAlive</key>\n");
-            xml.push_str(&format!(
-                "    <{}/>\n",
-                if keep_alive { "true" } else { "false" }
-            ));
-            xml.push_str("    \n");
-        }
+    fn press(input: &mut TextInput, code: KeyCode, modifiers: KeyModifiers) {
+        assert!(input.handle_key(KeyEvent::new(code, modifiers)));
+    }
 
-        if let Some(stdout) = &plist.standard_out_path {
-            xml.push_str("    <key>StandardOutPath</key>\n");
-            xml.push_str(&format!("    <string>{}</string>\n", Self::xml_escape(stdout)));
-            xml.push_str("    \n");
-        }
+    #[test]
+    fn test_text_input_edits_in_the_middle() {
+        let mut input = TextInput::new("/usr/bin/tset".to_string());
+        press(&mut input, KeyCode::Left, KeyModifiers::NONE);
+        press(&mut input, KeyCode::Left, KeyModifiers::NONE);
+        press(&mut input, KeyCode::Backspace, KeyModifiers::NONE);
+        press(&mut input, KeyCode::Right, KeyModifiers::NONE);
+        press(&mut input, KeyCode::Char('s'), KeyModifiers::NONE);
+        assert_eq!(input.text(), "/usr/bin/test");
+
+        press(&mut input, KeyCode::Char('a'), KeyModifiers::CONTROL);
+        press(&mut input, KeyCode::Delete, KeyModifiers::NONE);
+        assert_eq!(input.text(), "usr/bin/test");
+    }
 
-        if let Some(stderr) = &plist.standard_error_path {
-            xml.push_str("    <key>StandardErrorPath</key>\n");
-            xml.push_str(&format!("    <string>{}</string>\n", Self::xml_escape(stderr)));
-            xml.push_str("    \n");
-        }
+    #[test]
+    fn test_text_input_handles_multibyte_graphemes() {
+        let mut input = TextInput::new("café 👩‍💻".to_string());
+        press(&mut input, KeyCode::Backspace, KeyModifiers::NONE);
+        assert_eq!(input.text(), "café ");
+        press(&mut input, KeyCode::Left, KeyModifiers::NONE);
+        press(&mut input, KeyCode::Left, KeyModifiers::NONE);
+        press(&mut input, KeyCode::Delete, KeyModifiers::NONE);
+        assert_eq!(input.text(), "caf ");
+    }
 
-        if let Some(workdir) = &plist.working_directory {
-            xml.push_str("    <key>WorkingDirectory</key>\n");
-            xml.push_str(&format!("    <string>{}</string>\n", Self::xml_escape(workdir)));
-            xml.push_str("    \n");
-        }
+    #[test]
+    fn test_text_input_word_motions_and_kills() {
+        let mut input = TextInput::new("/var/log/my-agent.log".to_string());
+        press(&mut input, KeyCode::Char('w'), KeyModifiers::CONTROL);
+        assert_eq!(input.text(), "/var/log/my-agent.");
+        press(&mut input, KeyCode::Left, KeyModifiers::CONTROL);
+        assert_eq!(input.cursor, "/var/log/my-".len());
+        press(&mut input, KeyCode::Char('b'), KeyModifiers::ALT);
+        press(&mut input, KeyCode::Char('k'), KeyModifiers::CONTROL);
+        assert_eq!(input.text(), "/var/log/");
+        press(&mut input, KeyCode::Left, KeyModifiers::NONE);
+        press(&mut input, KeyCode::Char('u'), KeyModifiers::CONTROL);
+        assert_eq!(input.text(), "/");
+    }
 
-        if let Some(program) = &plist.program {
-            xml.push_str("    <key>Program</key>\n");
-            xml.push_str(&format!("    <string>{}</string>\n", Self::xml_escape(program)));
-            xml.push_str("    \n");
-        }
+    #[test]
+    fn test_text_input_selection_is_replaced() {
+        let mut input = TextInput::new("hello world".to_string());
+        press(
+            &mut input,
+            KeyCode::Left,
+            KeyModifiers::SHIFT | KeyModifiers::CONTROL,
+        );
+        assert_eq!(input.selection(), Some(6..11));
+        press(&mut input, KeyCode::Char('t'), KeyModifiers::NONE);
+        assert_eq!(input.text(), "hello t");
+        assert_eq!(input.selection(), None);
+
+        press(&mut input, KeyCode::Home, KeyModifiers::SHIFT);
+        press(&mut input, KeyCode::Backspace, KeyModifiers::NONE);
+        assert_eq!(input.text(), "");
+    }
 
-        if let Some(interval) = plist.throttle_interval {
-            xml.push_str("    <key>ThrottleInterval</key>\n");
-            xml.push_str(&format!("    <integer>{}</integer>\n", interval));
-            xml.push_str("    \n");
-        }
+    #[test]
+    fn test_text_input_stays_on_one_line() {
+        let mut input = TextInput::new("one".to_string());
+        assert!(!input.handle_key(KeyEvent::new(KeyCode::Char('j'), KeyModifiers::CONTROL)));
+        assert!(!input.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::ALT)));
+        assert!(!input.handle_key(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE)));
+        input.insert("\ntwo\r\nthree");
+        assert_eq!(input.text(), "one two three");
+    }
 
-        if let Some(abandon) = plist.abandon_process_group {
-            xml.push_str("    <key>AbandonProcessGroup</key>\n");
-            xml.push_str(&format!(
-                "    <{}/>\n",
-                if abandon { "true" } else { "false" }
-            ));
-            xml.push_str("    \n");
+    fn type_text(editor: &mut ListEditor, text: &str) {
+        for c in text.chars() {
+            assert!(editor.handle_key(KeyEvent::from(KeyCode::Char(c))));
         }
+        assert!(editor.handle_key(KeyEvent::from(KeyCode::Enter)));
+    }
 
-        if let Some(pressured) = plist.enable_pressured_exit {
-            xml.push_str("    <key>EnablePressuredExit</key>\n");
-            xml.push_str(&format!(
-                "    <{}/>\n",
-                if pressured { "true" } else { "false" }
-            ));
-            xml.push_str("    \n");
-        }
+    #[test]
+    fn test_list_editor_adds_deletes_and_reorders_rows() {
+        let mut plist = PlistData {
+            program_arguments: Some(vec!["/bin/echo".to_string(), "world".to_string()]),
+            ..Default::default()
+        };
+        let mut editor = ListEditor::new(FormField::ProgramArguments, &plist);
+        editor.handle_key(KeyEvent::from(KeyCode::Char('a')));
+        type_text(&mut editor, "hello");
+        assert_eq!(editor.selected, 1);
+        editor.handle_key(KeyEvent::from(KeyCode::Char('J')));
+        editor.handle_key(KeyEvent::from(KeyCode::Char('a')));
+        type_text(&mut editor, "!");
+        editor.handle_key(KeyEvent::from(KeyCode::Char('d')));
+
+        // Cancelling a freshly added row drops it
+        editor.handle_key(KeyEvent::from(KeyCode::Char('a')));
+        editor.handle_key(KeyEvent::from(KeyCode::Esc));
+
+        editor.apply(&mut plist);
+        assert_eq!(
+            plist.program_arguments,
+            Some(vec![
+                "/bin/echo".to_string(),
+                "world".to_string(),
+                "hello".to_string()
+            ])
+        );
 
-        if let Some(transactions) = plist.enable_transactions {
-            xml.push_str("    <key>EnableTransactions</key>\n");
-            xml.push_str(&format!(
-                "    <{}/>\n",
-                if transactions { "true" } else { "false" }
-            ));
-            xml.push_str("    \n");
+        for _ in 0..3 {
+            editor.handle_key(KeyEvent::from(KeyCode::Char('d')));
         }
+        editor.apply(&mut plist);
+        assert_eq!(plist.program_arguments, None);
+    }
 
-        if let Some(monitor) = plist.event_monitor {
-            xml.push_str("    <key>EventMonitor</key>\n");
-            xml.push_str(&format!(
-                "    <{}/>\n",
-                if monitor { "true" } else { "false" }
-            ));
-            xml.push_str("    \n");
-        }
+    #[test]
+    fn test_list_editor_edits_environment_table() {
+        let mut plist = PlistData {
+            environment_variables: Some(vec![("PATH".to_string(), "/usr/bin".to_string())]),
+            ..Default::default()
+        };
+        let mut editor = ListEditor::new(FormField::EnvironmentVariables, &plist);
+        editor.handle_key(KeyEvent::from(KeyCode::Char('a')));
+        type_text(&mut editor, "LANG");
+        type_text(&mut editor, "en_US.UTF-8");
+
+        editor.handle_key(KeyEvent::from(KeyCode::Char('k')));
+        editor.handle_key(KeyEvent::from(KeyCode::Char('l')));
+        editor.handle_key(KeyEvent::from(KeyCode::Enter));
+        editor.handle_key(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL));
+        type_text(&mut editor, "/opt/bin");
+
+        editor.apply(&mut plist);
+        assert_eq!(
+            plist.environment_variables,
+            Some(vec![
+                ("PATH".to_string(), "/opt/bin".to_string()),
+                ("LANG".to_string(), "en_US.UTF-8".to_string()),
+            ])
+        );
+    }
 
-        if let Some(spawn_type) = &plist.posix_spawn_type {
-            xml.push_str("    <key>POSIXSpawnType</key>\n");
-            xml.push_str(&format!("    <string>{}</string>\n", Self::xml_escape(spawn_type)));
-            xml.push_str("    \n");
-        }
+    #[test]
+    fn test_boolean_cycles_through_unset() {
+        assert_eq!(cycle_bool(None), Some(true));
+        assert_eq!(cycle_bool(Some(true)), Some(false));
+        assert_eq!(cycle_bool(Some(false)), None);
+        assert_eq!(bool_label(None), "unset");
+        assert_eq!(bool_text(None), "");
+    }
 
-        if let Some(ids) = &plist.associated_bundle_identifiers {
-            xml.push_str("    <key>AssociatedBundleIdentifiers</key>\n");
-            xml.push_str("    <array>\n");
-            for id in ids {
-                xml.push_str(&format!("        <string>{}</string>\n", Self::xml_escape(id)));
-            }
-            xml.push_str("    </array>\n");
-            xml.push_str("    \n");
-        }
+    #[test]
+    fn test_picker_single_value() {
+        let mut plist = PlistData {
+            process_type: Some("Custom".to_string()),
+            ..Default::default()
+        };
+        let mut picker = Picker::new(FormField::ProcessType, &plist);
+        // Unknown values already in the plist stay selectable
+        assert_eq!(picker.options.last().map(String::as_str), Some("Custom"));
+        assert_eq!(picker.selected, PROCESS_TYPES.len() + 1);
+
+        picker.selected = 2;
+        assert!(picker.handle_key(KeyEvent::from(KeyCode::Enter)));
+        picker.apply(&mut plist);
+        assert_eq!(plist.process_type.as_deref(), Some("Standard"));
+
+        picker.selected = 0;
+        picker.apply(&mut plist);
+        assert_eq!(plist.process_type, None);
+    }
 
-        if let Some(session_type) = &plist.limit_load_to_session_type {
-            xml.push_str("    <key>LimitLoadToSessionType</key>\n");
-            match session_type {
-                LimitLoadToSessionType::Single(s) => {
-                    xml.push_str(&format!("    <string>{}</string>\n", Self::xml_escape(s)));
-                }
-                LimitLoadToSessionType::Multiple(sessions) => {
-                    xml.push_str("    <array>\n");
-                    for session in sessions {
-                        xml.push_str(&format!("        <string>{}</string>\n", Self::xml_escape(session)));
-                    }
-                    xml.push_str("    </array>\n");
-                }
-            }
-            xml.push_str("    \n");
-        }
+    #[test]
+    fn test_picker_session_types() {
+        let mut plist = PlistData::default();
+        let mut picker = Picker::new(FormField::LimitLoadToSessionType, &plist);
+        assert!(!picker.handle_key(KeyEvent::from(KeyCode::Char(' '))));
+        picker.apply(&mut plist);
+        assert!(matches!(
+            &plist.limit_load_to_session_type,
+            Some(LimitLoadToSessionType::Single(s)) if s == "Aqua"
+        ));
+
+        picker.handle_key(KeyEvent::from(KeyCode::Char('j')));
+        picker.handle_key(KeyEvent::from(KeyCode::Char(' ')));
+        assert!(picker.handle_key(KeyEvent::from(KeyCode::Enter)));
+        picker.apply(&mut plist);
+        assert!(matches!(
+            &plist.limit_load_to_session_type,
+            Some(LimitLoadToSessionType::Multiple(v)) if v == &["Aqua", "Background"]
+        ));
+    }
 
-        if let Some(env_vars) = &plist.environment_variables {
-            xml.push_str("    <key>EnvironmentVariables</key>\n");
-            xml.push_str("    <dict>\n");
-            for (key, value) in env_vars {
-                xml.push_str(&format!("        <key>{}</key>\n", Self::xml_escape(key)));
-                xml.push_str(&format!("        <string>{}</string>\n", Self::xml_escape(value)));
-            }
-            xml.push_str("    </dict>\n");
-            xml.push_str("    \n");
-        }
+    #[test]
+    fn test_interval_validation() {
+        let mut plist = PlistData {
+            start_interval: Some(60),
+            ..Default::default()
+        };
+        let field = FormField::StartInterval;
+        assert!(plist.set_field_text(&field, "5m").is_err());
+        assert!(plist.set_field_text(&field, "-1").is_err());
+        assert!(plist.set_field_text(&field, "2147483648").is_err());
+        // Rejected values leave the field alone
+        assert_eq!(plist.start_interval, Some(60));
+
+        plist.set_field_text(&field, " 2147483647 ").unwrap();
+        assert_eq!(plist.start_interval, Some(i32::MAX));
+        plist.set_field_text(&field, "").unwrap();
+        assert_eq!(plist.start_interval, None);
+    }
+
+    #[test]
+    fn test_field_text_validation() {
+        let mut plist = PlistData::default();
+        assert!(plist.set_field_text(&FormField::Label, "  ").is_err());
+        assert!(plist.set_field_text(&FormField::RunAtLoad, "yes").is_err());
+        assert!(
+            plist
+                .set_field_text(&FormField::EnvironmentVariables, "PATH=/bin\nBROKEN")
+                .is_err()
+        );
+        assert!(
+            plist
+                .set_field_text(&FormField::EnvironmentVariables, "=value")
+                .is_err()
+        );
+        assert_eq!(plist.environment_variables, None);
 
-        xml.push_str("</dict>\n");
-        xml.push_str("</plist>\n");
-        Ok(xml)
+        plist
+            .set_field_text(&FormField::EnvironmentVariables, "A=1=2\n\nB=")
+            .unwrap();
+        assert_eq!(
+            plist.environment_variables,
+            Some(vec![
+                ("A".to_string(), "1=2".to_string()),
+                ("B".to_string(), String::new()),
+            ])
+        );
     }
 
-    fn refresh_log_content(&mut self) {
-        let path: Option<String> = match self.log_view {
-            LogView::Stdout => self
-                .selected_plist
-                .as_ref()
-                .and_then(|p| p.standard_out_path.as_deref().map(|s| s.to_string())),
-            LogView::Stderr => self
-                .selected_plist
-                .as_ref()
-                .and_then(|p| p.standard_error_path.as_deref().map(|s| s.to_string())),
+    #[test]
+    fn test_environment_values_are_kept_verbatim() {
+        let mut plist = PlistData::default();
+        plist
+            .set_field_text(
+                &FormField::EnvironmentVariables,
+                "GREETING=  hi there \nZ=1",
+            )
+            .unwrap();
+        assert_eq!(
+            plist.environment_variables,
+            Some(vec![
+                ("GREETING".to_string(), "  hi there ".to_string()),
+                ("Z".to_string(), "1".to_string()),
+            ])
+        );
+        assert_eq!(
+            plist.field_text(&FormField::EnvironmentVariables),
+            "GREETING=  hi there \nZ=1"
+        );
+    }
+
+    #[test]
+    fn test_environment_order_survives_round_trip() {
+        let plist = PlistData {
+            label: Some("com.example.env".to_string()),
+            environment_variables: Some(vec![
+                ("ZETA".to_string(), "last & <first>".to_string()),
+                ("ALPHA".to_string(), " padded ".to_string()),
+                ("MIDDLE".to_string(), "\"quoted\"".to_string()),
+            ]),
+            ..Default::default()
         };
+        let xml = App::plist_to_xml(&plist).unwrap();
+        let zeta = xml.find("<key>ZETA</key>").unwrap();
+        let alpha = xml.find("<key>ALPHA</key>").unwrap();
+        let middle = xml.find("<key>MIDDLE</key>").unwrap();
+        assert!(zeta < alpha && alpha < middle);
+
+        let parsed = parse_plist_xml(&xml).unwrap();
+        assert_eq!(parsed.environment_variables, plist.environment_variables);
+        assert_eq!(App::plist_to_xml(&parsed).unwrap(), xml);
+    }
 
-        self.log_lines = match path {
-            Some(ref p) if std::path::Path::new(p).exists() => {
-                let content = fs::read_to_string(p).unwrap_or_default();
-                const MAX_LINES: usize = 10_000;
-                let all: Vec<String> = content.lines().map(|l| l.to_string()).collect();
-                let start = all.len().saturating_sub(MAX_LINES);
-                all[start..].to_vec()
-            }
-            _ => Vec::new(),
+    #[test]
+    fn test_duplicate_environment_names() {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0">
+<dict>
+    <key>EnvironmentVariables</key>
+    <dict>
+        <key>PATH</key>
+        <string>/usr/bin</string>
+        <key>HOME</key>
+        <string>/tmp</string>
+        <key>PATH</key>
+        <string>/opt/bin</string>
+    </dict>
+</dict>
+</plist>"#;
+        let plist = parse_plist_xml(content).unwrap();
+        let env_vars = plist.environment_variables.unwrap();
+        assert_eq!(env_vars.len(), 3);
+        assert_eq!(duplicate_env_names(&env_vars), vec!["PATH"]);
+    }
+
+    #[test]
+    fn test_list_editor_rejects_empty_variable_names() {
+        let mut editor = ListEditor::new(FormField::EnvironmentVariables, &PlistData::default());
+        editor.handle_key(KeyEvent::from(KeyCode::Char('a')));
+        editor.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert!(editor.error.is_some());
+        assert!(editor.input.is_some());
+        assert_eq!(editor.column, 0);
+    }
+
+    #[test]
+    fn test_command_line_splits_into_arguments() {
+        let mut plist = PlistData {
+            program_arguments: Some(vec!["/bin/echo".to_string(), "two words".to_string()]),
+            ..Default::default()
         };
+        let mut editor = ListEditor::new(FormField::ProgramArguments, &plist);
+        editor.handle_key(KeyEvent::from(KeyCode::Char('c')));
+        assert_eq!(
+            editor.input.as_ref().unwrap().text(),
+            "/bin/echo 'two words'"
+        );
+
+        editor.handle_key(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL));
+        editor.paste("/usr/bin/rsync -a \\\n  \"$HOME/My Files\" 'it'\\''s'\n");
+        assert!(editor.handle_key(KeyEvent::from(KeyCode::Enter)));
+        editor.apply(&mut plist);
+        assert_eq!(
+            plist.program_arguments,
+            Some(vec![
+                "/usr/bin/rsync".to_string(),
+                "-a".to_string(),
+                "$HOME/My Files".to_string(),
+                "it's".to_string(),
+            ])
+        );
+
+        editor.handle_key(KeyEvent::from(KeyCode::Char('c')));
+        editor.paste(" 'unterminated");
+        editor.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert!(editor.error.is_some());
+        assert!(editor.command_line);
+    }
+
+    #[test]
+    fn test_command_line_paste_with_carriage_returns() {
+        let mut plist = PlistData::default();
+        let mut editor = ListEditor::new(FormField::ProgramArguments, &plist);
+        editor.handle_key(KeyEvent::from(KeyCode::Char('c')));
+        editor.paste("/bin/echo \\\r\n  one \\\r  two\r\n");
+        assert!(editor.handle_key(KeyEvent::from(KeyCode::Enter)));
+        editor.apply(&mut plist);
+        assert_eq!(
+            plist.program_arguments,
+            Some(vec![
+                "/bin/echo".to_string(),
+                "one".to_string(),
+                "two".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00-00-00Z");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29T00-00-00Z");
+        assert_eq!(format_timestamp(1_755_163_800), "2025-08-14T09-30-00Z");
+    }
+
+    #[test]
+    fn test_backup_display_name() {
+        assert_eq!(
+            backup_display_name(Path::new("/b/2025-08-14T09-30-00Z.plist")),
+            "2025-08-14 09:30:00 UTC"
+        );
+        assert_eq!(
+            backup_display_name(Path::new("/b/2025-08-14T09-30-00Z.1.plist")),
+            "2025-08-14 09:30:00 UTC"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_marks_changes() {
+        let diff = unified_diff("a\nb\nc\n", "a\nB\nc\n");
+        assert_eq!(
+            diff,
+            vec![
+                (DiffKind::Hunk, "@@ -1,3 +1,3 @@".to_string()),
+                (DiffKind::Context, " a".to_string()),
+                (DiffKind::Removed, "-b".to_string()),
+                (DiffKind::Added, "+B".to_string()),
+                (DiffKind::Context, " c".to_string()),
+            ]
+        );
+        assert!(unified_diff("same\n", "same\n").is_empty());
+    }
+
+    #[test]
+    fn test_example_plist_file() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>com.user.price-checker-eth</string>
+    
+    <key>ProgramArguments</key>
+    <array>
+        <string>/Users/dev/.local/bin/price-checker-eth</string>
+    </array>
+    
+    <key>StartInterval</key>
+    <integer>600</integer>
+    
+    <key>RunAtLoad</key>
+    <true/>
+    
+    <key>KeepAlive</key>
+    <false/>
+    
+    <key>StandardOutPath</key>
+    <string>/Users/dev/Documents/github.com/hollanddd/price-checker-eth/logs/price-checker-eth.log</string>
+    
+    <key>StandardErrorPath</key>
+    <string>/Users/dev/Documents/github.com/hollanddd/price-checker-eth/logs/price-checker-eth.error.log</string>
+    
+    <key>WorkingDirectory</key>
+    <string>/Users/dev/Documents/github.com/hollanddd/price-checker-eth</string>
+</dict>
+</plist>"#;
+
+        let parsed = parse_plist_xml(xml).unwrap();
+
+        assert_eq!(parsed.label, Some("com.user.price-checker-eth".to_string()));
+        assert_eq!(
+            parsed.program_arguments,
+            Some(vec!["/Users/dev/.local/bin/price-checker-eth".to_string()])
+        );
+        assert_eq!(parsed.start_interval, Some(600));
+        assert_eq!(parsed.run_at_load, Some(true));
+        assert_eq!(parsed.keep_alive, Some(false));
+        assert_eq!(parsed.standard_out_path, Some("/Users/dev/Documents/github.com/hollanddd/price-checker-eth/logs/price-checker-eth.log".to_string()));
+        assert_eq!(parsed.standard_error_path, Some("/Users/dev/Documents/github.com/hollanddd/price-checker-eth/logs/price-checker-eth.error.log".to_string()));
+        assert_eq!(
+            parsed.working_directory,
+            Some("/Users/dev/Documents/github.com/hollanddd/price-checker-eth".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_plist_value_tree() {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Zeta</key><string>a &amp; b &lt;c&gt; &#x41;</string>
+    <key>Alpha</key>
+    <array>
+        <integer>-3</integer>
+        <real>1.5</real>
+        <false/>
+        <dict/>
+    </array>
+</dict>
+</plist>"#;
+        let value = parse_plist_value(content).unwrap();
+        assert_eq!(
+            value,
+            PlistValue::Dict(vec![
+                (
+                    "Zeta".to_string(),
+                    PlistValue::String("a & b <c> A".to_string())
+                ),
+                (
+                    "Alpha".to_string(),
+                    PlistValue::Array(vec![
+                        PlistValue::Integer(-3),
+                        PlistValue::Real(1.5),
+                        PlistValue::Boolean(false),
+                        PlistValue::Dict(Vec::new()),
+                    ])
+                ),
+            ])
+        );
+        assert!(parse_plist_value("<dict><string>x</string></dict>").is_err());
+        assert!(parse_plist_value("<dict><key>A</key>").is_err());
+    }
 
-        self.log_scroll_offset = 0;
+    #[test]
+    fn test_unmodeled_keys_survive_save() {
+        let content = r#"<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>com.example.extra</string>
+    <key>KeepAlive</key>
+    <dict>
+        <key>SuccessfulExit</key>
+        <false/>
+    </dict>
+    <key>StartInterval</key>
+    <string>60</string>
+    <key>WatchPaths</key>
+    <array>
+        <string>/tmp/watched</string>
+    </array>
+</dict>
+</plist>"#;
+        let plist = parse_plist_xml(content).unwrap();
+        assert_eq!(plist.keep_alive, None);
+        assert_eq!(plist.start_interval, None);
+        let keys: Vec<&str> = plist.extra.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, ["KeepAlive", "StartInterval", "WatchPaths"]);
+
+        let reparsed = parse_plist_xml(&App::plist_to_xml(&plist).unwrap()).unwrap();
+        assert_eq!(reparsed, plist);
+
+        // Setting the field in the form replaces the mistyped value
+        let mut edited = plist.clone();
+        edited.start_interval = Some(60);
+        let xml = App::plist_to_xml(&edited).unwrap();
+        assert_eq!(xml.matches("<key>StartInterval</key>").count(), 1);
+        assert!(xml.contains("<integer>60</integer>"));
     }
 
-    fn handle_log_keys(&mut self, key: KeyEvent) -> Result<()> {
-        match key.code {
-            KeyCode::Char('j') | KeyCode::Down => {
-                self.log_scroll_offset = self.log_scroll_offset.saturating_add(1);
-            }
-            KeyCode::Char('k') | KeyCode::Up => {
-                self.log_scroll_offset = self.log_scroll_offset.saturating_sub(1);
-            }
-            KeyCode::Char('g') => {
-                self.log_scroll_offset = 0;
-            }
-            KeyCode::Char('G') => {
-                self.log_scroll_offset = self.log_lines.len().saturating_sub(20) as u16;
-            }
-            KeyCode::PageDown => {
-                self.log_scroll_offset = self.log_scroll_offset.saturating_add(10);
-            }
-            KeyCode::PageUp => {
-                self.log_scroll_offset = self.log_scroll_offset.saturating_sub(10);
-            }
-            KeyCode::Char('t') => {
-                self.log_view = match self.log_view {
-                    LogView::Stdout => LogView::Stderr,
-                    LogView::Stderr => LogView::Stdout,
-                };
-                self.refresh_log_content();
-            }
-            KeyCode::Char('r') => {
-                self.refresh_log_content();
-                self.set_status_message("✓ Log refreshed".to_string());
-            }
-            _ => {}
-        }
-        Ok(())
+    fn lint_ids(plist: &PlistData, filename: Option<&str>) -> Vec<&'static str> {
+        lint_plist(plist, filename)
+            .into_iter()
+            .map(|diagnostic| diagnostic.rule)
+            .collect()
     }
 
-    fn draw_log_panel(&mut self, frame: &mut Frame, area: Rect) {
-        let (border_style, title_style) = (
-            Style::default().fg(Theme::BORDER_FOCUSED),
-            Style::default()
-                .fg(Theme::ACCENT_PRIMARY)
-                .add_modifier(Modifier::BOLD),
+    #[test]
+    fn test_lint_clean_plist() {
+        let plist = PlistData {
+            label: Some("com.example.clean".to_string()),
+            program_arguments: Some(vec!["/usr/bin/true".to_string()]),
+            standard_out_path: Some("/tmp/clean.log".to_string()),
+            start_interval: Some(300),
+            ..Default::default()
+        };
+        assert!(lint_plist(&plist, Some("com.example.clean.plist")).is_empty());
+    }
+
+    #[test]
+    fn test_lint_rules() {
+        assert_eq!(
+            lint_ids(&PlistData::default(), None),
+            ["missing-label", "missing-program"]
         );
 
-        let log_path: Option<String> = match self.log_view {
-            LogView::Stdout => self
-                .selected_plist
-                .as_ref()
-                .and_then(|p| p.standard_out_path.as_deref().map(|s| s.to_string())),
-            LogView::Stderr => self
-                .selected_plist
-                .as_ref()
-                .and_then(|p| p.standard_error_path.as_deref().map(|s| s.to_string())),
+        let base = PlistData {
+            label: Some("com.example.job".to_string()),
+            program_arguments: Some(vec!["/usr/bin/env".to_string()]),
+            ..Default::default()
         };
+        assert_eq!(
+            lint_ids(&base, Some("job.plist")),
+            ["label-filename-mismatch"]
+        );
 
-        let stdout_style = if self.log_view == LogView::Stdout {
-            Style::default()
-                .fg(Theme::ACCENT_SECONDARY)
-                .add_modifier(Modifier::BOLD)
-        } else {
-            Style::default().fg(Theme::TEXT_DIM)
-        };
-        let stderr_style = if self.log_view == LogView::Stderr {
-            Style::default()
-                .fg(Theme::ACCENT_ERROR)
-                .add_modifier(Modifier::BOLD)
-        } else {
-            Style::default().fg(Theme::TEXT_DIM)
+        let plist = PlistData {
+            program: Some("/usr/bin/python3".to_string()),
+            start_interval: Some(60),
+            keep_alive: Some(true),
+            throttle_interval: Some(1),
+            ..base.clone()
         };
+        assert_eq!(
+            lint_ids(&plist, None),
+            [
+                "program-mismatch",
+                "interval-with-keepalive",
+                "short-throttle-interval"
+            ]
+        );
 
-        let line_count = self.log_lines.len();
-        let count_suffix = if line_count > 0 {
-            format!("  ({} lines)", line_count)
-        } else {
-            String::new()
+        let plist = PlistData {
+            program_arguments: Some(vec!["bin/run.sh".to_string()]),
+            standard_error_path: Some("~/Library/Logs/job.log".to_string()),
+            ..base.clone()
         };
+        let diagnostics = lint_plist(&plist, None);
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics.iter().all(|d| d.rule == "relative-path"));
+        assert_eq!(diagnostics[0].key.as_deref(), Some("StandardErrorPath"));
+        assert_eq!(diagnostics[1].key.as_deref(), Some("ProgramArguments"));
+
+        // A bare command name is looked up in PATH, which is fine
+        let plist = PlistData {
+            program_arguments: Some(vec!["say".to_string()]),
+            ..base
+        };
+        assert!(lint_plist(&plist, None).is_empty());
+    }
 
-        let title_line = Line::from(vec![
-            Span::styled("📜 Logs  ", title_style),
-            Span::styled("stdout", stdout_style),
-            Span::styled(" | ", Style::default().fg(Theme::SUBTLE)),
-            Span::styled("stderr", stderr_style),
-            Span::styled(count_suffix, Style::default().fg(Theme::TEXT_DIM)),
-        ]);
-
-        let text: Vec<Line> = if self.selected_plist.is_none() {
-            vec![
-                Line::from(""),
-                Line::from(vec![Span::styled(
-                    "Select an agent from the sidebar to view logs",
-                    Style::default()
-                        .fg(Theme::TEXT_DIM)
-                        .add_modifier(Modifier::ITALIC),
-                )]),
+    #[test]
+    fn test_lint_unknown_keys_and_wrong_types() {
+        let content = r#"<dict>
+    <key>Label</key>
+    <string>com.example.types</string>
+    <key>Program</key>
+    <string>/usr/bin/true</string>
+    <key>RunAtLoad</key>
+    <string>yes</string>
+    <key>EnvironmentVariables</key>
+    <dict>
+        <key>DEBUG</key>
+        <integer>1</integer>
+    </dict>
+    <key>RunAtStartup</key>
+    <true/>
+    <key>Nice</key>
+    <integer>5</integer>
+</dict>"#;
+        let diagnostics = lint_plist(&parse_plist_xml(content).unwrap(), None);
+        let found: Vec<(&str, Option<&str>, Severity)> = diagnostics
+            .iter()
+            .map(|d| (d.rule, d.key.as_deref(), d.severity))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("wrong-type", Some("RunAtLoad"), Severity::Error),
+                ("wrong-type", Some("EnvironmentVariables"), Severity::Error),
+                ("unknown-key", Some("RunAtStartup"), Severity::Warning),
             ]
-        } else {
-            match log_path {
-                None => {
-                    let label = match self.log_view {
-                        LogView::Stdout => "StandardOutPath",
-                        LogView::Stderr => "StandardErrorPath",
-                    };
-                    vec![
-                        Line::from(""),
-                        Line::from(vec![Span::styled(
-                            format!("No {} configured for this agent", label),
-                            Style::default()
-                                .fg(Theme::TEXT_DIM)
-                                .add_modifier(Modifier::ITALIC),
-                        )]),
-                        Line::from(""),
-                        Line::from(vec![Span::styled(
-                            "Set the path in the agent editor (Tab to switch panels)",
-                            Style::default().fg(Theme::SUBTLE),
-                        )]),
-                    ]
-                }
-                Some(ref path) => {
-                    if self.log_lines.is_empty() {
-                        if std::path::Path::new(path).exists() {
-                            vec![
-                                Line::from(vec![Span::styled(
-                                    path.as_str(),
-                                    Style::default().fg(Theme::SUBTLE),
-                                )]),
-                                Line::from(""),
-                                Line::from(vec![Span::styled(
-                                    "Log file is empty",
-                                    Style::default()
-                                        .fg(Theme::TEXT_DIM)
-                                        .add_modifier(Modifier::ITALIC),
-                                )]),
-                            ]
-                        } else {
-                            vec![
-                                Line::from(vec![Span::styled(
-                                    "Log file not found:",
-                                    Style::default().fg(Theme::ACCENT_ERROR),
-                                )]),
-                                Line::from(""),
-                                Line::from(vec![Span::styled(
-                                    path.as_str(),
-                                    Style::default().fg(Theme::SUBTLE),
-                                )]),
-                            ]
-                        }
-                    } else {
-                        let mut lines: Vec<Line> = vec![
-                            Line::from(vec![Span::styled(
-                                path.as_str(),
-                                Style::default().fg(Theme::SUBTLE),
-                            )]),
-                            Line::from(""),
-                        ];
-                        lines.extend(self.log_lines.iter().enumerate().map(|(i, line)| {
-                            Line::from(vec![
-                                Span::styled(
-                                    format!("{:5} │ ", i + 1),
-                                    Style::default().fg(Theme::SUBTLE),
-                                ),
-                                Span::styled(line.clone(), Style::default().fg(Theme::FOREGROUND)),
-                            ])
-                        }));
-                        lines
-                    }
-                }
-            }
-        };
+        );
+    }
 
-        let paragraph = Paragraph::new(text)
-            .block(
-                Block::default()
-                    .title(title_line)
-                    .borders(Borders::ALL)
-                    .border_type(BorderType::Rounded)
-                    .border_style(border_style)
-                    .style(Style::default().bg(Theme::BACKGROUND))
-                    .padding(ratatui::widgets::Padding::uniform(1)),
-            )
-            .scroll((self.log_scroll_offset, 0));
+    #[cfg(unix)]
+    #[test]
+    fn test_check_plist_paths() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("run.sh");
+        fs::write(&script, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o644)).unwrap();
+        let logs = dir.path().join("logs");
+        let plist = PlistData {
+            program_arguments: Some(vec![script.to_string_lossy().to_string()]),
+            working_directory: Some(dir.path().join("missing").to_string_lossy().to_string()),
+            standard_out_path: Some(logs.join("out.log").to_string_lossy().to_string()),
+            standard_error_path: Some(logs.join("out.log").to_string_lossy().to_string()),
+            ..Default::default()
+        };
+        let rules: Vec<&str> = check_plist_paths(&plist).iter().map(|d| d.rule).collect();
+        assert_eq!(
+            rules,
+            [
+                "program-not-executable",
+                "missing-working-directory",
+                "missing-log-directory"
+            ]
+        );
+        assert_eq!(missing_log_directories(&plist), vec![logs.clone()]);
 
-        frame.render_widget(paragraph, area);
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        fs::create_dir_all(&logs).unwrap();
+        let plist = PlistData {
+            working_directory: None,
+            ..plist
+        };
+        assert!(check_plist_paths(&plist).is_empty());
+        assert!(missing_log_directories(&plist).is_empty());
     }
 
-    fn quit(&mut self) {
-        self.running = false;
+    #[cfg(unix)]
+    #[test]
+    fn test_programs_resolve_against_launchd_path() {
+        let sh = resolve_program("sh").unwrap();
+        assert!(
+            LAUNCHD_PATH
+                .split(':')
+                .any(|dir| sh.parent() == Some(Path::new(dir)))
+        );
+        let plist = PlistData {
+            program_arguments: Some(vec!["lam-no-such-command".to_string()]),
+            ..Default::default()
+        };
+        let diagnostics = check_plist_paths(&plist);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].rule, "program-not-found");
+        assert!(diagnostics[0].message.contains(LAUNCHD_PATH));
+
+        let plist = PlistData {
+            program: Some("/nonexistent/lam/tool".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(check_plist_paths(&plist)[0].key.as_deref(), Some("Program"));
     }
-}
 
-fn parse_plist_xml(content: &str) -> Result<PlistData> {
-    let mut plist_data = PlistData::default();
-
-    let lines: Vec<&str> = content.lines().collect();
-    let mut i = 0;
-    let mut in_dict = false;
-    let mut current_key = String::new();
-    let mut program_args = Vec::new();
-    let mut bundle_identifiers = Vec::new();
-    let mut session_types = Vec::new();
-    let mut env_vars = std::collections::HashMap::new();
-    let mut collecting_array = false;
-    let mut collecting_env_dict = false;
-    let mut env_key = String::new();
-    let mut array_type = String::new();
-
-    while i < lines.len() {
-        let line = lines[i].trim();
-
-        if line == "<dict>" && !collecting_env_dict {
-            if current_key == "EnvironmentVariables" {
-                collecting_env_dict = true;
-            } else {
-                in_dict = true;
-            }
-        } else if line == "</dict>" {
-            if collecting_env_dict {
-                collecting_env_dict = false;
-                plist_data.environment_variables = Some(env_vars.clone());
-                current_key.clear();
-            } else {
-                in_dict = false;
-            }
-        } else if line == "<array>" {
-            collecting_array = true;
-            array_type = current_key.clone();
-            match array_type.as_str() {
-                "ProgramArguments" => program_args.clear(),
-                "AssociatedBundleIdentifiers" => bundle_identifiers.clear(),
-                "LimitLoadToSessionType" => session_types.clear(),
-                _ => {}
-            }
-        } else if line == "</array>" {
-            collecting_array = false;
-            match array_type.as_str() {
-                "ProgramArguments" => {
-                    plist_data.program_arguments = Some(program_args.clone());
-                }
-                "AssociatedBundleIdentifiers" => {
-                    plist_data.associated_bundle_identifiers = Some(bundle_identifiers.clone());
-                }
-                "LimitLoadToSessionType" => {
-                    plist_data.limit_load_to_session_type =
-                        Some(LimitLoadToSessionType::Multiple(session_types.clone()));
-                }
-                _ => {}
-            }
-            current_key.clear();
-        } else if collecting_env_dict && line.starts_with("<key>") && line.ends_with("</key>") {
-            env_key = line[5..line.len() - 6].to_string();
-        } else if collecting_env_dict && line.starts_with("<string>") && line.ends_with("</string>")
-        {
-            env_vars.insert(env_key.clone(), line[8..line.len() - 9].to_string());
-            env_key.clear();
-        } else if in_dict && line.starts_with("<key>") && line.ends_with("</key>") {
-            current_key = line[5..line.len() - 6].to_string();
-        } else if in_dict && !current_key.is_empty() {
-            match current_key.as_str() {
-                "Label" if line.starts_with("<string>") => {
-                    plist_data.label = Some(line[8..line.len() - 9].to_string());
-                }
-                "Program" if line.starts_with("<string>") => {
-                    plist_data.program = Some(line[8..line.len() - 9].to_string());
-                }
-                "StartInterval" | "ThrottleInterval" if line.starts_with("<integer>") => {
-                    if let Ok(val) = line[9..line.len() - 10].parse() {
-                        match current_key.as_str() {
-                            "StartInterval" => plist_data.start_interval = Some(val),
-                            "ThrottleInterval" => plist_data.throttle_interval = Some(val),
-                            _ => {}
-                        }
-                    }
-                }
-                "RunAtLoad"
-                | "KeepAlive"
-                | "AbandonProcessGroup"
-                | "EnablePressuredExit"
-                | "EnableTransactions"
-                | "EventMonitor" => {
-                    let value = line == "<true/>";
-                    match current_key.as_str() {
-                        "RunAtLoad" => plist_data.run_at_load = Some(value),
-                        "KeepAlive" => plist_data.keep_alive = Some(value),
-                        "AbandonProcessGroup" => plist_data.abandon_process_group = Some(value),
-                        "EnablePressuredExit" => plist_data.enable_pressured_exit = Some(value),
-                        "EnableTransactions" => plist_data.enable_transactions = Some(value),
-                        "EventMonitor" => plist_data.event_monitor = Some(value),
-                        _ => {}
-                    }
-                }
-                "StandardOutPath" | "StandardErrorPath" | "WorkingDirectory" | "POSIXSpawnType"
-                    if line.starts_with("<string>") =>
-                {
-                    let value = line[8..line.len() - 9].to_string();
-                    match current_key.as_str() {
-                        "StandardOutPath" => plist_data.standard_out_path = Some(value),
-                        "StandardErrorPath" => plist_data.standard_error_path = Some(value),
-                        "WorkingDirectory" => plist_data.working_directory = Some(value),
-                        "POSIXSpawnType" => plist_data.posix_spawn_type = Some(value),
-                        _ => {}
-                    }
-                }
-                "LimitLoadToSessionType" if line.starts_with("<string>") => {
-                    plist_data.limit_load_to_session_type = Some(LimitLoadToSessionType::Single(
-                        line[8..line.len() - 9].to_string(),
-                    ));
-                }
-                _ => {}
-            }
+    #[test]
+    fn test_quick_fixes_clear_their_diagnostics() {
+        let mut plist = PlistData {
+            label: Some("com.example.fix".to_string()),
+            program: Some("/usr/local/bin/tool".to_string()),
+            program_arguments: Some(vec!["tool-alias".to_string(), "--flag".to_string()]),
+            standard_out_path: Some("~/Library/Logs/fix.log".to_string()),
+            extra: vec![("RunAtStartup".to_string(), PlistValue::Boolean(true))],
+            ..Default::default()
+        };
+        let fixes: Vec<QuickFix> = lint_plist(&plist, Some("fix.plist"))
+            .into_iter()
+            .filter_map(|diagnostic| diagnostic.fix)
+            .collect();
+        assert_eq!(
+            fixes,
+            [
+                QuickFix::MergeProgramIntoArguments,
+                QuickFix::RenameFile("com.example.fix.plist".to_string()),
+                QuickFix::ExpandTilde("StandardOutPath"),
+                QuickFix::RemoveKey("RunAtStartup".to_string()),
+            ]
+        );
+
+        for fix in &fixes {
+            assert_eq!(
+                fix.apply(&mut plist),
+                !matches!(fix, QuickFix::RenameFile(_))
+            );
+        }
+        assert_eq!(plist.program, None);
+        assert_eq!(
+            plist.program_arguments,
+            Some(vec![
+                "/usr/local/bin/tool".to_string(),
+                "--flag".to_string()
+            ])
+        );
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(
+            plist.standard_out_path,
+            Some(
+                home.join("Library/Logs/fix.log")
+                    .to_string_lossy()
+                    .to_string()
+            )
+        );
+        assert!(plist.extra.is_empty());
+        assert!(lint_plist(&plist, Some("com.example.fix.plist")).is_empty());
+    }
 
-            if collecting_array && line.starts_with("<string>") && line.ends_with("</string>") {
-                let value = line[8..line.len() - 9].to_string();
-                match array_type.as_str() {
-                    "ProgramArguments" => program_args.push(value),
-                    "AssociatedBundleIdentifiers" => bundle_identifiers.push(value),
-                    "LimitLoadToSessionType" => session_types.push(value),
-                    _ => {}
-                }
-            }
+    #[test]
+    fn test_expand_tilde_only_handles_home() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(expand_tilde("~"), Some(home.display().to_string()));
+        assert_eq!(expand_tilde("~other/logs"), None);
+        assert_eq!(expand_tilde("/tmp/~"), None);
+
+        let plist = PlistData {
+            label: Some("com.example.user".to_string()),
+            program: Some("~other/bin/tool".to_string()),
+            ..Default::default()
+        };
+        let diagnostics = lint_plist(&plist, None);
+        assert_eq!(diagnostics[0].rule, "relative-path");
+        assert_eq!(diagnostics[0].fix, None);
+    }
 
-            if !collecting_array && !collecting_env_dict {
-                current_key.clear();
-            }
-        }
-        i += 1;
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
     }
 
-    Ok(plist_data)
-}
+    #[test]
+    fn test_parse_lint_args() {
+        let options = parse_lint_args(&args(&[
+            "--format=json",
+            "--severity",
+            "error",
+            "a.plist",
+            "dir",
+        ]))
+        .unwrap();
+        assert_eq!(options.format, LintFormat::Json);
+        assert_eq!(options.fail_on, Severity::Error);
+        assert!(!options.check_paths);
+        assert_eq!(
+            options.paths,
+            [PathBuf::from("a.plist"), PathBuf::from("dir")]
+        );
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        assert!(parse_lint_args(&args(&["--check-paths"])).is_err());
+        assert!(parse_lint_args(&args(&["--format", "xml", "a.plist"])).is_err());
+        assert!(parse_lint_args(&args(&["--format"])).is_err());
+    }
 
     #[test]
-    fn test_parse_label_element() {
-        let xml = r#"<dict>
+    fn test_lint_directory_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("nested");
+        fs::create_dir(&nested).unwrap();
+        fs::write(
+            dir.path().join("com.example.good.plist"),
+            App::plist_to_xml(&PlistData {
+                label: Some("com.example.good".to_string()),
+                program_arguments: Some(vec!["/usr/bin/true".to_string()]),
+                ..Default::default()
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        fs::write(
+            nested.join("bad.plist"),
+            "<dict>\n    <key>Label</key>\n    <integer>1</integer>\n</dict>\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let mut paths = Vec::new();
+        collect_plist_files(dir.path(), &mut paths).unwrap();
+        assert_eq!(paths.len(), 2);
+        let files: Vec<LintedFile> = paths.iter().map(|path| lint_file(path, false)).collect();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&format_lint_report(&files, LintFormat::Json)).unwrap();
+        let rules: Vec<&str> = json
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|result| result["rule"].as_str().unwrap())
+            .collect();
+        assert_eq!(rules, ["missing-label", "missing-program", "wrong-type"]);
+        assert_eq!(json[2]["line"], 2);
+
+        let sarif: serde_json::Value =
+            serde_json::from_str(&format_lint_report(&files, LintFormat::Sarif)).unwrap();
+        assert_eq!(sarif["runs"][0]["results"].as_array().unwrap().len(), 3);
+        assert_eq!(
+            sarif["runs"][0]["results"][2]["locations"][0]["physicalLocation"]["region"]["startLine"],
+            2
+        );
+
+        let checkstyle = format_lint_report(&files, LintFormat::Checkstyle);
+        assert_eq!(checkstyle.matches("<file ").count(), 2);
+        assert!(checkstyle.contains("source=\"lam.wrong-type\""));
+
+        let human = format_lint_report(&files, LintFormat::Human);
+        assert!(human.ends_with("✗ 3 errors and 0 warnings in 2 files\n"));
+
+        assert!(collect_plist_files(&dir.path().join("missing"), &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_format_plist_default_style() {
+        let xml = r#"<plist version="1.0"><dict>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>Custom</key>
+    <string>x</string>
     <key>Label</key>
     <string>com.user.test</string>
-</dict>"#;
+    <key>ProgramArguments</key>
+    <array><string>/bin/echo</string></array>
+</dict></plist>"#;
+        let formatted = format_plist(&parse_plist_value(xml).unwrap(), &FormatStyle::default());
 
-        let parsed = parse_plist_xml(xml).unwrap();
-        assert_eq!(parsed.label, Some("com.user.test".to_string()));
+        assert_eq!(
+            formatted,
+            format!(
+                "{}<dict>\n\t<key>Label</key>\n\t<string>com.user.test</string>\n\t<key>ProgramArguments</key>\n\t<array>\n\t\t<string>/bin/echo</string>\n\t</array>\n\t<key>RunAtLoad</key>\n\t<true/>\n\t<key>Custom</key>\n\t<string>x</string>\n</dict>\n</plist>\n",
+                PLIST_HEADER
+            )
+        );
+        let value = parse_plist_value(&formatted).unwrap();
+        assert_eq!(format_plist(&value, &FormatStyle::default()), formatted);
     }
 
     #[test]
-    fn test_parse_start_interval_element() {
-        let xml = r#"<dict>
-    <key>StartInterval</key>
-    <integer>300</integer>
-</dict>"#;
+    fn test_format_plist_alphabetical_with_blank_lines() {
+        let xml = "<dict><key>b</key><integer>2</integer><key>a</key><dict><key>z</key><string>1</string><key>y</key><string>2</string></dict></dict>";
+        let style = FormatStyle {
+            indent: Indent::Spaces(2),
+            key_order: KeyOrder::Alphabetical,
+            blank_lines: true,
+        };
+        let formatted = format_plist(&parse_plist_value(xml).unwrap(), &style);
 
-        let parsed = parse_plist_xml(xml).unwrap();
-        assert_eq!(parsed.start_interval, Some(300));
+        // Nested dictionaries keep their order
+        assert!(formatted.ends_with(
+            "<dict>\n  <key>a</key>\n  <dict>\n    <key>z</key>\n    <string>1</string>\n    <key>y</key>\n    <string>2</string>\n  </dict>\n\n  <key>b</key>\n  <integer>2</integer>\n</dict>\n</plist>\n"
+        ));
     }
 
     #[test]
-    fn test_parse_boolean_true_element() {
+    fn test_to_value_keeps_file_order() {
         let xml = r#"<dict>
     <key>RunAtLoad</key>
     <true/>
+    <key>Custom</key>
+    <string>x</string>
+    <key>Label</key>
+    <string>com.user.test</string>
 </dict>"#;
+        let mut plist = parse_plist_xml(xml).unwrap();
+        plist.keep_alive = Some(true);
+        let style = FormatStyle {
+            key_order: KeyOrder::Preserve,
+            ..FormatStyle::default()
+        };
+        let PlistValue::Dict(entries) = plist.to_value() else {
+            panic!("expected a dict");
+        };
+        let keys: Vec<&str> = entries.iter().map(|(key, _)| key.as_str()).collect();
 
-        let parsed = parse_plist_xml(xml).unwrap();
-        assert_eq!(parsed.run_at_load, Some(true));
+        assert_eq!(keys, ["RunAtLoad", "Custom", "Label", "KeepAlive"]);
+        assert_eq!(
+            parse_plist_xml(&format_plist(&plist.to_value(), &style))
+                .unwrap()
+                .to_value(),
+            plist.to_value()
+        );
     }
 
     #[test]
-    fn test_parse_boolean_false_element() {
-        let xml = r#"<dict>
-    <key>KeepAlive</key>
-    <false/>
-</dict>"#;
+    fn test_config_format_table() {
+        let config: Config =
+            toml::from_str("[format]\nindent = 4\nkey_order = \"alphabetical\"\n").unwrap();
+        assert_eq!(config.format.indent, Indent::Spaces(4));
+        assert_eq!(config.format.key_order, KeyOrder::Alphabetical);
+        assert!(!config.format.blank_lines);
+
+        let config: Config = toml::from_str("[format]\nindent = \"tab\"\n").unwrap();
+        assert_eq!(config.format.indent, Indent::Tab);
+        assert!(toml::from_str::<Config>("[format]\nindent = 0\n").is_err());
+        assert!(toml::from_str::<Config>("[format]\nsort = true\n").is_err());
+    }
 
-        let parsed = parse_plist_xml(xml).unwrap();
-        assert_eq!(parsed.keep_alive, Some(false));
+    #[test]
+    fn test_parse_fmt_args() {
+        let options = parse_fmt_args(
+            &args(&[
+                "--check",
+                "--indent=2",
+                "--key-order",
+                "preserve",
+                "--blank-lines",
+                "a.plist",
+            ]),
+            FormatStyle::default(),
+        )
+        .unwrap();
+
+        assert!(options.check);
+        assert_eq!(
+            options.style,
+            FormatStyle {
+                indent: Indent::Spaces(2),
+                key_order: KeyOrder::Preserve,
+                blank_lines: true,
+            }
+        );
+        assert_eq!(options.paths, [PathBuf::from("a.plist")]);
+        assert!(parse_fmt_args(&args(&["--key-order=random"]), FormatStyle::default()).is_err());
+        assert!(parse_fmt_args(&[], FormatStyle::default()).is_err());
     }
 
     #[test]
-    fn test_parse_program_arguments_array() {
-        let xml = r#"<dict>
+    fn test_layout_keeps_source_style() {
+        let original = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\r\n<!DOCTYPE plist PUBLIC \"-//Apple Computer//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\r\n<plist version=\"1.0\">\r\n<dict>\r\n\t<key>RunAtLoad</key>\r\n\t<false/>\r\n\r\n\t<key>Label</key>\r\n\t<string>com.user.test</string>\r\n\r\n\t<key>ProgramArguments</key>\r\n\t<array>\r\n\t\t<string>/bin/echo</string>\r\n\t</array>\r\n</dict>\r\n</plist>\r\n";
+        let layout = PlistLayout::detect(original).unwrap();
+        assert_eq!(layout.indent, "\t");
+        assert_eq!(layout.blank_line.as_deref(), Some(""));
+        assert!(layout.crlf && !layout.trailing_blank_line);
+
+        let mut plist = parse_plist_xml(original).unwrap();
+        assert_eq!(layout.write(&plist.to_value()), original);
+
+        plist.run_at_load = Some(true);
+        let saved = layout.write(&plist.to_value());
+        let changed: Vec<(&str, &str)> = original
+            .split("\r\n")
+            .zip(saved.split("\r\n"))
+            .filter(|(before, after)| before != after)
+            .collect();
+        assert_eq!(changed, [("\t<false/>", "\t<true/>")]);
+    }
+
+    #[test]
+    fn test_layout_round_trips_plist_to_xml() {
+        let plist = PlistData {
+            label: Some("com.user.test".to_string()),
+            program: Some("/bin/echo".to_string()),
+            keep_alive: Some(true),
+            ..Default::default()
+        };
+        let xml = App::plist_to_xml(&plist).unwrap();
+        let layout = PlistLayout::detect(&xml).unwrap();
+
+        assert_eq!(layout.blank_line.as_deref(), Some("    "));
+        assert!(layout.trailing_blank_line);
+        assert_eq!(
+            layout.write(&parse_plist_xml(&xml).unwrap().to_value()),
+            xml
+        );
+        assert_eq!(
+            PlistLayout::detect("<plist><string>x</string></plist>"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_diff_plist_values() {
+        let old = parse_plist_value(
+            r#"<dict>
+    <key>Label</key><string>com.user.test</string>
     <key>ProgramArguments</key>
-    <array>
-        <string>/usr/bin/test</string>
-        <string>--flag</string>
-    </array>
-</dict>"#;
+    <array><string>/bin/echo</string><string>hello</string></array>
+    <key>EnvironmentVariables</key>
+    <dict><key>PATH</key><string>/bin</string><key>HOME</key><string>/tmp</string></dict>
+    <key>RunAtLoad</key><true/>
+</dict>"#,
+        )
+        .unwrap();
+        let new = parse_plist_value(
+            r#"<dict>
+	<key>EnvironmentVariables</key>
+	<dict><key>HOME</key><string>/tmp</string><key>PATH</key><string>/usr/bin</string></dict>
+	<key>KeepAlive</key><true/>
+	<key>ProgramArguments</key>
+	<array><string>/bin/echo</string><string>-n</string><string>hello</string></array>
+	<key>Label</key><string>com.user.test</string>
+</dict>"#,
+        )
+        .unwrap();
+        let mut changes = Vec::new();
+        diff_plist_values("", &old, &new, &mut changes);
+        let described: Vec<String> = changes
+            .iter()
+            .map(|change| format!("{} {}", change.kind.sign(), change.describe()))
+            .collect();
 
-        let parsed = parse_plist_xml(xml).unwrap();
         assert_eq!(
-            parsed.program_arguments,
-            Some(vec!["/usr/bin/test".to_string(), "--flag".to_string()])
+            described,
+            [
+                "+ ProgramArguments[1]: \"-n\"",
+                "~ EnvironmentVariables.PATH: \"/bin\" → \"/usr/bin\"",
+                "- RunAtLoad: true",
+                "+ KeepAlive: true",
+            ]
         );
+
+        let mut changes = Vec::new();
+        diff_plist_values("", &old, &old, &mut changes);
+        assert!(changes.is_empty());
     }
 
     #[test]
-    fn test_parse_string_paths() {
-        let xml = r#"<dict>
-    <key>StandardOutPath</key>
-    <string>/tmp/out.log</string>
-    <key>StandardErrorPath</key>
-    <string>/tmp/err.log</string>
-    <key>WorkingDirectory</key>
-    <string>/tmp</string>
-</dict>"#;
+    fn test_format_plist_changes_json() {
+        let changes = vec![
+            PlistChange {
+                path: "StartInterval".to_string(),
+                kind: ChangeKind::Changed(PlistValue::Integer(60), PlistValue::Integer(300)),
+            },
+            PlistChange {
+                path: "KeepAlive".to_string(),
+                kind: ChangeKind::Removed(PlistValue::Boolean(true)),
+            },
+        ];
+        let json: serde_json::Value = serde_json::from_str(&format_plist_changes(
+            "a.plist",
+            "b.plist",
+            &changes,
+            DiffFormat::Json,
+        ))
+        .unwrap();
+
+        assert_eq!(json["old"], "a.plist");
+        assert_eq!(
+            json["changes"][0],
+            serde_json::json!({"path": "StartInterval", "change": "changed", "old": 60, "new": 300})
+        );
+        assert_eq!(
+            json["changes"][1],
+            serde_json::json!({"path": "KeepAlive", "change": "removed", "old": true})
+        );
+        assert!(
+            format_plist_changes("a", "b", &changes, DiffFormat::Human)
+                .ends_with("0 added, 1 removed, 1 changed\n")
+        );
+    }
 
-        let parsed = parse_plist_xml(xml).unwrap();
-        assert_eq!(parsed.standard_out_path, Some("/tmp/out.log".to_string()));
-        assert_eq!(parsed.standard_error_path, Some("/tmp/err.log".to_string()));
-        assert_eq!(parsed.working_directory, Some("/tmp".to_string()));
+    #[test]
+    fn test_audit_plist_flags_persistence() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("com.evil.agent.plist");
+        fs::write(&path, "").unwrap();
+        let plist = PlistData {
+            label: Some("com.evil.agent".to_string()),
+            program_arguments: Some(vec![
+                "/bin/bash".to_string(),
+                "/tmp/.hidden/payload.sh".to_string(),
+            ]),
+            run_at_load: Some(true),
+            keep_alive: Some(true),
+            ..Default::default()
+        };
+        let rules = |plist: &PlistData, trusted: &[String], now| -> Vec<&'static str> {
+            audit_plist(&path, plist, trusted, now)
+                .iter()
+                .map(|finding| finding.rule)
+                .collect()
+        };
+        let now = std::time::SystemTime::now();
+
+        assert_eq!(
+            rules(&plist, &[], now),
+            [
+                "script-from-temp-dir",
+                "persistent-unknown-vendor",
+                "recently-created"
+            ]
+        );
+        let later = now + std::time::Duration::from_secs(RECENT_AGENT_DAYS * 86_400);
+        assert_eq!(
+            rules(&plist, &["com.evil".to_string()], later),
+            ["script-from-temp-dir"]
+        );
+
+        let dropped = PlistData {
+            program: Some("/private/tmp/.x/agent".to_string()),
+            ..plist.clone()
+        };
+        assert_eq!(
+            rules(&dropped, &["com.evil".to_string()], later),
+            ["program-in-temp-dir", "hidden-program"]
+        );
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o666)).unwrap();
+            assert_eq!(
+                rules(&PlistData::default(), &[], later),
+                ["plist-writable-by-others"]
+            );
+        }
     }
 
     #[test]
-    fn test_example_plist_file() {
-        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
-<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
-<plist version="1.0">
-<dict>
-    <key>Label</key>
-    <string>com.user.price-checker-eth</string>
-    
-    <key>ProgramArguments</key>
-    <array>
-        <string>/Users/dev/.local/bin/price-checker-eth</string>
-    </array>
-    
-    <key>StartInterval</key>
-    <integer>600</integer>
-    
-    <key>RunAtLoad</key>
-    <true/>
-    
-    <key>KeepAlive</key>
-    <false/>
-    
-    <key>StandardOutPath</key>
-    <string>/Users/dev/Documents/github.com/hollanddd/price-checker-eth/logs/price-checker-eth.log</string>
-    
-    <key>StandardErrorPath</key>
-    <string>/Users/dev/Documents/github.com/hollanddd/price-checker-eth/logs/price-checker-eth.error.log</string>
-    
-    <key>WorkingDirectory</key>
-    <string>/Users/dev/Documents/github.com/hollanddd/price-checker-eth</string>
-</dict>
-</plist>"#;
+    fn test_audit_helpers() {
+        assert!(is_interpreter(Path::new("/usr/bin/python3.12")));
+        assert!(is_interpreter(Path::new("/bin/sh")));
+        assert!(!is_interpreter(Path::new("/usr/local/bin/shellcheck")));
+        assert!(is_trusted_vendor("com.apple.Finder", &[]));
+        assert!(!is_trusted_vendor("com.applesauce.agent", &[]));
+        assert!(is_trusted_vendor(
+            "com.example.sync",
+            &["com.example.".to_string()]
+        ));
+
+        let config: Config =
+            toml::from_str("[audit]\ntrusted_vendors = [\"com.example\"]\n").unwrap();
+        assert_eq!(config.audit.trusted_vendors, ["com.example"]);
+    }
 
-        let parsed = parse_plist_xml(xml).unwrap();
+    #[test]
+    fn test_format_audit_json() {
+        let finding = |rule| AuditFinding::new(rule, String::new());
+        let agents = vec![
+            AuditedAgent {
+                location: Some(TabLocation::User),
+                path: PathBuf::from("/a.plist"),
+                label: Some("a".to_string()),
+                program: None,
+                findings: vec![finding("recently-created")],
+            },
+            AuditedAgent {
+                location: None,
+                path: PathBuf::from("/b.plist"),
+                label: None,
+                program: Some("/tmp/b".to_string()),
+                findings: vec![finding("program-in-temp-dir"), finding("hidden-program")],
+            },
+            AuditedAgent {
+                location: Some(TabLocation::Apple),
+                path: PathBuf::from("/c.plist"),
+                label: None,
+                program: None,
+                findings: Vec::new(),
+            },
+        ];
+        let json: serde_json::Value = serde_json::from_str(&format_audit_json(&agents)).unwrap();
 
-        assert_eq!(parsed.label, Some("com.user.price-checker-eth".to_string()));
+        assert_eq!(json["scanned"], 3);
+        assert_eq!(json["flagged"], 2);
         assert_eq!(
-            parsed.program_arguments,
-            Some(vec!["/Users/dev/.local/bin/price-checker-eth".to_string()])
+            json["findings"],
+            serde_json::json!({"high": 1, "medium": 1, "low": 1})
         );
-        assert_eq!(parsed.start_interval, Some(600));
-        assert_eq!(parsed.run_at_load, Some(true));
-        assert_eq!(parsed.keep_alive, Some(false));
-        assert_eq!(parsed.standard_out_path, Some("/Users/dev/Documents/github.com/hollanddd/price-checker-eth/logs/price-checker-eth.log".to_string()));
-        assert_eq!(parsed.standard_error_path, Some("/Users/dev/Documents/github.com/hollanddd/price-checker-eth/logs/price-checker-eth.error.log".to_string()));
+        assert_eq!(json["agents"][0]["path"], "/b.plist");
+        assert_eq!(json["agents"][0]["risk"], "high");
+        assert_eq!(json["agents"][1]["location"], "user");
+    }
+
+    #[test]
+    fn test_snapshot_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let plist = |label: &str| {
+            format!(
+                "<plist><dict><key>Label</key><string>{}</string></dict></plist>",
+                label
+            )
+        };
+        let (kept, edited, removed, added) = (
+            dir.path().join("kept.plist"),
+            dir.path().join("edited.plist"),
+            dir.path().join("removed.plist"),
+            dir.path().join("added.plist"),
+        );
+        fs::write(&kept, plist("kept")).unwrap();
+        fs::write(&edited, plist("edited")).unwrap();
+        fs::write(&removed, plist("removed")).unwrap();
+        fs::write(dir.path().join("notes.txt"), "").unwrap();
+        let mut acknowledged = Snapshot::take(&[dir.path()]);
+        assert_eq!(acknowledged.agents.len(), 3);
+        assert_eq!(acknowledged.agents[&kept].label, None);
+        acknowledged.read_labels();
+        assert_eq!(acknowledged.agents[&kept].label.as_deref(), Some("kept"));
+
+        fs::write(&edited, plist("edited.v2")).unwrap();
+        fs::remove_file(&removed).unwrap();
+        fs::write(&added, plist("added")).unwrap();
+        let current = Snapshot::take(&[dir.path()]);
+
         assert_eq!(
-            parsed.working_directory,
-            Some("/Users/dev/Documents/github.com/hollanddd/price-checker-eth".to_string())
+            current.changes_since(&acknowledged),
+            [
+                (added.clone(), AgentChange::New),
+                (edited.clone(), AgentChange::Changed),
+                (removed.clone(), AgentChange::Removed),
+            ]
+        );
+
+        let mut updated = acknowledged.clone();
+        updated.update(&edited);
+        updated.update(&removed);
+        assert_eq!(current.changes_since(&updated), [(added, AgentChange::New)]);
+
+        assert_eq!(content_hash(b"a"), "af63dc4c8601ec8c");
+        let json = format!("{:#}", serde_json::json!(current));
+        assert_eq!(serde_json::from_str::<Snapshot>(&json).unwrap(), current);
+    }
+
+    #[test]
+    fn test_agent_watcher_reports_plists() {
+        let dir = tempfile::tempdir().unwrap();
+        let watcher = AgentWatcher::new(&[dir.path(), Path::new("/nonexistent/lam")]).unwrap();
+        let plist = dir.path().join("com.user.new.plist");
+        fs::write(dir.path().join("notes.txt"), "").unwrap();
+        fs::write(&plist, "<plist><dict/></plist>").unwrap();
+
+        // Events can arrive late and under the directory's resolved path
+        let canonical = |path: &Path| {
+            fs::canonicalize(path.parent().unwrap())
+                .unwrap()
+                .join(path.file_name().unwrap())
+        };
+        let wait_for_changes = || {
+            let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+            let mut paths = Vec::new();
+            while paths.is_empty() && std::time::Instant::now() < deadline {
+                std::thread::sleep(std::time::Duration::from_millis(20));
+                paths.extend(watcher.changed_plists().iter().map(|path| canonical(path)));
+            }
+            paths
+        };
+        assert_eq!(wait_for_changes(), [canonical(&plist)]);
+
+        fs::remove_file(&plist).unwrap();
+        assert_eq!(wait_for_changes(), [canonical(&plist)]);
+    }
+
+    #[test]
+    fn test_layout_keeps_untouched_entries_verbatim() {
+        let original = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<plist version=\"1.0\">\n<dict>\n\t<!-- Managed by hand -->\n\t<key>Label</key>\n\t<string>com.user.test</string>\n\t<key>ProgramArguments</key>\n\t<array>\n\t\t<string>/bin/sh</string>\n\t\t<string>--name=\"my agent\"</string>\n\t</array>\n\t<key>SoftResourceLimits</key>\n\t<dict>\n\t\t<key>NumberOfFiles</key>\n\t\t<integer>0x400</integer>\n\t</dict>\n\t<key>Token</key>\n\t<data>\n\tAAEC\n\tAwQF\n\t</data>\n\t<key>RunAtLoad</key>\n\t<false/>\n</dict>\n</plist>\n";
+        let layout = PlistLayout::detect(original).unwrap();
+        let mut plist = parse_plist_xml(original).unwrap();
+        assert_eq!(layout.write(&plist.to_value()), original);
+
+        plist.run_at_load = Some(true);
+        let saved = layout.write(&plist.to_value());
+        assert_eq!(saved, original.replace("<false/>", "<true/>"));
+
+        plist
+            .program_arguments
+            .as_mut()
+            .unwrap()
+            .push("say \"hi\" & <bye>".to_string());
+        let saved = layout.write(&plist.to_value());
+        assert!(saved.contains("\t<!-- Managed by hand -->\n\t<key>Label</key>"));
+        assert!(saved.contains("\t\t<string>say \"hi\" &amp; &lt;bye&gt;</string>\n\t</array>"));
+        assert!(saved.contains("<integer>0x400</integer>"));
+        assert!(saved.contains("\t<data>\n\tAAEC\n\tAwQF\n\t</data>"));
+    }
+
+    #[test]
+    fn test_flag_parser_values() {
+        let list = args(&["--format=a=b", "--format", "c", "file=1", "--format"]);
+        let mut parser = FlagParser::new(&list, "usage");
+        assert_eq!(parser.next_arg(), Some("--format"));
+        assert_eq!(parser.value().unwrap(), "a=b");
+        assert_eq!(parser.next_arg(), Some("--format"));
+        assert_eq!(parser.value().unwrap(), "c");
+        assert_eq!(parser.next_arg(), Some("file=1"));
+        assert_eq!(parser.next_arg(), Some("--format"));
+        assert_eq!(
+            parser.value().unwrap_err().to_string(),
+            "--format needs a value\nusage"
+        );
+        assert_eq!(parser.next_arg(), None);
+    }
+
+    #[test]
+    fn test_parse_diff_args() {
+        assert_eq!(
+            parse_diff_args(&args(&["--format=json", "old.plist", "new.plist"])).unwrap(),
+            DiffOptions {
+                format: DiffFormat::Json,
+                old: PathBuf::from("old.plist"),
+                new: PathBuf::from("new.plist"),
+            }
+        );
+        assert_eq!(
+            parse_diff_args(&args(&["a", "--format", "human", "b"]))
+                .unwrap()
+                .format,
+            DiffFormat::Human
+        );
+        assert!(parse_diff_args(&args(&["a"])).is_err());
+        assert!(parse_diff_args(&args(&["a", "b", "c"])).is_err());
+        assert!(parse_diff_args(&args(&["--format", "xml", "a", "b"])).is_err());
+        assert_eq!(
+            parse_diff_args(&args(&["--help"])).unwrap_err().to_string(),
+            DIFF_USAGE
+        );
+    }
+
+    #[test]
+    fn test_audit_file_flags_unreadable_plists() {
+        let dir = tempfile::tempdir().unwrap();
+        let rules = |name: &str, content: &[u8]| -> Vec<&'static str> {
+            let path = dir.path().join(name);
+            fs::write(&path, content).unwrap();
+            audit_file(&path, None, &[])
+                .findings
+                .iter()
+                .map(|finding| finding.rule)
+                .filter(|rule| *rule != "recently-created")
+                .collect()
+        };
+
+        assert_eq!(
+            rules("binary.plist", b"bplist00\xd1\x01\x02"),
+            ["unparseable-plist"]
+        );
+        assert_eq!(
+            rules("broken.plist", b"<plist><dict>"),
+            ["unparseable-plist"]
+        );
+        let conditional = "<plist><dict><key>Label</key><string>com.evil.agent</string><key>RunAtLoad</key><true/><key>KeepAlive</key><dict><key>SuccessfulExit</key><false/></dict></dict></plist>";
+        assert_eq!(
+            rules("conditional.plist", conditional.as_bytes()),
+            ["persistent-unknown-vendor"]
+        );
+    }
+
+    #[test]
+    fn test_layout_writes_reordered_environment_variables() {
+        let original = "<plist version=\"1.0\">\n<dict>\n\t<key>Label</key>\n\t<string>com.user.test</string>\n\t<key>EnvironmentVariables</key>\n\t<dict>\n\t\t<key>A</key>\n\t\t<string>1</string>\n\t\t<key>B</key>\n\t\t<string>2</string>\n\t</dict>\n</dict>\n</plist>\n";
+        let layout = PlistLayout::detect(original).unwrap();
+        let mut plist = parse_plist_xml(original).unwrap();
+        plist.environment_variables.as_mut().unwrap().reverse();
+
+        let saved = layout.write(&plist.to_value());
+        assert_ne!(saved, original);
+        assert_eq!(
+            parse_plist_xml(&saved).unwrap().environment_variables,
+            Some(vec![
+                ("B".to_string(), "2".to_string()),
+                ("A".to_string(), "1".to_string()),
+            ])
+        );
+        assert!(saved.starts_with("<plist version=\"1.0\">\n<dict>\n\t<key>Label</key>\n\t<string>com.user.test</string>\n"));
+    }
+
+    #[test]
+    fn test_backups_are_grouped_by_label_newest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let backups = dir.path().join("backups");
+        let agent = |name: &str, label: &str| {
+            let path = dir.path().join(name);
+            fs::write(
+                &path,
+                format!(
+                    "<plist><dict><key>Label</key><string>{}</string></dict></plist>",
+                    label
+                ),
+            )
+            .unwrap();
+            path
+        };
+
+        let plist = agent("agent.plist", "com.user/agent");
+        let first = backup_plist_in(&backups, &plist).unwrap().unwrap();
+        let second = backup_plist_in(&backups, &plist).unwrap().unwrap();
+        assert_eq!(
+            first.parent(),
+            Some(backups.join("com.user_agent").as_path())
+        );
+        assert_eq!(list_backups_in(&backups, &plist).unwrap(), [second, first]);
+
+        for label in ["..", ".", ""] {
+            let plist = agent("odd.plist", label);
+            assert_eq!(backup_dir_for(&backups, &plist), backups.join("odd"));
+        }
+        assert_eq!(
+            backup_plist_in(&backups, &dir.path().join("missing.plist")).unwrap(),
+            None
         );
     }
 }